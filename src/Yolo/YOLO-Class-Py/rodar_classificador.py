@@ -0,0 +1,99 @@
+import argparse
+import json
+import os
+import sys
+
+from PIL import Image
+from ultralytics import YOLO
+
+
+def ler_excluidas(caminho_lista):
+    # Mesma lista de imagens ignoradas que o app passa para o detector
+    if not caminho_lista or not os.path.isfile(caminho_lista):
+        return set()
+    with open(caminho_lista, encoding='utf-8') as f:
+        return {os.path.abspath(linha.strip()) for linha in f if linha.strip()}
+
+
+def listar_imagens(dir_path, excluidas=frozenset()):
+    return [
+        os.path.join(raiz, nome)
+        for raiz, _, arquivos in sorted(os.walk(dir_path))
+        for nome in sorted(arquivos)
+        if nome.lower().endswith((".jpg", ".jpeg", ".png"))
+        and os.path.abspath(os.path.join(raiz, nome)) not in excluidas
+    ]
+
+
+def probabilidades(res, nomes):
+    return {nomes[i]: float(p) for i, p in enumerate(res.probs.data.tolist())}
+
+
+# Uma classificação por imagem inteira
+def classificar_imagens(model, imagens, imgsz, lote=32):
+    nomes = model.names
+    resultados = []
+    for inicio in range(0, len(imagens), lote):
+        parte = imagens[inicio:inicio + lote]
+        for caminho, res in zip(parte, model.predict(source=parte, imgsz=imgsz, verbose=False)):
+            resultados.append({"path": caminho, "probabilidades": probabilidades(res, nomes), "recortes": []})
+    return resultados
+
+
+# Uma classificação por caixa do detector; a imagem fica com a maior probabilidade de cada classe
+# entre os seus recortes ("a imagem tem uma fissura do tipo X")
+def classificar_recortes(model, imagens, deteccoes_path, imgsz):
+    nomes = model.names
+    with open(deteccoes_path, encoding="utf-8") as f:
+        deteccoes = {os.path.abspath(r["path"]): r for r in json.load(f)}
+    resultados = []
+    for caminho in imagens:
+        caixas = [d["bbox"] for d in deteccoes.get(os.path.abspath(caminho), {}).get("fissura", []) if d.get("bbox")]
+        resultado = {"path": caminho, "probabilidades": {}, "recortes": []}
+        if caixas:
+            with Image.open(caminho) as imagem:
+                imagem = imagem.convert("RGB")
+                recortes = [imagem.crop(tuple(int(round(v)) for v in caixa)) for caixa in caixas]
+            for caixa, res in zip(caixas, model.predict(source=recortes, imgsz=imgsz, verbose=False)):
+                probs = probabilidades(res, nomes)
+                resultado["recortes"].append({"bbox": caixa, "probabilidades": probs})
+                for classe, p in probs.items():
+                    resultado["probabilidades"][classe] = max(p, resultado["probabilidades"].get(classe, 0.0))
+        resultados.append(resultado)
+    return resultados
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser(description="Rodar o modelo YOLO de classificação nas imagens de um projeto.")
+    parser.add_argument("project_dir", type=str, help="Pasta do projeto (as imagens estão em project_dir/images)")
+    parser.add_argument("model_path", type=str, help="Caminho para o modelo de classificação (ex: best.pt)")
+    parser.add_argument("--excluir", type=str, default=None, help="Arquivo com caminhos de imagens a ignorar (um por linha)")
+    parser.add_argument("--modo", choices=["imagem", "recortes"], default="imagem", help="Classificar a imagem inteira ou cada caixa detectada")
+    parser.add_argument("--deteccoes", type=str, default=None, help="detection_results.json com as caixas (modo recortes)")
+    parser.add_argument("--imgsz", type=int, default=224, help="Tamanho de entrada do modelo")
+    args = parser.parse_args()
+
+    image_dir = os.path.join(os.path.abspath(args.project_dir), "images")
+    output_json_path = os.path.join(os.path.abspath(args.project_dir), "classification_results.json")
+    if not os.path.isdir(image_dir):
+        print(f"ERRO: O diretório de imagens não existe: {image_dir}", file=sys.stderr)
+        sys.exit(1)
+    if args.modo == "recortes" and not (args.deteccoes and os.path.exists(args.deteccoes)):
+        print("ERRO: O modo recortes precisa do arquivo de detecções (--deteccoes)", file=sys.stderr)
+        sys.exit(1)
+
+    print(f"Carregando modelo de classificação de: {args.model_path}", file=sys.stderr)
+    model = YOLO(args.model_path)
+    imagens = listar_imagens(image_dir, ler_excluidas(args.excluir))
+    print(f"Classificando {len(imagens)} imagem(ns) no modo {args.modo}.", file=sys.stderr)
+
+    if args.modo == "recortes":
+        resultados = classificar_recortes(model, imagens, args.deteccoes, args.imgsz)
+    else:
+        resultados = classificar_imagens(model, imagens, args.imgsz)
+
+    with open(output_json_path, "w", encoding="utf-8") as f:
+        json.dump(resultados, f, indent=4, ensure_ascii=False)
+    print(f"Resultados da classificação salvos em: {output_json_path}", file=sys.stderr)
+
+    print(json.dumps(resultados, indent=4, ensure_ascii=False))
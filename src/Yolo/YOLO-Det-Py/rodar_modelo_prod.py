@@ -5,9 +5,140 @@ from collections import defaultdict
 import os # Ensure os is imported
 import sys # Import sys for stderr
 
-def rodar_modelo(dir_path, model_path):
+def ler_excluidas(caminho_lista):
+    # Lista de imagens (uma por linha) que o app pediu para ignorar, ex: duplicatas
+    if not caminho_lista or not os.path.isfile(caminho_lista):
+        return set()
+    with open(caminho_lista, encoding='utf-8') as f:
+        return {os.path.abspath(linha.strip()) for linha in f if linha.strip()}
+
+def listar_imagens(dir_path, excluidas=frozenset()):
+    return [
+        os.path.join(raiz, nome)
+        for raiz, _, arquivos in sorted(os.walk(dir_path))
+        for nome in sorted(arquivos)
+        if nome.lower().endswith((".jpg", ".jpeg", ".png"))
+        and os.path.abspath(os.path.join(raiz, nome)) not in excluidas
+    ]
+
+def gerar_tiles(largura, altura, tamanho, sobreposicao):
+    # Janelas (x1, y1, x2, y2) de `tamanho` px com a sobreposição pedida; a última de cada eixo
+    # encosta na borda para cobrir a imagem inteira
+    passo = max(1, int(tamanho * (1 - sobreposicao)))
+
+    def inicios(total):
+        if total <= tamanho:
+            return [0]
+        valores = list(range(0, total - tamanho + 1, passo))
+        if valores[-1] + tamanho < total:
+            valores.append(total - tamanho)
+        return valores
+
+    return [
+        (x, y, min(x + tamanho, largura), min(y + tamanho, altura))
+        for y in inicios(altura)
+        for x in inicios(largura)
+    ]
+
+def area(caixa):
+    return max(0.0, caixa[2] - caixa[0]) * max(0.0, caixa[3] - caixa[1])
+
+def intersecao(a, b):
+    return area([max(a[0], b[0]), max(a[1], b[1]), min(a[2], b[2]), min(a[3], b[3])])
+
+def faixa_compartilhada(tile_a, tile_b):
+    # Região comum a dois tiles (a faixa de sobreposição entre eles), ou None se não se tocam
+    faixa = [max(tile_a[0], tile_b[0]), max(tile_a[1], tile_b[1]), min(tile_a[2], tile_b[2]), min(tile_a[3], tile_b[3])]
+    return faixa if area(faixa) > 0 else None
+
+def na_faixa(caixa, faixa):
+    # A caixa toca a faixa (inclusive só encostando na borda, caso de fissuras cortadas na emenda)
+    return caixa[0] <= faixa[2] and caixa[2] >= faixa[0] and caixa[1] <= faixa[3] and caixa[3] >= faixa[1]
+
+def mesclar_caixas(caixas, iou_minimo=0.5, cobertura_minima=0.6):
+    # Une caixas da mesma classe vindas de tiles diferentes que se repetem na faixa de sobreposição
+    # entre eles ou que foram cortadas na emenda (uma cobre a maior parte da outra, ou IoU alto).
+    # Caixas do mesmo tile já passaram pelo NMS do modelo e nunca são unidas. Mantém a maior confiança.
+    mescladas = []
+    for caixa in sorted(caixas, key=lambda c: c["confidence"], reverse=True):
+        for existente in mescladas:
+            if existente["cls"] != caixa["cls"] or caixa["tile"] in existente["tiles"]:
+                continue
+            faixas = [faixa_compartilhada(tile, caixa["tile"]) for tile in existente["tiles"]]
+            if not any(f and na_faixa(existente["bbox"], f) and na_faixa(caixa["bbox"], f) for f in faixas):
+                continue
+            inter = intersecao(existente["bbox"], caixa["bbox"])
+            uniao = area(existente["bbox"]) + area(caixa["bbox"]) - inter
+            menor = min(area(existente["bbox"]), area(caixa["bbox"]))
+            if (uniao > 0 and inter / uniao >= iou_minimo) or (menor > 0 and inter / menor >= cobertura_minima):
+                a, b = existente["bbox"], caixa["bbox"]
+                existente["bbox"] = [min(a[0], b[0]), min(a[1], b[1]), max(a[2], b[2]), max(a[3], b[3])]
+                existente["tiles"].append(caixa["tile"])
+                break
+        else:
+            mescladas.append(dict(caixa, tiles=[caixa["tile"]]))
+    return mescladas
+
+def rodar_modelo_ladrilhado(model, imagens, label_to_name, tamanho, sobreposicao, imgsz=None, lote=16):
+    import cv2
+
+    # Sem --imgsz cada tile entra no modelo no próprio tamanho, sem redimensionar
+    imgsz = imgsz or tamanho
+    print(f"Modo ladrilhado: tiles de {tamanho}px com {sobreposicao:.0%} de sobreposição (entrada do modelo: {imgsz}px).", file=sys.stderr)
+    resultados_imagens = []
+    for img_path in imagens:
+        imagem = cv2.imread(img_path)
+        if imagem is None:
+            print(f"Aviso: não foi possível ler a imagem {img_path}.", file=sys.stderr)
+            continue
+        altura, largura = imagem.shape[:2]
+        tiles = gerar_tiles(largura, altura, tamanho, sobreposicao)
+
+        caixas = []
+        for inicio in range(0, len(tiles), lote):
+            janelas = tiles[inicio:inicio + lote]
+            recortes = [imagem[y1:y2, x1:x2] for (x1, y1, x2, y2) in janelas]
+            for janela, res in zip(janelas, model.predict(source=recortes, imgsz=imgsz, verbose=False)):
+                x1, y1 = janela[0], janela[1]
+                if res.boxes is None:
+                    continue
+                for xyxy, cls, conf in zip(res.boxes.xyxy.tolist(), res.boxes.cls.tolist(), res.boxes.conf.tolist()):
+                    caixas.append({
+                        "cls": int(cls),
+                        "confidence": float(conf),
+                        "bbox": [xyxy[0] + x1, xyxy[1] + y1, xyxy[2] + x1, xyxy[3] + y1],
+                        "tile": janela,
+                    })
+
+        resultados_imagens.append({
+            "path": img_path,
+            "width": largura,
+            "height": altura,
+            "fissura": [
+                {"name": nome_classe(label_to_name, c["cls"]), "confidence": c["confidence"], "bbox": c["bbox"]}
+                for c in mesclar_caixas(caixas)
+            ],
+        })
+    return resultados_imagens
+
+# Mapa índice -> chave da taxonomia do modelo, no formato "0=retracao,1=termica"
+def ler_classes(argumento):
+    if not argumento:
+        return {0: "retracao", 1: "termica"}
+    classes = {}
+    for item in argumento.split(","):
+        indice, chave = item.split("=", 1)
+        classes[int(indice)] = chave.strip()
+    return classes
+
+def nome_classe(label_to_name, cls):
+    # Índice fora da taxonomia: mantém a detecção com um nome identificável em vez de falhar
+    return label_to_name.get(int(cls), f"classe_{int(cls)}")
+
+def rodar_modelo(dir_path, model_path, excluidas=frozenset(), tile_size=0, tile_overlap=0.2, imgsz=None, label_to_name=None):
     print(f"Carregando modelo de: {model_path}", file=sys.stderr)
-    label_to_name = {0: "retracao", 1: "termica"}
+    if label_to_name is None:
+        label_to_name = ler_classes(None)
 
     model = YOLO(model_path)
 
@@ -17,8 +148,20 @@ def rodar_modelo(dir_path, model_path):
     print(f"Rodando predição com o padrão glob para busca recursiva: {glob_pattern}", file=sys.stderr)
     print(f"Diretório base sendo pesquisado (caminho absoluto): {os.path.abspath(dir_path)}", file=sys.stderr)
 
+    source = glob_pattern
+    if excluidas or tile_size > 0:
+        source = listar_imagens(dir_path, excluidas)
+        print(f"{len(excluidas)} imagem(ns) excluída(s) da detecção; {len(source)} a processar.", file=sys.stderr)
+        if not source:
+            return []
+
+    # Imagens de alta resolução: detecção por tiles para não perder fissuras finas no redimensionamento
+    if tile_size > 0:
+        return rodar_modelo_ladrilhado(model, source, label_to_name, tile_size, tile_overlap, imgsz)
+
     # Attempt to suppress Ultralytics' own stdout logging
-    results = model.predict(source=glob_pattern, verbose=False)
+    predict_kwargs = {"imgsz": imgsz} if imgsz else {}
+    results = model.predict(source=source, verbose=False, **predict_kwargs)
 
     # dicionário intermediário: chave = caminho, valor = lista de fissuras
     per_image = defaultdict(lambda: {"path": None, "fissura": []})
@@ -31,11 +174,12 @@ def rodar_modelo(dir_path, model_path):
                 img_path = res.path
                 if per_image[img_path]["path"] is None:
                     per_image[img_path]["path"] = img_path
+                    per_image[img_path]["height"], per_image[img_path]["width"] = res.orig_shape[:2]
 
-                # iterar pareando classe e confiança corretamente
-                for cls, conf in zip(res.boxes.cls.tolist(), res.boxes.conf.tolist()):
+                # iterar pareando classe, confiança e caixa (pixels da imagem original) corretamente
+                for cls, conf, xyxy in zip(res.boxes.cls.tolist(), res.boxes.conf.tolist(), res.boxes.xyxy.tolist()):
                     per_image[img_path]["fissura"].append(
-                        {"name": label_to_name[int(cls)], "confidence": float(conf)}
+                        {"name": nome_classe(label_to_name, cls), "confidence": float(conf), "bbox": xyxy}
                     )
             else:
                 # Handle cases where a result object might not have detections (e.g. non-image file processed by glob)
@@ -56,6 +200,12 @@ if __name__ == "__main__":
     parser = argparse.ArgumentParser(description="Rodar modelo YOLO em um projeto específico.")
     parser.add_argument("project_name", type=str, help="Nome do projeto (a pasta de imagens estará em Projects/project_name/images)")
     parser.add_argument("model_path", type=str, help="Caminho para o arquivo do modelo (ex: best.pt)")
+    parser.add_argument("--excluir", type=str, default=None, help="Arquivo com caminhos de imagens a ignorar (um por linha)")
+    parser.add_argument("--tile-size", type=int, default=0, help="Tamanho dos tiles em pixels (0 = imagem inteira)")
+    parser.add_argument("--tile-overlap", type=float, default=0.2, help="Sobreposição entre tiles (fração, ex: 0.2)")
+    parser.add_argument("--modelo-id", type=str, default=None, help="Id do modelo no registro do app, gravado em cada resultado")
+    parser.add_argument("--imgsz", type=int, default=None, help="Tamanho de entrada do modelo (padrão: o do treino)")
+    parser.add_argument("--classes", type=str, default=None, help="Taxonomia do modelo: índice=chave separados por vírgula (ex: 0=retracao,1=termica)")
     args = parser.parse_args()
 
     # Assuming the script CWD when run by Rust is 'src/app-rust/'
@@ -85,7 +235,12 @@ if __name__ == "__main__":
         print("[]") 
         exit(1)
 
-    results_list = rodar_modelo(abs_image_dir, args.model_path) # Pass absolute path here
+    results_list = rodar_modelo(abs_image_dir, args.model_path, ler_excluidas(args.excluir), args.tile_size, args.tile_overlap, args.imgsz, ler_classes(args.classes)) # Pass absolute path here
+
+    # Cada resultado registra o modelo que o gerou, para o app não misturar versões
+    if args.modelo_id:
+        for resultado in results_list:
+            resultado["modelo"] = args.modelo_id
     
     # Save the results to a JSON file
     try:
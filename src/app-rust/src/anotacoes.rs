@@ -0,0 +1,413 @@
+// anotacoes.rs
+// Importa anotações feitas fora do app (sessões do labelImg, datasets YOLO ou COCO) como
+// referência (ground truth) das imagens do projeto. As caixas ficam no índice, junto da imagem,
+// e são exibidas ao lado das detecções do modelo.
+//
+// Formatos aceitos:
+// - YOLO: um .txt por imagem ("classe cx cy w h" normalizados), nomes das classes em
+//   classes.txt ou data.yaml/dataset.yaml na mesma pasta ou acima
+// - Pascal VOC: um .xml por imagem (formato do labelImg)
+// - COCO: um .json com images, annotations e categories
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::collections::{HashMap, BTreeMap};
+use anyhow::{Result, Context, bail};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use walkdir::WalkDir;
+use crate::image_processor::{ImageMetadata, sanitize_filename};
+use crate::project_index::ProjectIndex;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FormatoAnotacao {
+    Yolo,
+    Voc,
+    Coco,
+}
+
+impl std::fmt::Display for FormatoAnotacao {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatoAnotacao::Yolo => write!(f, "YOLO"),
+            FormatoAnotacao::Voc => write!(f, "Pascal VOC"),
+            FormatoAnotacao::Coco => write!(f, "COCO"),
+        }
+    }
+}
+
+// Caixa de referência de uma imagem, em pixels da imagem original
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnotacaoReferencia {
+    pub classe: String,
+    pub bbox: [f64; 4], // [x1, y1, x2, y2]
+    pub formato: FormatoAnotacao,
+    pub arquivo: String, // Arquivo de onde a anotação veio
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResumoImportacao {
+    pub imagens_anotadas: usize,
+    pub caixas: usize,
+    pub nao_encontradas: Vec<String>, // Imagens anotadas que não estão no projeto
+    pub ambiguas: Vec<String>, // Nomes que existem em mais de uma fachada
+    pub erros: Vec<String>,
+}
+
+impl ResumoImportacao {
+    pub fn descricao(&self) -> String {
+        let mut texto = format!("{} caixa(s) importada(s) em {} imagem(ns).", self.caixas, self.imagens_anotadas);
+        if !self.nao_encontradas.is_empty() {
+            texto.push_str(&format!(" {} imagem(ns) anotada(s) não pertencem ao projeto.", self.nao_encontradas.len()));
+        }
+        if !self.ambiguas.is_empty() {
+            texto.push_str(&format!(" Ignoradas por nome repetido em mais de uma fachada: {}.", self.ambiguas.join(", ")));
+        }
+        if !self.erros.is_empty() {
+            texto.push_str(&format!(" Erros: {}", self.erros.join("; ")));
+        }
+        texto
+    }
+}
+
+// Caixa como está no arquivo: YOLO usa coordenadas normalizadas, que dependem do tamanho da imagem
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CaixaLida {
+    Normalizada([f64; 4]), // [cx, cy, w, h] em 0..1
+    Pixels([f64; 4]), // [x1, y1, x2, y2]
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AnotacaoLida {
+    imagem: String, // Nome do arquivo de imagem (ou só o nome-base, no YOLO)
+    classe: String,
+    caixa: CaixaLida,
+    formato: FormatoAnotacao,
+    arquivo: String,
+}
+
+// Nomes de classe sem acento e em minúsculas, para casar "Térmica" com "termica"
+pub fn normalizar_classe(classe: &str) -> String {
+    classe
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' => 'a',
+            'é' | 'ê' => 'e',
+            'í' => 'i',
+            'ó' | 'ô' | 'õ' => 'o',
+            'ú' | 'ü' => 'u',
+            'ç' => 'c',
+            ' ' => '_',
+            outro => outro,
+        })
+        .collect()
+}
+
+fn decodificar_xml(texto: &str) -> String {
+    texto.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn nome_arquivo(caminho: &str) -> String {
+    caminho.rsplit(['/', '\\']).next().unwrap_or(caminho).to_string()
+}
+
+// Nomes das classes YOLO: classes.txt (labelImg) ou o bloco names: do data.yaml (ultralytics)
+fn ler_classes_yolo(dir: &Path) -> Option<Vec<String>> {
+    let classes_txt = dir.join("classes.txt");
+    if let Ok(content) = fs::read_to_string(&classes_txt) {
+        return Some(content.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect());
+    }
+    let re_item = Regex::new(r"^\s+(\d+)\s*:\s*['\x22]?([^'\x22#]+?)['\x22]?\s*(#.*)?$").unwrap();
+    let re_lista = Regex::new(r"^names\s*:\s*\[(.*)\]").unwrap();
+    for nome in ["data.yaml", "dataset.yaml"] {
+        let Ok(content) = fs::read_to_string(dir.join(nome)) else { continue };
+        let mut classes: BTreeMap<usize, String> = BTreeMap::new();
+        let mut em_names = false;
+        for linha in content.lines() {
+            if let Some(lista) = re_lista.captures(linha) {
+                return Some(lista[1].split(',').map(|c| c.trim().trim_matches(['\'', '"']).to_string()).collect());
+            }
+            if linha.trim_start().starts_with("names") {
+                em_names = true;
+                continue;
+            }
+            if em_names {
+                match re_item.captures(linha) {
+                    Some(item) => { classes.insert(item[1].parse().unwrap_or(0), item[2].trim().to_string()); }
+                    None if linha.trim().is_empty() => {}
+                    None => em_names = false,
+                }
+            }
+        }
+        if !classes.is_empty() {
+            return Some(classes.into_values().collect());
+        }
+    }
+    None
+}
+
+// Procura a lista de classes da pasta do rótulo até a raiz da importação (labels/train -> dataset)
+fn classes_yolo_para(arquivo: &Path, raiz: &Path, cache: &mut HashMap<PathBuf, Option<Vec<String>>>) -> Option<Vec<String>> {
+    let mut dir = arquivo.parent();
+    while let Some(atual) = dir {
+        let classes = cache.entry(atual.to_path_buf()).or_insert_with(|| ler_classes_yolo(atual)).clone();
+        if classes.is_some() {
+            return classes;
+        }
+        if atual == raiz {
+            break;
+        }
+        dir = atual.parent();
+    }
+    None
+}
+
+fn ler_yolo(arquivo: &Path, classes: Option<&[String]>) -> Result<Vec<AnotacaoLida>> {
+    let content = fs::read_to_string(arquivo)
+        .with_context(|| format!("Falha ao ler {}", arquivo.display()))?;
+    let imagem = arquivo.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let mut anotacoes = Vec::new();
+    for (numero, linha) in content.lines().enumerate() {
+        let campos: Vec<&str> = linha.split_whitespace().collect();
+        if campos.is_empty() {
+            continue;
+        }
+        let valores: Vec<f64> = campos.iter().skip(1).take(4).filter_map(|v| v.parse().ok()).collect();
+        let (Ok(id), [cx, cy, w, h]) = (campos[0].parse::<usize>(), valores.as_slice()) else {
+            bail!("{}: linha {} inválida", arquivo.display(), numero + 1);
+        };
+        let classe = classes.and_then(|c| c.get(id)).cloned().unwrap_or_else(|| id.to_string());
+        anotacoes.push(AnotacaoLida {
+            imagem: imagem.clone(),
+            classe,
+            caixa: CaixaLida::Normalizada([*cx, *cy, *w, *h]),
+            formato: FormatoAnotacao::Yolo,
+            arquivo: arquivo.display().to_string(),
+        });
+    }
+    Ok(anotacoes)
+}
+
+fn ler_voc(arquivo: &Path) -> Result<Vec<AnotacaoLida>> {
+    let content = fs::read_to_string(arquivo)
+        .with_context(|| format!("Falha ao ler {}", arquivo.display()))?;
+    let re_filename = Regex::new(r"<filename>\s*(.*?)\s*</filename>").unwrap();
+    let re_object = Regex::new(r"(?s)<object>(.*?)</object>").unwrap();
+    let re_name = Regex::new(r"<name>\s*(.*?)\s*</name>").unwrap();
+    let re_coord = |tag: &str| Regex::new(&format!(r"<{0}>\s*(-?[\d.]+)\s*</{0}>", tag)).unwrap();
+    let (re_xmin, re_ymin, re_xmax, re_ymax) = (re_coord("xmin"), re_coord("ymin"), re_coord("xmax"), re_coord("ymax"));
+
+    if !content.contains("<annotation") {
+        bail!("{} não é uma anotação Pascal VOC", arquivo.display());
+    }
+    // Sem <filename>, o labelImg usa o mesmo nome-base do XML
+    let imagem = re_filename
+        .captures(&content)
+        .map(|c| nome_arquivo(&decodificar_xml(&c[1])))
+        .unwrap_or_else(|| arquivo.file_stem().unwrap_or_default().to_string_lossy().into_owned());
+
+    let mut anotacoes = Vec::new();
+    for objeto in re_object.captures_iter(&content) {
+        let corpo = &objeto[1];
+        let coord = |re: &Regex| re.captures(corpo).and_then(|c| c[1].parse::<f64>().ok());
+        let (Some(classe), Some(x1), Some(y1), Some(x2), Some(y2)) = (
+            re_name.captures(corpo).map(|c| decodificar_xml(&c[1])),
+            coord(&re_xmin),
+            coord(&re_ymin),
+            coord(&re_xmax),
+            coord(&re_ymax),
+        ) else {
+            bail!("{}: objeto sem nome ou sem bndbox", arquivo.display());
+        };
+        anotacoes.push(AnotacaoLida {
+            imagem: imagem.clone(),
+            classe,
+            caixa: CaixaLida::Pixels([x1, y1, x2, y2]),
+            formato: FormatoAnotacao::Voc,
+            arquivo: arquivo.display().to_string(),
+        });
+    }
+    Ok(anotacoes)
+}
+
+#[derive(Deserialize)]
+struct CocoImagem {
+    id: u64,
+    file_name: String,
+}
+
+#[derive(Deserialize)]
+struct CocoAnotacao {
+    image_id: u64,
+    category_id: u64,
+    bbox: [f64; 4], // [x, y, largura, altura]
+}
+
+#[derive(Deserialize)]
+struct CocoCategoria {
+    id: u64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CocoDataset {
+    images: Vec<CocoImagem>,
+    annotations: Vec<CocoAnotacao>,
+    #[serde(default)]
+    categories: Vec<CocoCategoria>,
+}
+
+fn ler_coco(arquivo: &Path) -> Result<Vec<AnotacaoLida>> {
+    let content = fs::read_to_string(arquivo)
+        .with_context(|| format!("Falha ao ler {}", arquivo.display()))?;
+    let dataset: CocoDataset = serde_json::from_str(&content)
+        .with_context(|| format!("{} não é um arquivo COCO válido", arquivo.display()))?;
+    let imagens: HashMap<u64, String> = dataset.images.into_iter().map(|im| (im.id, nome_arquivo(&im.file_name))).collect();
+    let categorias: HashMap<u64, String> = dataset.categories.into_iter().map(|c| (c.id, c.name)).collect();
+
+    let mut anotacoes = Vec::new();
+    for anotacao in dataset.annotations {
+        let Some(imagem) = imagens.get(&anotacao.image_id) else { continue };
+        let [x, y, w, h] = anotacao.bbox;
+        anotacoes.push(AnotacaoLida {
+            imagem: imagem.clone(),
+            classe: categorias.get(&anotacao.category_id).cloned().unwrap_or_else(|| anotacao.category_id.to_string()),
+            caixa: CaixaLida::Pixels([x, y, x + w, y + h]),
+            formato: FormatoAnotacao::Coco,
+            arquivo: arquivo.display().to_string(),
+        });
+    }
+    Ok(anotacoes)
+}
+
+// Lê todos os arquivos de anotação de um arquivo ou pasta
+fn ler_origem(origem: &Path, resumo: &mut ResumoImportacao) -> Vec<AnotacaoLida> {
+    let arquivos: Vec<PathBuf> = if origem.is_dir() {
+        WalkDir::new(origem)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        vec![origem.to_path_buf()]
+    };
+    let raiz = if origem.is_dir() { origem } else { origem.parent().unwrap_or(origem) };
+
+    let mut cache_classes = HashMap::new();
+    let mut anotacoes = Vec::new();
+    for arquivo in arquivos {
+        let nome = arquivo.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+        let extensao = arquivo.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+        let lidas = match extensao.as_str() {
+            "txt" if nome != "classes.txt" => {
+                let classes = classes_yolo_para(&arquivo, raiz, &mut cache_classes);
+                ler_yolo(&arquivo, classes.as_deref())
+            }
+            "xml" => ler_voc(&arquivo),
+            "json" => ler_coco(&arquivo),
+            _ => continue,
+        };
+        match lidas {
+            Ok(lidas) => anotacoes.extend(lidas),
+            Err(e) => resumo.erros.push(format!("{:#}", e)),
+        }
+    }
+    anotacoes
+}
+
+// Acha a imagem do projeto pelo nome do arquivo (já sanitizado na ingestão) ou, no YOLO, pelo nome-base
+fn localizar_imagem<'a>(candidatas: &'a [(String, String)], imagem: &str, formato: FormatoAnotacao) -> Vec<&'a str> {
+    let sanitizado = sanitize_filename(imagem);
+    candidatas
+        .iter()
+        .filter(|(file_name, stem)| match formato {
+            FormatoAnotacao::Yolo => *stem == sanitizado,
+            _ => *file_name == sanitizado,
+        })
+        .map(|(file_name, _)| file_name.as_str())
+        .collect()
+}
+
+fn dimensoes(imagem: &ImageMetadata) -> Option<(f64, f64)> {
+    match &imagem.qualidade {
+        Some(q) => Some((q.largura as f64, q.altura as f64)),
+        None => image::image_dimensions(&imagem.path).ok().map(|(w, h)| (w as f64, h as f64)),
+    }
+}
+
+fn para_pixels(caixa: CaixaLida, imagem: &ImageMetadata) -> Option<[f64; 4]> {
+    match caixa {
+        CaixaLida::Pixels(bbox) => Some(bbox),
+        CaixaLida::Normalizada([cx, cy, w, h]) => {
+            let (largura, altura) = dimensoes(imagem)?;
+            Some([(cx - w / 2.0) * largura, (cy - h / 2.0) * altura, (cx + w / 2.0) * largura, (cy + h / 2.0) * altura])
+        }
+    }
+}
+
+// Importa as anotações para o índice. Imagens que recebem anotações nesta importação têm as
+// anotações anteriores substituídas, então reimportar a mesma pasta não duplica caixas.
+pub fn importar(project_dir: &Path, origem: &Path) -> Result<ResumoImportacao> {
+    let mut resumo = ResumoImportacao::default();
+    let lidas = ler_origem(origem, &mut resumo);
+    if lidas.is_empty() && resumo.erros.is_empty() {
+        bail!("Nenhum arquivo de anotação (YOLO .txt, Pascal VOC .xml ou COCO .json) encontrado em {}", origem.display());
+    }
+
+    let mut index = ProjectIndex::abrir(project_dir)?;
+    let candidatas: Vec<(String, String)> = index.predios
+        .iter()
+        .flat_map(|p| p.fachadas.values())
+        .flat_map(|f| f.imagens.iter())
+        .map(|im| {
+            let stem = Path::new(&im.file_name).file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            (im.file_name.clone(), stem)
+        })
+        .collect();
+
+    let mut por_imagem: BTreeMap<String, Vec<AnotacaoLida>> = BTreeMap::new();
+    for anotacao in lidas {
+        match localizar_imagem(&candidatas, &anotacao.imagem, anotacao.formato).as_slice() {
+            [file_name] => por_imagem.entry(file_name.to_string()).or_default().push(anotacao),
+            [] => {
+                if !resumo.nao_encontradas.contains(&anotacao.imagem) {
+                    resumo.nao_encontradas.push(anotacao.imagem);
+                }
+            }
+            _ => {
+                if !resumo.ambiguas.contains(&anotacao.imagem) {
+                    resumo.ambiguas.push(anotacao.imagem);
+                }
+            }
+        }
+    }
+
+    for imagem in index.predios.iter_mut().flat_map(|p| p.fachadas.values_mut()).flat_map(|f| f.imagens.iter_mut()) {
+        let Some(anotacoes) = por_imagem.get(&imagem.file_name) else { continue };
+        let mut convertidas = Vec::new();
+        for anotacao in anotacoes {
+            match para_pixels(anotacao.caixa, imagem) {
+                Some(bbox) => convertidas.push(AnotacaoReferencia {
+                    classe: normalizar_classe(&anotacao.classe),
+                    bbox,
+                    formato: anotacao.formato,
+                    arquivo: anotacao.arquivo.clone(),
+                }),
+                None => resumo.erros.push(format!("Não foi possível ler as dimensões de {}", imagem.path.display())),
+            }
+        }
+        resumo.imagens_anotadas += 1;
+        resumo.caixas += convertidas.len();
+        imagem.anotacoes = convertidas;
+    }
+
+    index.save(project_dir)?;
+    Ok(resumo)
+}
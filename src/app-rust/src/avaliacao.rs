@@ -0,0 +1,258 @@
+// avaliacao.rs
+// Avaliação do modelo contra as anotações de referência importadas (ver anotacoes.rs).
+// Só entram imagens do projeto com anotações e que não foram excluídas pelo índice.
+//
+// - Precisão, recall e F1 por classe usam as detecções filtradas pela política do projeto,
+//   ou seja, medem o modelo no ponto de operação usado nos relatórios.
+// - AP@0.5 usa todas as detecções brutas (curva precisão x recall completa, interpolação em
+//   todos os pontos); o mAP@0.5 é a média entre as classes com anotações.
+// - A matriz de confusão casa cada detecção filtrada com a anotação de maior IoU (>= 0.5),
+//   independente da classe; o que sobra vai para a linha/coluna "fundo".
+use std::path::Path;
+use std::collections::BTreeSet;
+use anyhow::{Result, Context};
+use crate::anotacoes::{AnotacaoReferencia, normalizar_classe};
+use crate::deteccoes;
+use crate::manual_processor::{ImageAnalysisResult, FissuraData};
+use crate::project_index::ProjectIndex;
+use crate::project_settings::PoliticaDeteccao;
+
+pub const IOU_MINIMO: f64 = 0.5;
+pub const ROTULO_FUNDO: &str = "fundo";
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MetricasClasse {
+    pub classe: String,
+    pub anotacoes: usize,
+    pub verdadeiros_positivos: usize,
+    pub falsos_positivos: usize,
+    pub falsos_negativos: usize,
+    pub precisao: f64,
+    pub recall: f64,
+    pub f1: f64,
+    pub ap50: f64,
+}
+
+// Linhas: classe anotada; colunas: classe detectada. A última linha/coluna é o fundo.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MatrizConfusao {
+    pub rotulos: Vec<String>,
+    pub valores: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Avaliacao {
+    pub imagens_avaliadas: usize,
+    pub classes: Vec<MetricasClasse>,
+    pub map50: f64,
+    pub matriz: MatrizConfusao,
+}
+
+// Par imagem/anotações pronto para avaliar
+struct Amostra<'a> {
+    referencia: &'a [AnotacaoReferencia],
+    brutas: Vec<FissuraData>,
+    filtradas: Vec<FissuraData>,
+}
+
+fn iou(a: &[f64; 4], b: &[f64; 4]) -> f64 {
+    let area = |c: &[f64; 4]| (c[2] - c[0]).max(0.0) * (c[3] - c[1]).max(0.0);
+    let inter = area(&[a[0].max(b[0]), a[1].max(b[1]), a[2].min(b[2]), a[3].min(b[3])]);
+    let uniao = area(a) + area(b) - inter;
+    if uniao > 0.0 { inter / uniao } else { 0.0 }
+}
+
+// Detecções com a classe no mesmo formato das anotações; sem caixa não dá para avaliar
+fn normalizar(fissuras: &[FissuraData]) -> Vec<FissuraData> {
+    fissuras
+        .iter()
+        .filter(|f| f.bbox.is_some())
+        .map(|f| FissuraData { name: normalizar_classe(&f.name), ..f.clone() })
+        .collect()
+}
+
+// Casamento guloso por confiança, como no VOC/COCO: cada anotação aceita uma única detecção.
+// Devolve as detecções em ordem de confiança decrescente com a anotação casada, se houver.
+fn casar<'a, R: std::borrow::Borrow<AnotacaoReferencia>>(deteccoes: &[&'a FissuraData], referencia: &[R]) -> Vec<(&'a FissuraData, Option<usize>)> {
+    let mut ordenadas: Vec<&FissuraData> = deteccoes.to_vec();
+    ordenadas.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut usadas = vec![false; referencia.len()];
+    ordenadas
+        .into_iter()
+        .map(|deteccao| {
+            let caixa = deteccao.bbox.unwrap_or_default();
+            let melhor = referencia
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !usadas[*i])
+                .map(|(i, r)| (i, iou(&caixa, &r.borrow().bbox)))
+                .filter(|(_, valor)| *valor >= IOU_MINIMO)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i);
+            if let Some(i) = melhor {
+                usadas[i] = true;
+            }
+            (deteccao, melhor)
+        })
+        .collect()
+}
+
+// Área sob a curva precisão x recall com precisão interpolada (envelope monotônico)
+fn average_precision(mut resultados: Vec<(f64, bool)>, total_anotacoes: usize) -> f64 {
+    if total_anotacoes == 0 {
+        return 0.0;
+    }
+    resultados.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut pontos: Vec<(f64, f64)> = Vec::with_capacity(resultados.len()); // (recall, precisão)
+    let (mut vp, mut fp) = (0usize, 0usize);
+    for (_, verdadeiro) in resultados {
+        if verdadeiro { vp += 1 } else { fp += 1 }
+        pontos.push((vp as f64 / total_anotacoes as f64, vp as f64 / (vp + fp) as f64));
+    }
+    let mut ap = 0.0;
+    let mut recall_anterior = 0.0;
+    for i in 0..pontos.len() {
+        let precisao_max = pontos[i..].iter().map(|p| p.1).fold(0.0, f64::max);
+        ap += (pontos[i].0 - recall_anterior) * precisao_max;
+        recall_anterior = pontos[i].0;
+    }
+    ap
+}
+
+fn razao(a: usize, b: usize) -> f64 {
+    if b == 0 { 0.0 } else { a as f64 / b as f64 }
+}
+
+fn matriz_confusao(amostras: &[Amostra], classes: &[String]) -> MatrizConfusao {
+    let mut rotulos = classes.to_vec();
+    rotulos.push(ROTULO_FUNDO.to_string());
+    let fundo = classes.len();
+    let posicao = |classe: &str| classes.iter().position(|c| c == classe).unwrap_or(fundo);
+    let mut valores = vec![vec![0usize; rotulos.len()]; rotulos.len()];
+
+    for amostra in amostras {
+        let deteccoes: Vec<&FissuraData> = amostra.filtradas.iter().collect();
+        let casadas = casar(&deteccoes, amostra.referencia);
+        let mut usadas = vec![false; amostra.referencia.len()];
+        for (deteccao, anotacao) in casadas {
+            match anotacao {
+                Some(i) => {
+                    usadas[i] = true;
+                    valores[posicao(&amostra.referencia[i].classe)][posicao(&deteccao.name)] += 1;
+                }
+                None => valores[fundo][posicao(&deteccao.name)] += 1,
+            }
+        }
+        for (i, anotacao) in amostra.referencia.iter().enumerate() {
+            if !usadas[i] {
+                valores[posicao(&anotacao.classe)][fundo] += 1;
+            }
+        }
+    }
+    MatrizConfusao { rotulos, valores }
+}
+
+pub fn avaliar(brutas: &[ImageAnalysisResult], index: &ProjectIndex, politica: &PoliticaDeteccao) -> Avaliacao {
+    let amostras: Vec<Amostra> = brutas
+        .iter()
+        .filter(|resultado| !index.excluida(Path::new(&resultado.path)))
+        .filter_map(|resultado| {
+            let imagem = index.imagem(Path::new(&resultado.path))?;
+            if imagem.anotacoes.is_empty() {
+                return None;
+            }
+            Some(Amostra {
+                referencia: &imagem.anotacoes,
+                brutas: normalizar(&resultado.fissura),
+                filtradas: normalizar(&deteccoes::aplicar_politica(&resultado.fissura, politica)),
+            })
+        })
+        .collect();
+
+    let classes: Vec<String> = amostras
+        .iter()
+        .flat_map(|a| {
+            a.referencia.iter().map(|r| r.classe.clone())
+                .chain(a.filtradas.iter().map(|f| f.name.clone()))
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut metricas = Vec::new();
+    for classe in &classes {
+        let mut resultados_brutos = Vec::new();
+        let (mut vp, mut fp, mut total_anotacoes) = (0usize, 0usize, 0usize);
+        for amostra in &amostras {
+            let referencia: Vec<&AnotacaoReferencia> = amostra.referencia.iter().filter(|r| r.classe == *classe).collect();
+            total_anotacoes += referencia.len();
+
+            let brutas: Vec<&FissuraData> = amostra.brutas.iter().filter(|f| f.name == *classe).collect();
+            resultados_brutos.extend(casar(&brutas, &referencia).into_iter().map(|(f, anotacao)| (f.confidence, anotacao.is_some())));
+
+            let filtradas: Vec<&FissuraData> = amostra.filtradas.iter().filter(|f| f.name == *classe).collect();
+            for (_, anotacao) in casar(&filtradas, &referencia) {
+                if anotacao.is_some() { vp += 1 } else { fp += 1 }
+            }
+        }
+        let precisao = razao(vp, vp + fp);
+        let recall = razao(vp, total_anotacoes);
+        metricas.push(MetricasClasse {
+            classe: classe.clone(),
+            anotacoes: total_anotacoes,
+            verdadeiros_positivos: vp,
+            falsos_positivos: fp,
+            falsos_negativos: total_anotacoes - vp,
+            precisao,
+            recall,
+            f1: if precisao + recall > 0.0 { 2.0 * precisao * recall / (precisao + recall) } else { 0.0 },
+            ap50: average_precision(resultados_brutos, total_anotacoes),
+        });
+    }
+
+    let com_anotacoes: Vec<f64> = metricas.iter().filter(|m| m.anotacoes > 0).map(|m| m.ap50).collect();
+    let map50 = if com_anotacoes.is_empty() { 0.0 } else { com_anotacoes.iter().sum::<f64>() / com_anotacoes.len() as f64 };
+
+    Avaliacao {
+        imagens_avaliadas: amostras.len(),
+        matriz: matriz_confusao(&amostras, &classes),
+        classes: metricas,
+        map50,
+    }
+}
+
+impl Avaliacao {
+    // Métricas por classe seguidas da matriz de confusão, no mesmo arquivo
+    pub fn exportar_csv(&self, destino: &Path) -> Result<()> {
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_path(destino)
+            .with_context(|| format!("Falha ao criar {}", destino.display()))?;
+        writer.write_record(["classe", "anotacoes", "vp", "fp", "fn", "precisao", "recall", "f1", "ap50"])?;
+        for m in &self.classes {
+            writer.write_record([
+                m.classe.clone(),
+                m.anotacoes.to_string(),
+                m.verdadeiros_positivos.to_string(),
+                m.falsos_positivos.to_string(),
+                m.falsos_negativos.to_string(),
+                format!("{:.4}", m.precisao),
+                format!("{:.4}", m.recall),
+                format!("{:.4}", m.f1),
+                format!("{:.4}", m.ap50),
+            ])?;
+        }
+        writer.write_record(["mAP@0.5", &format!("{:.4}", self.map50)])?;
+
+        let mut cabecalho = vec!["anotada \\ detectada".to_string()];
+        cabecalho.extend(self.matriz.rotulos.iter().cloned());
+        writer.write_record(&cabecalho)?;
+        for (rotulo, linha) in self.matriz.rotulos.iter().zip(&self.matriz.valores) {
+            let mut registro = vec![rotulo.clone()];
+            registro.extend(linha.iter().map(|v| v.to_string()));
+            writer.write_record(&registro)?;
+        }
+        writer.flush()
+            .with_context(|| format!("Falha ao salvar {}", destino.display()))
+    }
+}
@@ -0,0 +1,173 @@
+// classificacao.rs
+// Segunda etapa de análise: o modelo de classificação (YOLO-Class-Py) roda depois do detector e
+// grava em Projects/<projeto>/classification_results.json as probabilidades de cada classe por
+// imagem. No modo "recortes" cada caixa detectada é classificada e a imagem fica com a maior
+// probabilidade de cada classe entre os seus recortes.
+//
+// Os gráficos e relatórios mostram as duas leituras lado a lado: quantas imagens o classificador
+// diz ter fissura do tipo X e quantas caixas do tipo X o detector encontrou.
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::process::{Command, Stdio};
+use std::collections::BTreeMap;
+use anyhow::{Result, Context, bail};
+use serde::{Serialize, Deserialize};
+use crate::anotacoes::normalizar_classe;
+use crate::deteccoes::DETECTION_FILE_NAME;
+use crate::manual_processor::ImageAnalysisResult;
+use crate::project_index::{ProjectIndex, caminho_relativo_imagens};
+use crate::project_settings::{ConfiguracaoClassificacao, ModoClassificacao};
+
+pub const CLASSIFICATION_FILE_NAME: &str = "classification_results.json";
+
+// Modelo de classificação que acompanha o repositório
+pub fn modelo_padrao() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join("Yolo").join("YOLO-Class-Py").join("best.pt")
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClassificacaoRecorte {
+    pub bbox: [f64; 4],
+    pub probabilidades: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClassificacaoImagem {
+    pub path: String,
+    #[serde(default)]
+    pub probabilidades: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub recortes: Vec<ClassificacaoRecorte>,
+}
+
+impl ClassificacaoImagem {
+    // Classe mais provável e a probabilidade dela
+    pub fn classe_prevista(&self) -> Option<(&str, f64)> {
+        self.probabilidades
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(classe, p)| (classe.as_str(), *p))
+    }
+}
+
+// Imagens por classe segundo o classificador e caixas por classe segundo o detector
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResumoClassificacao {
+    pub classe: String,
+    pub imagens: usize,
+    pub caixas: usize,
+}
+
+// Resultados da última classificação; projeto sem classificação devolve lista vazia
+pub fn carregar(project_dir: &Path) -> Result<Vec<ClassificacaoImagem>> {
+    let path = project_dir.join(CLASSIFICATION_FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Falha ao ler {}", path.display()))?;
+    let mut resultados: Vec<ClassificacaoImagem> = serde_json::from_str(&content)
+        .with_context(|| format!("Falha ao parsear {}", path.display()))?;
+    // Os nomes vêm das pastas do dataset de treino do classificador; normalizados para casar com
+    // a taxonomia do detector
+    for resultado in resultados.iter_mut() {
+        resultado.probabilidades = normalizar_probabilidades(&resultado.probabilidades);
+        for recorte in resultado.recortes.iter_mut() {
+            recorte.probabilidades = normalizar_probabilidades(&recorte.probabilidades);
+        }
+    }
+    Ok(resultados)
+}
+
+// Acompanha as imagens movidas pela reorganização do projeto, como na revisão e nas detecções.
+// O arquivo é regravado como o script o deixou, sem normalizar os nomes das classes.
+pub fn atualizar_caminhos(project_dir: &Path, movimentos: &[(PathBuf, PathBuf)]) -> Result<()> {
+    let path = project_dir.join(CLASSIFICATION_FILE_NAME);
+    if !path.exists() || movimentos.is_empty() {
+        return Ok(());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Falha ao ler {}", path.display()))?;
+    let mut resultados: Vec<ClassificacaoImagem> = serde_json::from_str(&content)
+        .with_context(|| format!("Falha ao parsear {}", path.display()))?;
+
+    let relativos: Vec<(PathBuf, &PathBuf)> = movimentos
+        .iter()
+        .filter_map(|(origem, destino)| caminho_relativo_imagens(origem).map(|rel| (rel, destino)))
+        .collect();
+    let mut mudou = false;
+    for resultado in resultados.iter_mut() {
+        let Some(rel) = caminho_relativo_imagens(Path::new(&resultado.path)) else { continue };
+        if let Some((_, destino)) = relativos.iter().find(|(origem_rel, _)| *origem_rel == rel) {
+            resultado.path = destino.display().to_string();
+            mudou = true;
+        }
+    }
+    if !mudou {
+        return Ok(());
+    }
+    fs::write(&path, serde_json::to_string_pretty(&resultados)?)
+        .with_context(|| format!("Falha ao salvar {}", path.display()))
+}
+
+fn normalizar_probabilidades(probabilidades: &BTreeMap<String, f64>) -> BTreeMap<String, f64> {
+    probabilidades.iter().map(|(classe, p)| (normalizar_classe(classe), *p)).collect()
+}
+
+// Roda o script de classificação; no modo recortes usa as caixas do detection_results.json atual
+pub fn executar(app_rust_dir: &Path, project_dir: &Path, config: &ConfiguracaoClassificacao, exclusoes_path: &Path) -> Result<Vec<ClassificacaoImagem>> {
+    let script_path = app_rust_dir.join("..").join("Yolo").join("YOLO-Class-Py").join("rodar_classificador.py");
+    if !script_path.exists() {
+        bail!("Script de classificação não encontrado em: {}", script_path.display());
+    }
+    let model_path = config.modelo.as_ref().map(PathBuf::from).unwrap_or_else(modelo_padrao);
+    if !model_path.exists() {
+        bail!("Modelo de classificação não encontrado em: {}", model_path.display());
+    }
+
+    let mut comando = Command::new("python3");
+    comando
+        .current_dir(app_rust_dir)
+        .arg(&script_path)
+        .arg(project_dir)
+        .arg(&model_path)
+        .arg("--excluir")
+        .arg(exclusoes_path)
+        .arg("--modo")
+        .arg(config.modo.argumento_script())
+        .arg("--imgsz")
+        .arg(config.tamanho_entrada.max(32).to_string());
+    if config.modo == ModoClassificacao::Recortes {
+        comando.arg("--deteccoes").arg(project_dir.join(DETECTION_FILE_NAME));
+    }
+    let output = comando
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Falha ao executar o script de classificação")?;
+    if !output.status.success() {
+        bail!("Script de classificação falhou: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    carregar(project_dir)
+}
+
+// Conta, por classe, as imagens que o classificador atribui à classe (classe mais provável com
+// probabilidade >= confiança mínima) e as caixas que o detector encontrou, nas mesmas imagens
+// consideradas pelo projeto
+pub fn resumir(classificacoes: &[ClassificacaoImagem], deteccoes: &[ImageAnalysisResult], index: &ProjectIndex, confianca_minima: f64) -> Vec<ResumoClassificacao> {
+    let mut resumo: BTreeMap<String, ResumoClassificacao> = BTreeMap::new();
+    fn entrada<'a>(resumo: &'a mut BTreeMap<String, ResumoClassificacao>, classe: &str) -> &'a mut ResumoClassificacao {
+        resumo.entry(classe.to_string()).or_insert_with(|| ResumoClassificacao { classe: classe.to_string(), ..Default::default() })
+    }
+    for classificacao in classificacoes.iter().filter(|c| !index.excluida(Path::new(&c.path))) {
+        if let Some((classe, p)) = classificacao.classe_prevista() {
+            if p >= confianca_minima {
+                entrada(&mut resumo, classe).imagens += 1;
+            }
+        }
+    }
+    for fissura in deteccoes.iter().flat_map(|r| r.fissura.iter()) {
+        entrada(&mut resumo, &normalizar_classe(&fissura.name)).caixas += 1;
+    }
+    resumo.into_values().collect()
+}
@@ -2,6 +2,7 @@ use dioxus::prelude::*;
 use std::path::{Path, PathBuf};
 use dioxus_router::prelude::Link;
 use crate::Route;
+use crate::manifesto::ManifestoProjeto;
 
 // Add context provider for project name
 pub static PROJECT_NAME: GlobalSignal<Option<String>> = Signal::global(|| None);
@@ -37,6 +38,7 @@ pub fn NewProject() -> Element {
     let mut description = use_signal(|| String::new());
     let mut year = use_signal(|| "".to_string());
     let mut leader = use_signal(|| String::new());
+    let mut company = use_signal(|| String::new());
     let mut structure_type = use_signal(|| String::new());
     let mut observations = use_signal(|| String::new());
     let mut status = use_signal(|| String::new());
@@ -50,9 +52,13 @@ pub fn NewProject() -> Element {
             return;
         }
 
+        let Ok(project_year) = year().trim().parse::<u32>() else {
+            status.set("Ano inválido.".to_string());
+            return;
+        };
+
         is_creating.set(true);
         let project_name_raw = name().trim().to_string();
-        let project_year = year().trim().to_string();
 
         // Sanitize the project name
         let sanitized_project_name = sanitize_name(&project_name_raw);
@@ -72,8 +78,21 @@ pub fn NewProject() -> Element {
             if let Some(projects_dir) = get_or_create_projects_dir() {
                 let new_folder = projects_dir.join(project_name_for_folder);
 
+                let manifesto = ManifestoProjeto {
+                    nome: project_name_raw,
+                    descricao: description().trim().to_string(),
+                    responsavel: leader().trim().to_string(),
+                    empresa: company().trim().to_string(),
+                    tipo_estrutura: structure_type().trim().to_string(),
+                    ano_construcao: Some(project_year),
+                    observacoes: observations().trim().to_string(),
+                    criado_em: chrono::Local::now().format("%Y-%m-%d").to_string(),
+                };
+
                 if let Err(e) = std::fs::create_dir_all(&new_folder) {
                     status.set(format!("Erro ao criar pasta: {}", e));
+                } else if let Err(e) = manifesto.save(&new_folder) {
+                    status.set(format!("Erro ao salvar dados do projeto: {}", e));
                 } else {
                     status.set(format!("Projeto criado em: {}", new_folder.display()));
                     project_path.set(Some(new_folder));
@@ -144,6 +163,15 @@ pub fn NewProject() -> Element {
                             oninput: move |e| leader.set(e.value())
                         }
                     }
+                    div {
+                        label { class: "block text-gray-700 mb-1", "Empresa responsável pela inspeção" }
+                        input {
+                            class: "w-full px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                            r#type: "text",
+                            value: "{company()}",
+                            oninput: move |e| company.set(e.value())
+                        }
+                    }
                     div {
                         label { class: "block text-gray-700 mb-1", "Tipo de estrutura do edifício" }
                         input {
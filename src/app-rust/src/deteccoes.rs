@@ -0,0 +1,140 @@
+// deteccoes.rs
+// Carregamento único dos resultados de detecção (detection_results.json) para gráficos e relatórios.
+// Aqui são aplicados, sempre da mesma forma, as exclusões do índice (duplicatas, baixa qualidade),
+// a política de detecção do projeto (confiança mínima por classe, NMS, classes ignoradas) e as
+// decisões da revisão humana (review.json). As fissuras com caixa recebem as medidas já salvas por
+// geometria.rs, na escala do projeto; medir fica para a etapa de processamento (medir_pendentes).
+use std::path::Path;
+use std::fs;
+use anyhow::{Result, Context, bail};
+use crate::manual_processor::{ImageAnalysisResult, FissuraData};
+use crate::project_index::ProjectIndex;
+use crate::project_settings::{ProjectSettings, PoliticaDeteccao};
+use crate::revisao::{self, RevisaoProjeto};
+use crate::modelos::ModeloRegistrado;
+use crate::geometria;
+
+pub const DETECTION_FILE_NAME: &str = "detection_results.json";
+
+// Resultados exatamente como o script de detecção os gravou
+pub fn carregar_brutas(project_dir: &Path) -> Result<Vec<ImageAnalysisResult>> {
+    let path = project_dir.join(DETECTION_FILE_NAME);
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Falha ao ler {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Falha ao parsear {}", path.display()))
+}
+
+fn iou(a: &[f64; 4], b: &[f64; 4]) -> f64 {
+    let area = |c: &[f64; 4]| (c[2] - c[0]).max(0.0) * (c[3] - c[1]).max(0.0);
+    let inter = area(&[a[0].max(b[0]), a[1].max(b[1]), a[2].min(b[2]), a[3].min(b[3])]);
+    let uniao = area(a) + area(b) - inter;
+    if uniao > 0.0 { inter / uniao } else { 0.0 }
+}
+
+// Filtra as fissuras de uma imagem pela política. O NMS só atua em detecções com caixa.
+pub fn aplicar_politica(fissuras: &[FissuraData], politica: &PoliticaDeteccao) -> Vec<FissuraData> {
+    let mut candidatas: Vec<&FissuraData> = fissuras
+        .iter()
+        .filter(|f| !politica.ignora(&f.name))
+        .filter(|f| f.confidence >= politica.confianca_minima_para(&f.name))
+        .collect();
+    candidatas.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let mut mantidas: Vec<FissuraData> = Vec::new();
+    for fissura in candidatas {
+        let suprimida = fissura.bbox.is_some_and(|caixa| {
+            mantidas.iter().any(|m| {
+                m.name.eq_ignore_ascii_case(&fissura.name)
+                    && m.bbox.is_some_and(|outra| iou(&caixa, &outra) > politica.iou_nms)
+            })
+        });
+        if !suprimida {
+            mantidas.push(fissura.clone());
+        }
+    }
+    mantidas
+}
+
+// Remove imagens excluídas pelo índice, aplica a política às restantes e combina com a revisão humana
+pub fn filtrar(brutas: &[ImageAnalysisResult], index: &ProjectIndex, politica: &PoliticaDeteccao, revisao: &RevisaoProjeto) -> Vec<ImageAnalysisResult> {
+    brutas
+        .iter()
+        .filter(|resultado| !index.excluida(Path::new(&resultado.path)))
+        .map(|resultado| {
+            let filtradas = aplicar_politica(&resultado.fissura, politica);
+            let revisao_imagem = revisao.imagem(Path::new(&resultado.path));
+            ImageAnalysisResult {
+                fissura: revisao::aplicar(&resultado.fissura, &filtradas, revisao_imagem),
+                ..resultado.clone()
+            }
+        })
+        .collect()
+}
+
+// Ids dos modelos que geraram os resultados (None = resultado anterior ao registro de modelos)
+pub fn modelos_presentes(brutas: &[ImageAnalysisResult]) -> Vec<Option<String>> {
+    let mut modelos: Vec<Option<String>> = brutas.iter().map(|r| r.modelo.clone()).collect();
+    modelos.sort();
+    modelos.dedup();
+    modelos
+}
+
+// Problema de procedência dos resultados em relação ao modelo ativo do projeto, se houver
+pub fn aviso_modelo(brutas: &[ImageAnalysisResult], modelo_ativo: Option<&ModeloRegistrado>) -> Option<String> {
+    match modelos_presentes(brutas).as_slice() {
+        [] => None,
+        [None] => Some("Resultados sem identificação do modelo (detecção anterior ao registro de modelos). Rode a detecção novamente.".to_string()),
+        [Some(id)] => match modelo_ativo {
+            Some(ativo) if ativo.id != *id => Some(format!(
+                "Resultados gerados pelo modelo {}, mas o modelo ativo do projeto é {}. Rode a detecção novamente.",
+                id, ativo.descricao()
+            )),
+            _ => None,
+        },
+        varios => Some(format!(
+            "Resultados de modelos diferentes misturados: {}",
+            varios.iter().map(|m| m.clone().unwrap_or_else(|| "desconhecido".to_string())).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+// Resultados prontos para uso, com as configurações e a revisão salvas no projeto.
+// Resultados de modelos diferentes no mesmo arquivo são recusados em vez de somados.
+pub fn carregar(project_dir: &Path) -> Result<Vec<ImageAnalysisResult>> {
+    let brutas = carregar_brutas(project_dir)?;
+    if modelos_presentes(&brutas).len() > 1 {
+        bail!("{} contém resultados de modelos diferentes; rode a detecção novamente", DETECTION_FILE_NAME);
+    }
+    let index = ProjectIndex::abrir(project_dir)?;
+    let settings = ProjectSettings::load(project_dir)?;
+    let revisao = RevisaoProjeto::load(project_dir)?;
+    let mut resultados = filtrar(&brutas, &index, &settings.politica, &revisao);
+    geometria::anexar(project_dir, &mut resultados, &settings.escala)?;
+    Ok(resultados)
+}
+
+// Mede as fissuras ainda sem medida: todas as detecções do modelo, independente da política (mudar
+// os limiares não exige medir de novo), e as caixas desenhadas na revisão
+pub fn medir_pendentes(project_dir: &Path, brutas: &[ImageAnalysisResult]) -> Result<usize> {
+    let revisao = RevisaoProjeto::load(project_dir)?;
+    let com_manuais: Vec<ImageAnalysisResult> = brutas
+        .iter()
+        .map(|resultado| ImageAnalysisResult {
+            fissura: revisao::aplicar(&resultado.fissura, &resultado.fissura, revisao.imagem(Path::new(&resultado.path))),
+            ..resultado.clone()
+        })
+        .collect();
+    geometria::medir_pendentes(project_dir, &com_manuais)
+}
+
+// Classes presentes nos resultados brutos (para montar os controles da política)
+pub fn classes_presentes(brutas: &[ImageAnalysisResult]) -> Vec<String> {
+    let mut classes: Vec<String> = brutas
+        .iter()
+        .flat_map(|r| r.fissura.iter().map(|f| f.name.to_lowercase()))
+        .collect();
+    classes.sort();
+    classes.dedup();
+    classes
+}
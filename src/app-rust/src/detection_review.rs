@@ -0,0 +1,590 @@
+// detection_review.rs
+// Tela de revisão humana das detecções: percorre as imagens do projeto e permite aceitar, rejeitar
+// ou reclassificar cada detecção, anotar observações e desenhar caixas que o modelo não encontrou.
+// As decisões vão para review.json (ver revisao.rs); o detection_results.json não é alterado.
+use dioxus::prelude::*;
+use dioxus_router::prelude::*;
+use std::path::{Path, PathBuf};
+use rfd::AsyncFileDialog;
+use crate::deteccoes;
+use crate::exportacao_dataset::{self, FRACAO_VALIDACAO_PADRAO};
+use crate::anotacoes;
+use crate::manual_processor::ImageAnalysisResult;
+use crate::project_index::{self, ProjectIndex};
+use crate::project_settings::ProjectSettings;
+use crate::revisao::{RevisaoProjeto, StatusRevisao};
+use crate::taxonomia::Taxonomia;
+use crate::Route;
+
+// Largura (px) com que a imagem é exibida; as caixas são convertidas por esta escala
+const LARGURA_EXIBICAO: f64 = 800.0;
+
+fn dimensoes(resultado: &ImageAnalysisResult) -> (f64, f64) {
+    match (resultado.width, resultado.height) {
+        (Some(w), Some(h)) => (w as f64, h as f64),
+        _ => image::image_dimensions(&resultado.path)
+            .map(|(w, h)| (w as f64, h as f64))
+            .unwrap_or((LARGURA_EXIBICAO, LARGURA_EXIBICAO * 0.75)),
+    }
+}
+
+fn cor_status(status: StatusRevisao) -> &'static str {
+    match status {
+        StatusRevisao::Aceita => "#22c55e",
+        StatusRevisao::Rejeitada => "#ef4444",
+        StatusRevisao::Pendente => "#facc15",
+    }
+}
+
+fn texto_status(status: StatusRevisao) -> &'static str {
+    match status {
+        StatusRevisao::Aceita => "Aceita",
+        StatusRevisao::Rejeitada => "Rejeitada",
+        StatusRevisao::Pendente => "Pendente",
+    }
+}
+
+fn pendentes(resultado: &ImageAnalysisResult, revisao: &RevisaoProjeto) -> usize {
+    let revisao_imagem = revisao.imagem(Path::new(&resultado.path));
+    resultado.fissura
+        .iter()
+        .filter(|f| revisao_imagem.and_then(|r| r.decisao(f)).is_none_or(|d| d.status == StatusRevisao::Pendente))
+        .count()
+}
+
+// review.json como foi lido; com erro, a tela não grava nada por cima das decisões já registradas
+type RevisaoLida = Result<RevisaoProjeto, String>;
+
+fn carregar_revisao(project_dir: &Path) -> RevisaoLida {
+    RevisaoProjeto::load(project_dir).map_err(|e| format!("Erro ao abrir a revisão do projeto: {:#}", e))
+}
+
+// Aplica uma alteração à revisão e salva, exigindo o nome do revisor e o review.json lido
+fn alterar_revisao(
+    project_dir: &Path,
+    lida: Signal<RevisaoLida>,
+    mut revisao: Signal<RevisaoProjeto>,
+    mut status: Signal<String>,
+    alteracao: impl FnOnce(&mut RevisaoProjeto),
+) {
+    if lida.read().is_err() {
+        status.set("A revisão do projeto não pôde ser lida; corrija o review.json antes de registrar decisões.".to_string());
+        return;
+    }
+    if revisao.read().revisor.trim().is_empty() {
+        status.set("Informe o nome do revisor antes de registrar decisões.".to_string());
+        return;
+    }
+    alteracao(&mut revisao.write());
+    match revisao.read().save(project_dir) {
+        Ok(()) => status.set(String::new()),
+        Err(e) => status.set(format!("Erro ao salvar revisão: {}", e)),
+    }
+}
+
+#[derive(Props, PartialEq, Clone)]
+pub struct DetectionReviewProps {
+    pub project_name: String,
+}
+
+#[component]
+pub fn DetectionReview(props: DetectionReviewProps) -> Element {
+    let project_dir: PathBuf = project_index::project_dir(&props.project_name);
+    let navigator = use_navigator();
+    let mut status = use_signal(String::new);
+
+    let mut indice = use_signal({
+        let project_dir = project_dir.clone();
+        move || ProjectIndex::abrir(&project_dir).unwrap_or_default()
+    });
+    // Só entram imagens que não foram excluídas do projeto (duplicatas, baixa qualidade)
+    let imagens = use_signal({
+        let project_dir = project_dir.clone();
+        move || {
+            let index = indice.peek();
+            deteccoes::carregar_brutas(&project_dir)
+                .map(|brutas| brutas.into_iter().filter(|r| !index.excluida(Path::new(&r.path))).collect::<Vec<_>>())
+                .map_err(|e| format!("{:#}", e))
+        }
+    });
+    let politica = use_signal({
+        let project_dir = project_dir.clone();
+        move || ProjectSettings::load(&project_dir).unwrap_or_default().politica
+    });
+    let mut revisao_lida = use_signal({
+        let project_dir = project_dir.clone();
+        move || carregar_revisao(&project_dir)
+    });
+    let mut revisao = use_signal(move || revisao_lida.peek().clone().unwrap_or_default());
+    let erro_revisao = revisao_lida.read().as_ref().err().cloned();
+    let bloqueada = erro_revisao.is_some();
+    let mut atual = use_signal(|| 0usize);
+    let mut desenho = use_signal(|| None::<[f64; 4]>); // Em coordenadas de exibição
+    // Classes que o revisor pode atribuir: as da taxonomia do modelo ativo
+    let taxonomia = use_signal({
+        let project_dir = project_dir.clone();
+        move || Taxonomia::do_projeto(&project_dir)
+    });
+    let mut classe_nova = use_signal(move || taxonomia.read().chaves().first().cloned().unwrap_or_default());
+
+    let imagens_lidas = imagens.read();
+    let lista = match &*imagens_lidas {
+        Ok(lista) => lista,
+        Err(erro) => {
+            return rsx! {
+                document::Stylesheet { href: asset!("/assets/tailwind.css") }
+                div { class: "min-h-screen bg-gray-100 flex flex-col items-center justify-center text-red-600",
+                    h1 { class: "text-2xl font-bold", "Erro ao carregar resultados de detecção" }
+                    p { "{erro}" }
+                }
+            };
+        }
+    };
+
+    let total = lista.len();
+    let posicao = atual().min(total.saturating_sub(1));
+    let total_deteccoes: usize = lista.iter().map(|r| r.fissura.len()).sum();
+    let total_pendentes: usize = lista.iter().map(|r| pendentes(r, &revisao.read())).sum();
+    let proxima_pendente = (1..=total)
+        .map(|passo| (posicao + passo) % total.max(1))
+        .find(|&i| pendentes(&lista[i], &revisao.read()) > 0);
+
+    let Some(resultado) = lista.get(posicao).cloned() else {
+        return rsx! {
+            document::Stylesheet { href: asset!("/assets/tailwind.css") }
+            div { class: "min-h-screen bg-gray-100 flex items-center justify-center text-gray-500",
+                "Nenhuma imagem com resultados de detecção neste projeto."
+            }
+        };
+    };
+
+    let image_path = PathBuf::from(&resultado.path);
+    let (largura, altura) = dimensoes(&resultado);
+    let escala = largura / LARGURA_EXIBICAO;
+    let altura_exibicao = altura / escala;
+    let revisao_imagem = revisao.read().imagem(&image_path).cloned().unwrap_or_default();
+    let passam_politica = deteccoes::aplicar_politica(&resultado.fissura, &politica.read());
+    let revisor = revisao.read().revisor.clone();
+    // Caixas de referência importadas (labelImg, YOLO, COCO), exibidas para comparação
+    let referencia = indice.read().imagem(&image_path).map(|im| im.anotacoes.clone()).unwrap_or_default();
+
+    rsx! {
+        document::Stylesheet { href: asset!("/assets/tailwind.css") }
+
+        div { class: "min-h-screen bg-gray-100 text-gray-900 font-sans",
+            document::Link {
+                href: "https://fonts.googleapis.com/icon?family=Material+Icons",
+                rel: "stylesheet"
+            }
+
+            header { class: "flex items-center justify-between bg-blue-600 text-black p-4 shadow",
+                div { class: "flex items-center gap-4",
+                    i { class: "material-icons", "fact_check" }
+                    h1 { class: "text-xl font-bold", "Revisão das Detecções: {props.project_name}" }
+                }
+                div { class: "flex items-center gap-2",
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        title: "Importa anotações do labelImg (Pascal VOC), YOLO ou COCO como referência",
+                        onclick: {
+                            let project_dir = project_dir.clone();
+                            move |_| {
+                                let project_dir = project_dir.clone();
+                                spawn(async move {
+                                    let Some(pasta) = AsyncFileDialog::new()
+                                        .set_title("Pasta com as anotações")
+                                        .pick_folder()
+                                        .await
+                                    else {
+                                        return;
+                                    };
+                                    status.set("Importando anotações...".to_string());
+                                    match anotacoes::importar(&project_dir, pasta.path()) {
+                                        Ok(resumo) => {
+                                            indice.set(ProjectIndex::abrir(&project_dir).unwrap_or_default());
+                                            status.set(resumo.descricao());
+                                        }
+                                        Err(e) => status.set(format!("Erro ao importar anotações: {:#}", e)),
+                                    }
+                                });
+                            }
+                        },
+                        i { class: "material-icons", "file_download" }
+                        "Importar anotações"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        title: "Exporta as detecções revisadas como dataset YOLO e Pascal VOC (labelImg)",
+                        onclick: {
+                            let project_dir = project_dir.clone();
+                            move |_| {
+                                let project_dir = project_dir.clone();
+                                spawn(async move {
+                                    let Some(pasta) = AsyncFileDialog::new()
+                                        .set_title("Pasta de destino do dataset")
+                                        .pick_folder()
+                                        .await
+                                    else {
+                                        return;
+                                    };
+                                    status.set("Exportando dataset...".to_string());
+                                    match exportacao_dataset::exportar(&project_dir, pasta.path(), FRACAO_VALIDACAO_PADRAO) {
+                                        Ok(resumo) => status.set(resumo.descricao()),
+                                        Err(e) => status.set(format!("Erro ao exportar dataset: {:#}", e)),
+                                    }
+                                });
+                            }
+                        },
+                        i { class: "material-icons", "model_training" }
+                        "Exportar dataset"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        onclick: {
+                            let project_name = props.project_name.clone();
+                            move |_| { navigator.push(Route::GraphView { project_name: project_name.clone() }); }
+                        },
+                        i { class: "material-icons", "bar_chart" }
+                        "Ver Gráficos"
+                    }
+                }
+            }
+
+            div { class: "container mx-auto px-4 py-6 max-w-6xl space-y-4",
+                div { class: "bg-white rounded-lg shadow-md p-4 flex flex-wrap items-center gap-4",
+                    label { class: "flex items-center gap-2 text-gray-700",
+                        "Revisor:"
+                        input {
+                            class: "px-3 py-1 border border-gray-300 rounded-md",
+                            r#type: "text",
+                            placeholder: "Nome do engenheiro",
+                            value: "{revisor}",
+                            disabled: bloqueada,
+                            onchange: {
+                                let project_dir = project_dir.clone();
+                                move |e: FormEvent| {
+                                    if revisao_lida.read().is_err() {
+                                        return;
+                                    }
+                                    revisao.write().revisor = e.value().trim().to_string();
+                                    if let Err(erro) = revisao.read().save(&project_dir) {
+                                        status.set(format!("Erro ao salvar revisão: {}", erro));
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    span { class: "text-gray-600", "Imagem {posicao + 1} de {total}" }
+                    span { class: "text-gray-600", "Pendentes: {total_pendentes} de {total_deteccoes} detecções" }
+                    div { class: "flex gap-2 ml-auto",
+                        button {
+                            class: "px-3 py-1 bg-gray-200 rounded-md hover:bg-gray-300 disabled:opacity-50",
+                            disabled: posicao == 0,
+                            onclick: move |_| { atual.set(posicao.saturating_sub(1)); desenho.set(None); },
+                            "← Anterior"
+                        }
+                        button {
+                            class: "px-3 py-1 bg-gray-200 rounded-md hover:bg-gray-300 disabled:opacity-50",
+                            disabled: posicao + 1 >= total,
+                            onclick: move |_| { atual.set(posicao + 1); desenho.set(None); },
+                            "Próxima →"
+                        }
+                        button {
+                            class: "px-3 py-1 bg-blue-600 text-white rounded-md hover:bg-blue-700 disabled:opacity-50",
+                            disabled: proxima_pendente.is_none(),
+                            onclick: move |_| {
+                                if let Some(i) = proxima_pendente {
+                                    atual.set(i);
+                                    desenho.set(None);
+                                }
+                            },
+                            "Próxima pendente"
+                        }
+                    }
+                }
+
+                if let Some(erro) = erro_revisao {
+                    div { class: "flex items-center gap-4 py-2 px-4 border border-red-300 bg-red-50 text-red-700 rounded-md",
+                        span { "{erro}. As decisões ficam bloqueadas até o arquivo ser lido, para não apagar a revisão registrada." }
+                        button {
+                            class: "ml-auto px-3 py-1 bg-white border border-red-300 rounded-md hover:bg-red-100",
+                            onclick: {
+                                let project_dir = project_dir.clone();
+                                move |_| {
+                                    let lida = carregar_revisao(&project_dir);
+                                    if let Ok(carregada) = &lida {
+                                        revisao.set(carregada.clone());
+                                    }
+                                    revisao_lida.set(lida);
+                                }
+                            },
+                            "Ler novamente"
+                        }
+                    }
+                }
+
+                if !status.read().is_empty() {
+                    p { class: "text-center text-gray-700 py-2 px-4 border border-gray-300 bg-white rounded-md", "{status}" }
+                }
+
+                div { class: "flex flex-wrap gap-4 items-start",
+                    // Imagem com as caixas; arrastar sobre ela desenha uma fissura não detectada
+                    div { class: "bg-white rounded-lg shadow-md p-4",
+                        p { class: "text-sm text-gray-600 mb-2 truncate", "{resultado.path}" }
+                        div {
+                            style: "position: relative; width: {LARGURA_EXIBICAO}px; height: {altura_exibicao}px;",
+                            img {
+                                src: "file://{resultado.path}",
+                                style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%;",
+                                draggable: "false",
+                            }
+                            svg {
+                                style: "position: absolute; top: 0; left: 0; cursor: crosshair;",
+                                width: "{LARGURA_EXIBICAO}",
+                                height: "{altura_exibicao}",
+                                onmousedown: move |e: MouseEvent| {
+                                    if bloqueada {
+                                        return;
+                                    }
+                                    let p = e.element_coordinates();
+                                    desenho.set(Some([p.x, p.y, p.x, p.y]));
+                                },
+                                onmousemove: move |e: MouseEvent| {
+                                    let p = e.element_coordinates();
+                                    if let Some(caixa) = desenho.write().as_mut() {
+                                        caixa[2] = p.x;
+                                        caixa[3] = p.y;
+                                    }
+                                },
+                                onmouseup: {
+                                    let project_dir = project_dir.clone();
+                                    let image_path = image_path.clone();
+                                    move |_| {
+                                        let Some([x0, y0, x1, y1]) = desenho.take() else { return };
+                                        if (x1 - x0).abs() < 4.0 || (y1 - y0).abs() < 4.0 {
+                                            return; // clique sem arrastar
+                                        }
+                                        let caixa = [x0.min(x1) * escala, y0.min(y1) * escala, x0.max(x1) * escala, y0.max(y1) * escala];
+                                        let classe = classe_nova();
+                                        alterar_revisao(&project_dir, revisao_lida, revisao, status, |r| r.adicionar_caixa(&image_path, &classe, caixa));
+                                        // A caixa nova é medida fora da thread da interface, para gráficos e relatórios;
+                                        // a gravação do measurements.json é serializada em geometria.rs
+                                        let project_dir = project_dir.clone();
+                                        spawn(async move {
+                                            let medidas = tokio::task::spawn_blocking(move || {
+                                                deteccoes::carregar_brutas(&project_dir)
+                                                    .and_then(|brutas| deteccoes::medir_pendentes(&project_dir, &brutas))
+                                            })
+                                            .await;
+                                            match medidas {
+                                                Ok(Ok(_)) => {}
+                                                Ok(Err(e)) => status.set(format!("Erro ao medir a caixa desenhada: {:#}", e)),
+                                                Err(e) => status.set(format!("Erro ao medir a caixa desenhada: {}", e)),
+                                            }
+                                        });
+                                    }
+                                },
+                                for (i, fissura) in resultado.fissura.iter().enumerate() {
+                                    rect {
+                                        key: "m{i}",
+                                        x: "{fissura.bbox.map_or(0.0, |b| b[0] / escala)}",
+                                        y: "{fissura.bbox.map_or(0.0, |b| b[1] / escala)}",
+                                        width: "{fissura.bbox.map_or(0.0, |b| (b[2] - b[0]) / escala)}",
+                                        height: "{fissura.bbox.map_or(0.0, |b| (b[3] - b[1]) / escala)}",
+                                        fill: "none",
+                                        stroke: cor_status(revisao_imagem.decisao(fissura).map_or(StatusRevisao::Pendente, |d| d.status)),
+                                        stroke_width: "2",
+                                        pointer_events: "none",
+                                    }
+                                    text {
+                                        key: "t{i}",
+                                        x: "{fissura.bbox.map_or(4.0, |b| b[0] / escala + 2.0)}",
+                                        y: "{fissura.bbox.map_or(14.0 * (i as f64 + 1.0), |b| b[1] / escala + 14.0)}",
+                                        fill: cor_status(revisao_imagem.decisao(fissura).map_or(StatusRevisao::Pendente, |d| d.status)),
+                                        font_size: "14",
+                                        pointer_events: "none",
+                                        "#{i + 1}"
+                                    }
+                                }
+                                for (i, anotacao) in referencia.iter().enumerate() {
+                                    rect {
+                                        key: "r{i}",
+                                        x: "{anotacao.bbox[0] / escala}",
+                                        y: "{anotacao.bbox[1] / escala}",
+                                        width: "{(anotacao.bbox[2] - anotacao.bbox[0]) / escala}",
+                                        height: "{(anotacao.bbox[3] - anotacao.bbox[1]) / escala}",
+                                        fill: "none",
+                                        stroke: "#22d3ee",
+                                        stroke_width: "2",
+                                        stroke_dasharray: "2 3",
+                                        pointer_events: "none",
+                                    }
+                                }
+                                for (i, caixa) in revisao_imagem.caixas_manuais.iter().enumerate() {
+                                    rect {
+                                        key: "c{i}",
+                                        x: "{caixa.bbox[0] / escala}",
+                                        y: "{caixa.bbox[1] / escala}",
+                                        width: "{(caixa.bbox[2] - caixa.bbox[0]) / escala}",
+                                        height: "{(caixa.bbox[3] - caixa.bbox[1]) / escala}",
+                                        fill: "none",
+                                        stroke: "#3b82f6",
+                                        stroke_width: "2",
+                                        stroke_dasharray: "6 3",
+                                        pointer_events: "none",
+                                    }
+                                }
+                                if let Some([x0, y0, x1, y1]) = desenho() {
+                                    rect {
+                                        x: "{x0.min(x1)}",
+                                        y: "{y0.min(y1)}",
+                                        width: "{(x1 - x0).abs()}",
+                                        height: "{(y1 - y0).abs()}",
+                                        fill: "rgba(59, 130, 246, 0.2)",
+                                        stroke: "#3b82f6",
+                                        pointer_events: "none",
+                                    }
+                                }
+                            }
+                        }
+                        p { class: "mt-2 text-xs text-gray-500",
+                            "Amarelo: pendente · Verde: aceita · Vermelho: rejeitada · Azul tracejado: desenhada · Ciano pontilhado: anotação de referência"
+                        }
+                        div { class: "flex items-center gap-2 mt-2 text-sm text-gray-600",
+                            "Classe da caixa desenhada:"
+                            select {
+                                class: "px-2 py-1 border border-gray-300 rounded-md bg-white",
+                                value: "{classe_nova}",
+                                onchange: move |e| classe_nova.set(e.value()),
+                                for classe in taxonomia.read().classes.iter() {
+                                    option { key: "{classe.chave}", value: "{classe.chave}", "{classe.rotulo}" }
+                                }
+                            }
+                        }
+                    }
+
+                    // Lista de detecções da imagem
+                    div { class: "flex-1 min-w-[320px] space-y-3",
+                        if resultado.fissura.is_empty() && revisao_imagem.caixas_manuais.is_empty() {
+                            p { class: "text-gray-500", "O modelo não detectou fissuras nesta imagem." }
+                        }
+                        for (i, fissura) in resultado.fissura.iter().cloned().enumerate() {
+                            {
+                                let decisao = revisao_imagem.decisao(&fissura).cloned();
+                                let status_atual = decisao.as_ref().map_or(StatusRevisao::Pendente, |d| d.status);
+                                let classe_atual = decisao.as_ref().and_then(|d| d.classe.clone()).unwrap_or_else(|| fissura.name.clone());
+                                let nota_atual = decisao.as_ref().and_then(|d| d.nota.clone()).unwrap_or_default();
+                                let abaixo_do_limiar = !passam_politica.contains(&fissura);
+                                rsx! {
+                                    div { key: "d{i}", class: "bg-white rounded-lg shadow-md p-3 space-y-2",
+                                        div { class: "flex items-center gap-2",
+                                            span {
+                                                class: "font-semibold",
+                                                style: "color: {cor_status(status_atual)};",
+                                                "#{i + 1}"
+                                            }
+                                            span { style: "color: {taxonomia.read().cor(&fissura.name)};", "⬤" }
+                                            span { "{taxonomia.read().rotulo(&fissura.name)} · {fissura.confidence * 100.0:.0}%" }
+                                            span { class: "text-xs px-2 py-0.5 rounded bg-gray-100", "{texto_status(status_atual)}" }
+                                            if abaixo_do_limiar {
+                                                span { class: "text-xs text-gray-500", "fora da política" }
+                                            }
+                                            if let Some(d) = &decisao {
+                                                span { class: "text-xs text-gray-400 ml-auto", "{d.revisor} · {d.data}" }
+                                            }
+                                        }
+                                        div { class: "flex flex-wrap items-center gap-2",
+                                            button {
+                                                class: "px-2 py-1 text-sm bg-green-600 text-white rounded-md hover:bg-green-700 disabled:opacity-50",
+                                                disabled: bloqueada,
+                                                onclick: {
+                                                    let (project_dir, image_path, fissura) = (project_dir.clone(), image_path.clone(), fissura.clone());
+                                                    let (classe, nota) = (classe_atual.clone(), nota_atual.clone());
+                                                    move |_| alterar_revisao(&project_dir, revisao_lida, revisao, status, |r| {
+                                                        r.decidir(&image_path, &fissura, StatusRevisao::Aceita, Some(classe.clone()), Some(nota.clone()))
+                                                    })
+                                                },
+                                                "Aceitar"
+                                            }
+                                            button {
+                                                class: "px-2 py-1 text-sm bg-red-600 text-white rounded-md hover:bg-red-700 disabled:opacity-50",
+                                                disabled: bloqueada,
+                                                onclick: {
+                                                    let (project_dir, image_path, fissura) = (project_dir.clone(), image_path.clone(), fissura.clone());
+                                                    let nota = nota_atual.clone();
+                                                    move |_| alterar_revisao(&project_dir, revisao_lida, revisao, status, |r| {
+                                                        r.decidir(&image_path, &fissura, StatusRevisao::Rejeitada, None, Some(nota.clone()))
+                                                    })
+                                                },
+                                                "Rejeitar"
+                                            }
+                                            select {
+                                                class: "px-2 py-1 text-sm border border-gray-300 rounded-md bg-white",
+                                                disabled: bloqueada,
+                                                value: "{classe_atual}",
+                                                onchange: {
+                                                    let (project_dir, image_path, fissura) = (project_dir.clone(), image_path.clone(), fissura.clone());
+                                                    let nota = nota_atual.clone();
+                                                    // Reclassificar implica confirmar a fissura com a nova classe
+                                                    move |e: FormEvent| alterar_revisao(&project_dir, revisao_lida, revisao, status, |r| {
+                                                        r.decidir(&image_path, &fissura, StatusRevisao::Aceita, Some(e.value()), Some(nota.clone()))
+                                                    })
+                                                },
+                                                if taxonomia.read().classe(&classe_atual).is_none() {
+                                                    option { value: "{classe_atual}", "{classe_atual}" }
+                                                }
+                                                for classe in taxonomia.read().classes.iter() {
+                                                    option { key: "{classe.chave}", value: "{classe.chave}", "{classe.rotulo}" }
+                                                }
+                                            }
+                                        }
+                                        input {
+                                            class: "w-full px-2 py-1 text-sm border border-gray-300 rounded-md",
+                                            r#type: "text",
+                                            placeholder: "Observação do revisor",
+                                            disabled: bloqueada,
+                                            value: "{nota_atual}",
+                                            onchange: {
+                                                let (project_dir, image_path, fissura) = (project_dir.clone(), image_path.clone(), fissura.clone());
+                                                let classe = classe_atual.clone();
+                                                move |e: FormEvent| alterar_revisao(&project_dir, revisao_lida, revisao, status, |r| {
+                                                    r.decidir(&image_path, &fissura, status_atual, Some(classe.clone()), Some(e.value()))
+                                                })
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        if !referencia.is_empty() {
+                            div { class: "bg-white rounded-lg shadow-md p-3 space-y-1",
+                                p { class: "font-semibold text-cyan-600", "Anotações de referência ({referencia.len()})" }
+                                for (i, anotacao) in referencia.iter().enumerate() {
+                                    p { key: "r{i}", class: "text-sm text-gray-700",
+                                        "{taxonomia.read().rotulo(&anotacao.classe)} · {anotacao.formato}"
+                                    }
+                                }
+                            }
+                        }
+                        for (i, caixa) in revisao_imagem.caixas_manuais.iter().cloned().enumerate() {
+                            div { key: "c{i}", class: "bg-white rounded-lg shadow-md p-3 flex items-center gap-2",
+                                span { class: "font-semibold text-blue-500", "Manual" }
+                                span { "{taxonomia.read().rotulo(&caixa.classe)}" }
+                                span { class: "text-xs text-gray-400", "{caixa.revisor} · {caixa.data}" }
+                                button {
+                                    class: "ml-auto px-2 py-1 text-sm bg-gray-200 rounded-md hover:bg-gray-300 disabled:opacity-50",
+                                    disabled: bloqueada,
+                                    onclick: {
+                                        let (project_dir, image_path) = (project_dir.clone(), image_path.clone());
+                                        move |_| alterar_revisao(&project_dir, revisao_lida, revisao, status, |r| r.remover_caixa(&image_path, i))
+                                    },
+                                    "Remover"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
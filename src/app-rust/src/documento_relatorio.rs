@@ -0,0 +1,504 @@
+// documento_relatorio.rs
+// Estrutura do relatório para os exportadores nativos (PDF, DOCX), montada a partir do modelo do
+// relatório (ReportData e o contexto do template), não do Markdown renderizado: identificação,
+// descrição do prédio, metodologia, tabela de fachadas, resumos, gráficos e uma seção por fissura
+// com tabela de medidas e a figura anotada com a caixa dela. Do Markdown atual só vêm as seções
+// editáveis preservadas (conclusões, recomendações; ver edicao_relatorio.rs). Os blocos não têm
+// formatação de página; o cabeçalho e o rodapé das páginas vêm dos dados do projeto.
+use std::collections::HashMap;
+use std::path::Path;
+use anyhow::{Result, Context};
+use image::{imageops, Rgb, RgbImage};
+use pulldown_cmark::{Alignment, Event, Options, Parser, Tag};
+use crate::deteccoes;
+use crate::edicao_relatorio;
+use crate::report_structures::{ContextoRelatorio, FissuraRelatorio, ReportData};
+use crate::taxonomia::Taxonomia;
+use crate::templates_relatorio::{formatar_data, porcentagem};
+
+// Lado maior das imagens exportadas: suficiente para impressão em A4 sem inflar o arquivo
+const LADO_MAXIMO_IMAGEM: u32 = 1600;
+const COR_CAIXA: Rgb<u8> = Rgb([230, 40, 40]);
+const TITULO_RELATORIO: &str = "Relatório de Inspeção de Fissuras";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trecho {
+    pub texto: String,
+    pub negrito: bool,
+    pub italico: bool,
+}
+
+impl Trecho {
+    fn normal(texto: impl Into<String>) -> Self {
+        Self { texto: texto.into(), negrito: false, italico: false }
+    }
+
+    fn negrito(texto: impl Into<String>) -> Self {
+        Self { texto: texto.into(), negrito: true, italico: false }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaixaAnotada {
+    pub bbox: [f64; 4], // [x1, y1, x2, y2] em pixels da imagem original
+    pub rotulo: String,
+}
+
+// Alinhamento de uma coluna de tabela; colunas numéricas vão à direita
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Alinhamento {
+    #[default]
+    Esquerda,
+    Direita,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bloco {
+    Titulo { nivel: u8, texto: String },
+    // Trechos com "\n" marcam quebras de linha forçadas
+    Paragrafo(Vec<Trecho>),
+    Item(Vec<Trecho>),
+    // Um alinhamento por coluna do cabeçalho
+    Tabela { cabecalho: Vec<String>, linhas: Vec<Vec<String>>, alinhamentos: Vec<Alinhamento> },
+    Imagem { caminho: String, legenda: String, caixas: Vec<CaixaAnotada> },
+    Separador,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DocumentoRelatorio {
+    pub titulo: String,
+    pub projeto: String,
+    pub empresa: String,
+    // Dados da folha de rosto
+    pub predio: String,
+    pub responsavel: String,
+    pub data_analise: String, // "DD/MM/YYYY"
+    pub blocos: Vec<Bloco>,
+}
+
+fn tabela(cabecalho: &[&str], linhas: Vec<Vec<String>>, alinhamentos: &[Alinhamento]) -> Bloco {
+    Bloco::Tabela {
+        cabecalho: cabecalho.iter().map(|c| c.to_string()).collect(),
+        linhas,
+        alinhamentos: alinhamentos.to_vec(),
+    }
+}
+
+// "**Rótulo:** valor"
+fn item(rotulo: &str, valor: &str) -> Bloco {
+    Bloco::Item(vec![Trecho::negrito(format!("{}: ", rotulo)), Trecho::normal(valor)])
+}
+
+// Tabela de medidas e dados de uma fissura (campo, valor), só com os campos que ela tem
+fn tabela_fissura(fissura: &FissuraRelatorio) -> Bloco {
+    let classificacao = match &fissura.classificacao_original {
+        Some(original) => format!("{} (modelo: {})", fissura.classificacao, original),
+        None => fissura.classificacao.clone(),
+    };
+    let linhas: Vec<Vec<String>> = [
+        Some(("Faceta", fissura.faceta_id.clone())),
+        Some(("Orientação", fissura.orientacao.clone())),
+        fissura.comprimento.clone().map(|v| ("Comprimento", v)),
+        fissura.largura.clone().map(|v| ("Abertura média", v)),
+        fissura.classe_abertura.clone().map(|v| ("Classe de abertura", v)),
+        Some(("Classificação", classificacao)),
+        Some(("Confiança do modelo", porcentagem(fissura.confianca, 0))),
+        fissura.classificacao_imagem.clone().map(|v| ("Classificação da imagem", v)),
+        Some(("Revisão", fissura.status_revisao.clone())),
+        fissura.nota_revisor.clone().map(|v| ("Nota do revisor", v)),
+        Some(("Observações da faceta", fissura.observacoes.clone())),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|(campo, valor)| vec![campo.to_string(), valor])
+    .collect();
+    tabela(&["Campo", "Valor"], linhas, &[Alinhamento::Esquerda, Alinhamento::Esquerda])
+}
+
+impl DocumentoRelatorio {
+    // Documento do relatório a partir dos dados dos prédios cobertos e do contexto montado com eles.
+    // `markdown` é o relatório atual (gerado ou editado no app), de onde vêm as seções preservadas.
+    pub fn montar(dados: &[ReportData], contexto: &ContextoRelatorio, taxonomia: &Taxonomia, markdown: &str) -> Self {
+        use Alinhamento::{Direita, Esquerda};
+        let primeiro = dados.first();
+        let mut blocos = vec![
+            Bloco::Titulo { nivel: 1, texto: TITULO_RELATORIO.to_string() },
+            Bloco::Paragrafo(vec![
+                Trecho::negrito("Nome do Projeto: "),
+                Trecho::normal(format!("{}\n", contexto.nome_projeto)),
+                Trecho::negrito("Data da Análise: "),
+                Trecho::normal(format!("{}\n", formatar_data(&contexto.data_analise, "%d/%m/%Y"))),
+                Trecho::negrito("Responsável Técnico: "),
+                Trecho::normal(format!("{}\n", contexto.nome_responsavel)),
+                Trecho::negrito("Identificação do Prédio: "),
+                Trecho::normal(format!("{}\n", contexto.nome_predio)),
+                Trecho::negrito("Endereço: "),
+                Trecho::normal(contexto.endereco_predio.clone()),
+            ]),
+            Bloco::Titulo { nivel: 2, texto: "1. Descrição Geral do Prédio".to_string() },
+            item("Número de andares", &contexto.numero_andares),
+            item("Ano de construção", &contexto.ano_construcao),
+            item("Tipo de estrutura", &contexto.tipo_estrutura),
+            item("Observações gerais", &contexto.observacoes_gerais),
+        ];
+
+        let metodologia = &contexto.metodologia;
+        blocos.push(Bloco::Titulo { nivel: 2, texto: "2. Metodologia".to_string() });
+        blocos.push(Bloco::Paragrafo(vec![Trecho::normal(format!(
+            "As fissuras foram detectadas automaticamente por um modelo YOLO ({}) e filtradas pela política de detecção do projeto.",
+            metodologia.ladrilhamento
+        ))]));
+        let mut limiares = vec![vec!["Padrão".to_string(), format!("{}%", metodologia.confianca_minima_padrao)]];
+        limiares.extend(metodologia.limiares.iter().map(|l| vec![l.classe.clone(), format!("{}%", l.confianca)]));
+        blocos.extend([
+            item("Modelo", &metodologia.modelo),
+            item("IoU para supressão de detecções sobrepostas (NMS)", &metodologia.iou_nms.to_string()),
+            item("Classes ignoradas", &metodologia.classes_ignoradas),
+            item("Escala das medidas", &metodologia.escala),
+            item(
+                "Revisão humana",
+                &format!("{} de {} fissura(s) revisada(s) por um engenheiro", metodologia.fissuras_revisadas, metodologia.fissuras_total),
+            ),
+            tabela(&["Classe", "Confiança mínima"], limiares, &[Esquerda, Direita]),
+        ]);
+
+        // Fachadas: a mesma ordem e identificação do relatório em Markdown
+        blocos.push(Bloco::Titulo { nivel: 2, texto: "3. Facetas Inspecionadas".to_string() });
+        let facetas = contexto.facetas
+            .iter()
+            .map(|f| vec![f.id.clone(), f.orientacao.clone(), f.qtd_imagens.to_string(), f.qtd_rachaduras.to_string(), f.observacoes.clone()])
+            .collect();
+        blocos.push(tabela(
+            &["ID da Faceta", "Orientação", "Imagens", "Nº de Fissuras", "Observações"],
+            facetas,
+            &[Esquerda, Esquerda, Direita, Direita, Esquerda],
+        ));
+
+        blocos.push(Bloco::Titulo { nivel: 2, texto: "4. Resumo por Classe".to_string() });
+        let classes = contexto.resumo_classes
+            .iter()
+            .map(|c| vec![c.rotulo.clone(), c.quantidade.to_string(), format!("{:.1}", c.peso), c.descricao.clone()])
+            .collect();
+        blocos.push(tabela(&["Classe", "Nº de Fissuras", "Peso de Severidade", "Descrição"], classes, &[Esquerda, Direita, Direita, Esquerda]));
+        blocos.push(Bloco::Paragrafo(vec![
+            Trecho::negrito("Índice de severidade: "),
+            Trecho::normal(format!("{} (soma dos pesos de severidade das fissuras)", contexto.indice_severidade)),
+        ]));
+        if !contexto.predios.is_empty() {
+            blocos.push(Bloco::Titulo { nivel: 3, texto: "Resumo por Prédio".to_string() });
+            let predios = contexto.predios
+                .iter()
+                .map(|p| vec![p.nome.clone(), p.endereco.clone(), p.imagens.to_string(), p.fissuras.to_string(), p.indice_severidade.clone()])
+                .collect();
+            blocos.push(tabela(
+                &["Prédio", "Endereço", "Imagens", "Nº de Fissuras", "Índice de Severidade"],
+                predios,
+                &[Esquerda, Esquerda, Direita, Direita, Direita],
+            ));
+            for predio in &contexto.predios {
+                blocos.push(Bloco::Titulo { nivel: 4, texto: predio.nome.clone() });
+                if predio.classes.is_empty() {
+                    blocos.push(Bloco::Paragrafo(vec![Trecho::normal("Nenhuma fissura considerada neste prédio.")]));
+                } else {
+                    let linhas = predio.classes.iter().map(|c| vec![c.rotulo.clone(), c.quantidade.to_string()]).collect();
+                    blocos.push(tabela(&["Classe", "Nº de Fissuras"], linhas, &[Esquerda, Direita]));
+                }
+            }
+        }
+        if let Some(classificacao) = &contexto.classificacao {
+            blocos.push(Bloco::Titulo { nivel: 3, texto: "Classificação das Imagens".to_string() });
+            blocos.push(Bloco::Paragrafo(vec![Trecho::normal(format!(
+                "Além do detector, um modelo de classificação analisou {} imagem(ns) ({}). Uma imagem conta para a classe mais provável quando a probabilidade é de pelo menos {}%.",
+                classificacao.imagens, classificacao.modo, classificacao.confianca_minima
+            ))]));
+            let linhas = classificacao.resumo
+                .iter()
+                .map(|c| vec![c.classe.clone(), c.imagens.to_string(), c.caixas.to_string()])
+                .collect();
+            blocos.push(tabela(
+                &["Classe", "Imagens (classificador)", "Caixas detectadas (detector)"],
+                linhas,
+                &[Esquerda, Direita, Direita],
+            ));
+        }
+
+        let mut figuras = 0;
+        blocos.push(Bloco::Titulo { nivel: 2, texto: "5. Estatísticas".to_string() });
+        if contexto.graficos.is_empty() {
+            blocos.push(Bloco::Paragrafo(vec![Trecho::normal("Nenhuma fissura considerada; não há gráficos para este relatório.")]));
+        }
+        for grafico in &contexto.graficos {
+            figuras += 1;
+            blocos.push(Bloco::Imagem {
+                caminho: grafico.imagem.clone(),
+                legenda: format!("Figura {} — {}", figuras, grafico.titulo),
+                caixas: Vec::new(),
+            });
+        }
+
+        // Uma seção por fissura: tabela de medidas e a foto com a caixa da própria fissura
+        blocos.push(Bloco::Titulo { nivel: 2, texto: "6. Detalhamento das Fissuras".to_string() });
+        for fissura in &contexto.fissuras {
+            blocos.push(Bloco::Titulo { nivel: 3, texto: format!("Fissura {}", fissura.id_fissura) });
+            blocos.push(tabela_fissura(fissura));
+            figuras += 1;
+            let arquivo = Path::new(&fissura.caminho_imagem).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            blocos.push(Bloco::Imagem {
+                caminho: fissura.caminho_imagem.clone(),
+                legenda: format!("Figura {} — Fissura {}: {} ({})", figuras, fissura.id_fissura, fissura.classificacao, arquivo),
+                caixas: fissura.bbox
+                    .map(|bbox| vec![CaixaAnotada { bbox, rotulo: taxonomia.rotulo(&fissura.classe) }])
+                    .unwrap_or_default(),
+            });
+        }
+
+        // Seções escritas pelo engenheiro, do Markdown atual; as de templates próprios vêm depois
+        let secoes = edicao_relatorio::extrair_secoes(markdown);
+        blocos.push(Bloco::Titulo { nivel: 2, texto: "7. Conclusões e Recomendações".to_string() });
+        // Sem o marcador no Markdown (template próprio sem a seção), vale o texto dos dados do prédio
+        let conhecidas = [
+            ("conclusao_geral", "Conclusões", &contexto.conclusao_geral),
+            ("recomendacoes", "Recomendações", &contexto.recomendacoes),
+        ];
+        let outras = secoes
+            .iter()
+            .filter(|(nome, _)| !conhecidas.iter().any(|(conhecida, _, _)| conhecida == nome))
+            .map(|(nome, texto)| (nome.replace('_', " "), texto.as_str()));
+        let mut secoes_finais: Vec<(String, &str)> = conhecidas
+            .iter()
+            .map(|(nome, titulo, dos_dados)| (titulo.to_string(), secoes.get(*nome).map_or(dos_dados.as_str(), String::as_str)))
+            .collect();
+        secoes_finais.extend(outras);
+        for (titulo, texto) in secoes_finais {
+            blocos.push(Bloco::Paragrafo(vec![Trecho::negrito(format!("{}:", titulo))]));
+            if texto.trim().is_empty() {
+                blocos.push(Bloco::Paragrafo(vec![Trecho { texto: "Não preenchido.".to_string(), negrito: false, italico: true }]));
+            } else {
+                blocos.extend(blocos_do_markdown(texto));
+            }
+        }
+
+        blocos.push(Bloco::Titulo { nivel: 2, texto: "8. Assinatura".to_string() });
+        blocos.extend([
+            item("Nome", &contexto.nome_responsavel),
+            item("Função", &contexto.funcao_responsavel),
+            item("Empresa", &contexto.nome_empresa),
+            Bloco::Separador,
+            Bloco::Paragrafo(vec![Trecho {
+                texto: "Relatório gerado automaticamente pela Plataforma de Análise de Fissuras.".to_string(),
+                negrito: false,
+                italico: true,
+            }]),
+        ]);
+
+        Self {
+            titulo: TITULO_RELATORIO.to_string(),
+            projeto: contexto.nome_projeto.clone(),
+            // Do ReportData, sem o "Não informado" do contexto, para o cabeçalho das páginas
+            empresa: primeiro.map(|d| d.nome_empresa.clone()).unwrap_or_default(),
+            predio: contexto.nome_predio.clone(),
+            responsavel: primeiro.map(|d| d.nome_responsavel.clone()).unwrap_or_default(),
+            data_analise: formatar_data(&contexto.data_analise, "%d/%m/%Y"),
+            blocos,
+        }
+    }
+
+    // Texto do cabeçalho das páginas: "<empresa> · <projeto>"
+    pub fn cabecalho(&self) -> String {
+        if self.empresa.is_empty() {
+            self.projeto.clone()
+        } else {
+            format!("{} · {}", self.empresa, self.projeto)
+        }
+    }
+}
+
+// Caixas consideradas no relatório (após exclusões, política e revisão) por caminho de imagem
+pub fn caixas_do_projeto(project_dir: &Path) -> Result<HashMap<String, Vec<CaixaAnotada>>> {
+    let resultados = deteccoes::carregar(project_dir).context("Falha ao carregar as detecções para anotar as imagens")?;
+    let taxonomia = Taxonomia::dos_resultados(project_dir, &resultados);
+    Ok(resultados
+        .iter()
+        .map(|resultado| {
+            let caixas = resultado.fissura
+                .iter()
+                .filter_map(|f| f.bbox.map(|bbox| CaixaAnotada { bbox, rotulo: taxonomia.rotulo(&f.name) }))
+                .collect();
+            (resultado.path.clone(), caixas)
+        })
+        .collect())
+}
+
+fn texto_dos_trechos(trechos: &[Trecho]) -> String {
+    trechos.iter().map(|t| t.texto.as_str()).collect::<String>().trim().to_string()
+}
+
+// Divide um trecho de Markdown (as seções editáveis) em blocos. Comentários HTML e HTML solto são
+// ignorados.
+pub fn blocos_do_markdown(markdown: &str) -> Vec<Bloco> {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let mut blocos = Vec::new();
+    let mut trechos: Vec<Trecho> = Vec::new();
+    let (mut negrito, mut italico) = (false, false);
+    // Tabela em montagem: alinhamentos e linhas de células; a primeira linha é o cabeçalho
+    let mut tabela: Option<(Vec<Alinhamento>, Vec<Vec<String>>)> = None;
+    let mut celula: Option<String> = None;
+    // Texto alternativo da imagem em montagem e o caminho dela
+    let mut imagem: Option<(String, String)> = None;
+    let mut ultimo_titulo = String::new();
+    let mut figuras = 0;
+    let mut em_item = 0usize; // Profundidade de listas abertas
+
+    for evento in Parser::new_ext(markdown, options) {
+        match evento {
+            Event::Start(Tag::Strong) => negrito = true,
+            Event::End(Tag::Strong) => negrito = false,
+            Event::Start(Tag::Emphasis) => italico = true,
+            Event::End(Tag::Emphasis) => italico = false,
+            Event::Start(Tag::Heading(..)) | Event::Start(Tag::Paragraph) | Event::Start(Tag::Item) => {
+                // Item com sublista: o texto antes da sublista fecha como item
+                if !texto_dos_trechos(&trechos).is_empty() {
+                    let trechos = std::mem::take(&mut trechos);
+                    blocos.push(if em_item > 0 { Bloco::Item(trechos) } else { Bloco::Paragrafo(trechos) });
+                }
+                trechos.clear();
+                if let Event::Start(Tag::Item) = evento {
+                    em_item += 1;
+                }
+            }
+            Event::End(Tag::Heading(nivel, ..)) => {
+                let texto = texto_dos_trechos(&trechos);
+                trechos.clear();
+                ultimo_titulo = texto.clone();
+                blocos.push(Bloco::Titulo { nivel: nivel as u8, texto });
+            }
+            Event::End(Tag::Paragraph) | Event::End(Tag::Item) => {
+                // Em listas "soltas" o texto do item vem dentro de um parágrafo
+                if !texto_dos_trechos(&trechos).is_empty() {
+                    let trechos = std::mem::take(&mut trechos);
+                    blocos.push(if em_item > 0 { Bloco::Item(trechos) } else { Bloco::Paragrafo(trechos) });
+                }
+                trechos.clear();
+                if let Event::End(Tag::Item) = evento {
+                    em_item -= 1;
+                }
+            }
+            Event::Start(Tag::Table(alinhamentos)) => {
+                let alinhamentos = alinhamentos
+                    .iter()
+                    .map(|a| if *a == Alignment::Right { Alinhamento::Direita } else { Alinhamento::Esquerda })
+                    .collect();
+                tabela = Some((alinhamentos, Vec::new()));
+            }
+            Event::Start(Tag::TableHead) | Event::Start(Tag::TableRow) => {
+                if let Some((_, linhas)) = tabela.as_mut() {
+                    linhas.push(Vec::new());
+                }
+            }
+            Event::Start(Tag::TableCell) => celula = Some(String::new()),
+            Event::End(Tag::TableCell) => {
+                if let (Some((_, linhas)), Some(texto)) = (tabela.as_mut(), celula.take()) {
+                    if let Some(linha) = linhas.last_mut() {
+                        linha.push(texto.trim().to_string());
+                    }
+                }
+            }
+            Event::End(Tag::Table(_)) => {
+                let (alinhamentos, mut linhas) = tabela.take().unwrap_or_default();
+                if !linhas.is_empty() {
+                    let cabecalho = linhas.remove(0);
+                    blocos.push(Bloco::Tabela { cabecalho, linhas, alinhamentos });
+                }
+            }
+            Event::Start(Tag::Image(_, destino, _)) => imagem = Some((String::new(), destino.to_string())),
+            Event::End(Tag::Image(..)) => {
+                if let Some((alternativo, caminho)) = imagem.take() {
+                    // A imagem fica em bloco próprio; o texto antes dela continua como parágrafo
+                    if !texto_dos_trechos(&trechos).is_empty() {
+                        let trechos = std::mem::take(&mut trechos);
+                        blocos.push(if em_item > 0 { Bloco::Item(trechos) } else { Bloco::Paragrafo(trechos) });
+                    }
+                    trechos.clear();
+                    figuras += 1;
+                    let descricao = if ultimo_titulo.is_empty() { alternativo } else { ultimo_titulo.clone() };
+                    let arquivo = Path::new(&caminho).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                    blocos.push(Bloco::Imagem {
+                        legenda: format!("Figura {} — {} ({})", figuras, descricao.trim(), arquivo),
+                        caminho,
+                        caixas: Vec::new(),
+                    });
+                }
+            }
+            Event::Text(texto) | Event::Code(texto) => {
+                if let Some((alternativo, _)) = imagem.as_mut() {
+                    alternativo.push_str(&texto);
+                } else if let Some(celula) = celula.as_mut() {
+                    celula.push_str(&texto);
+                } else {
+                    trechos.push(Trecho { texto: texto.to_string(), negrito, italico });
+                }
+            }
+            Event::SoftBreak => {
+                if let Some(celula) = celula.as_mut() {
+                    celula.push(' ');
+                } else {
+                    trechos.push(Trecho { texto: " ".to_string(), negrito, italico });
+                }
+            }
+            Event::HardBreak => trechos.push(Trecho { texto: "\n".to_string(), negrito, italico }),
+            Event::Rule => blocos.push(Bloco::Separador),
+            _ => {}
+        }
+    }
+    if !texto_dos_trechos(&trechos).is_empty() {
+        blocos.push(Bloco::Paragrafo(trechos));
+    }
+    blocos
+}
+
+// Imagem reduzida para exportação com as caixas detectadas desenhadas por cima
+pub fn imagem_anotada(caminho: &str, caixas: &[CaixaAnotada]) -> Result<RgbImage> {
+    let original = image::open(caminho)
+        .with_context(|| format!("Falha ao abrir a imagem {}", caminho))?
+        .to_rgb8();
+    let (largura, altura) = original.dimensions();
+    let escala = (LADO_MAXIMO_IMAGEM as f64 / largura.max(altura) as f64).min(1.0);
+    let mut imagem = if escala < 1.0 {
+        imageops::resize(
+            &original,
+            ((largura as f64 * escala).round() as u32).max(1),
+            ((altura as f64 * escala).round() as u32).max(1),
+            imageops::FilterType::Triangle,
+        )
+    } else {
+        original
+    };
+    let espessura = (imagem.width().max(imagem.height()) / 400).max(2);
+    for caixa in caixas {
+        let [x1, y1, x2, y2] = caixa.bbox.map(|v| (v * escala).round().max(0.0) as u32);
+        desenhar_retangulo(&mut imagem, x1, y1, x2, y2, espessura);
+    }
+    Ok(imagem)
+}
+
+fn desenhar_retangulo(imagem: &mut RgbImage, x1: u32, y1: u32, x2: u32, y2: u32, espessura: u32) {
+    let (largura, altura) = imagem.dimensions();
+    if largura == 0 || altura == 0 {
+        return;
+    }
+    let (x2, y2) = (x2.min(largura - 1), y2.min(altura - 1));
+    if x1 >= x2 || y1 >= y2 {
+        return;
+    }
+    for y in y1..=y2 {
+        for x in x1..=x2 {
+            let borda = x < x1 + espessura || x + espessura > x2 || y < y1 + espessura || y + espessura > y2;
+            if borda {
+                imagem.put_pixel(x, y, COR_CAIXA);
+            }
+        }
+    }
+}
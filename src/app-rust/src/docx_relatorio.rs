@@ -0,0 +1,602 @@
+// docx_relatorio.rs
+// Exportação do relatório para DOCX (Word) sem pandoc. O arquivo é montado direto como um pacote
+// OOXML (zip com os XMLs do Word) a partir do documento de documento_relatorio.rs, montado com os
+// dados do relatório e o mesmo usado no PDF: folha de rosto com projeto, prédio, responsável e data
+// da análise, tabela das fachadas, resumos e uma seção por fissura com a tabela de medidas e a
+// imagem anotada com legenda.
+//
+// A aparência pode ser personalizada como no pandoc: um reference.docx (na pasta do projeto ou em
+// src/Template/) fornece os estilos (fontes, cores, tamanhos) e o tema, e um logo.png/logo.jpg nas
+// mesmas pastas vai para a folha de rosto e para o cabeçalho. Como no pandoc, os estilos são achados
+// pelo nome interno (w:name: "heading 1", "caption", "Table Grid"...), já que o id muda conforme o
+// idioma do Word que salvou o arquivo; os que faltarem no reference.docx recebem a definição padrão.
+// Um reference.docx ilegível é erro da exportação, em vez de cair nos estilos padrão.
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context};
+use chrono::Local;
+use image::codecs::jpeg::JpegEncoder;
+use regex::Regex;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+use crate::documento_relatorio::{self, Alinhamento, Bloco, CaixaAnotada, DocumentoRelatorio, Trecho};
+
+pub const REFERENCE_FILE_NAME: &str = "reference.docx";
+const LOGO_FILE_NAMES: [&str; 3] = ["logo.png", "logo.jpg", "logo.jpeg"];
+
+// Medidas do Word: EMU nas imagens (914400 por polegada) e twips na página (1440 por polegada)
+const EMU_POR_CM: f64 = 360000.0;
+const LARGURA_MAXIMA_IMAGEM_CM: f64 = 16.0;
+const ALTURA_MAXIMA_IMAGEM_CM: f64 = 11.0;
+const QUALIDADE_JPEG: u8 = 85;
+// Largura útil da página A4 com margens de 2 cm, em twips
+const LARGURA_TEXTO_TWIPS: usize = 9638;
+
+// Pastas onde procurar reference.docx e logo: a do projeto tem prioridade sobre a global
+fn pastas_personalizacao(project_dir: &Path) -> [PathBuf; 2] {
+    [project_dir.to_path_buf(), PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src").join("Template")]
+}
+
+pub fn buscar_reference(project_dir: &Path) -> Option<PathBuf> {
+    pastas_personalizacao(project_dir).into_iter().map(|p| p.join(REFERENCE_FILE_NAME)).find(|p| p.exists())
+}
+
+pub fn buscar_logo(project_dir: &Path) -> Option<PathBuf> {
+    pastas_personalizacao(project_dir)
+        .into_iter()
+        .flat_map(|pasta| LOGO_FILE_NAMES.map(|nome| pasta.join(nome)))
+        .find(|p| p.exists())
+}
+
+fn escapar(texto: &str) -> String {
+    texto
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn run(texto: &str, negrito: bool, italico: bool) -> String {
+    let mut propriedades = String::new();
+    if negrito {
+        propriedades.push_str("<w:b/>");
+    }
+    if italico {
+        propriedades.push_str("<w:i/>");
+    }
+    let propriedades = if propriedades.is_empty() { String::new() } else { format!("<w:rPr>{}</w:rPr>", propriedades) };
+    format!(r#"<w:r>{}<w:t xml:space="preserve">{}</w:t></w:r>"#, propriedades, escapar(texto))
+}
+
+fn runs(trechos: &[Trecho]) -> String {
+    let mut xml = String::new();
+    // A quebra forçada do Markdown ("  \n") deixa espaços nas pontas das linhas
+    let mut inicio_linha = true;
+    for trecho in trechos {
+        for (i, parte) in trecho.texto.split('\n').enumerate() {
+            if i > 0 {
+                xml.push_str("<w:r><w:br/></w:r>");
+                inicio_linha = true;
+            }
+            let parte = if inicio_linha { parte.trim_start() } else { parte };
+            if !parte.is_empty() {
+                xml.push_str(&run(parte, trecho.negrito, trecho.italico));
+                inicio_linha = false;
+            }
+        }
+    }
+    xml
+}
+
+fn paragrafo(estilo: Option<&str>, conteudo: &str) -> String {
+    match estilo {
+        Some(estilo) => format!(r#"<w:p><w:pPr><w:pStyle w:val="{}"/></w:pPr>{}</w:p>"#, estilo, conteudo),
+        None => format!("<w:p>{}</w:p>", conteudo),
+    }
+}
+
+fn tabela(estilo: &str, cabecalho: &[String], linhas: &[Vec<String>], alinhamentos: &[Alinhamento]) -> String {
+    let celula = |texto: &str, negrito: bool, coluna: usize| {
+        let sombreamento = if negrito { r#"<w:tcPr><w:shd w:val="clear" w:color="auto" w:fill="E6E6E6"/></w:tcPr>"# } else { "" };
+        let alinhamento = match alinhamentos.get(coluna).copied().unwrap_or_default() {
+            Alinhamento::Esquerda => "",
+            Alinhamento::Direita => r#"<w:pPr><w:jc w:val="right"/></w:pPr>"#,
+        };
+        format!("<w:tc>{}<w:p>{}{}</w:p></w:tc>", sombreamento, alinhamento, run(texto, negrito, false))
+    };
+    let mut xml = format!(r#"<w:tbl><w:tblPr><w:tblStyle w:val="{}"/>"#, estilo);
+    xml.push_str(concat!(
+        r#"<w:tblW w:w="5000" w:type="pct"/>"#,
+        r#"<w:tblBorders><w:top w:val="single" w:sz="4" w:color="999999"/><w:left w:val="single" w:sz="4" w:color="999999"/>"#,
+        r#"<w:bottom w:val="single" w:sz="4" w:color="999999"/><w:right w:val="single" w:sz="4" w:color="999999"/>"#,
+        r#"<w:insideH w:val="single" w:sz="4" w:color="999999"/><w:insideV w:val="single" w:sz="4" w:color="999999"/></w:tblBorders>"#,
+        r#"<w:tblLayout w:type="autofit"/></w:tblPr>"#,
+    ));
+    // Toda linha (inclusive o cabeçalho) recebe o mesmo número de células, o da grade
+    let colunas = linhas.iter().map(|l| l.len()).chain([cabecalho.len()]).max().unwrap_or_default().max(1);
+    xml.push_str("<w:tblGrid>");
+    for _ in 0..colunas {
+        xml.push_str(&format!(r#"<w:gridCol w:w="{}"/>"#, LARGURA_TEXTO_TWIPS / colunas));
+    }
+    xml.push_str("</w:tblGrid>");
+    fn texto(linha: &[String], coluna: usize) -> &str {
+        linha.get(coluna).map_or("", |t| t.as_str())
+    }
+    // Cabeçalho repetido no topo de cada página quando a tabela quebra
+    xml.push_str(r#"<w:tr><w:trPr><w:tblHeader/></w:trPr>"#);
+    for c in 0..colunas {
+        xml.push_str(&celula(texto(cabecalho, c), true, c));
+    }
+    xml.push_str("</w:tr>");
+    for linha in linhas {
+        xml.push_str("<w:tr>");
+        for c in 0..colunas {
+            xml.push_str(&celula(texto(linha, c), false, c));
+        }
+        xml.push_str("</w:tr>");
+    }
+    // O Word exige um parágrafo entre tabelas seguidas
+    xml.push_str("</w:tbl><w:p/>");
+    xml
+}
+
+// Imagem embutida no texto (wp:inline), já gravada em word/media com o id de relação dado
+fn desenho(id_relacao: &str, numero: usize, largura_cm: f64, altura_cm: f64, descricao: &str) -> String {
+    let (cx, cy) = ((largura_cm * EMU_POR_CM) as u64, (altura_cm * EMU_POR_CM) as u64);
+    format!(
+        concat!(
+            r#"<w:r><w:drawing><wp:inline distT="0" distB="0" distL="0" distR="0">"#,
+            r#"<wp:extent cx="{cx}" cy="{cy}"/><wp:docPr id="{n}" name="Imagem {n}" descr="{d}"/>"#,
+            r#"<a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main">"#,
+            r#"<a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture">"#,
+            r#"<pic:pic xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture">"#,
+            r#"<pic:nvPicPr><pic:cNvPr id="{n}" name="Imagem {n}"/><pic:cNvPicPr/></pic:nvPicPr>"#,
+            r#"<pic:blipFill><a:blip r:embed="{id}"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>"#,
+            r#"<pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr>"#,
+            r#"</pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"#,
+        ),
+        cx = cx,
+        cy = cy,
+        n = numero,
+        d = escapar(descricao),
+        id = id_relacao,
+    )
+}
+
+// Tamanho de exibição que cabe nos limites mantendo a proporção
+fn tamanho_cm(largura_px: u32, altura_px: u32, largura_maxima: f64, altura_maxima: f64) -> (f64, f64) {
+    let largura = largura_maxima.min(altura_maxima * largura_px as f64 / altura_px.max(1) as f64);
+    (largura, largura * altura_px as f64 / largura_px.max(1) as f64)
+}
+
+struct Midia {
+    arquivo: String, // Nome dentro de word/media/
+    dados: Vec<u8>,
+}
+
+// Partes do pacote que dependem do conteúdo: corpo e mídias (com as relações do documento)
+struct Montagem {
+    corpo: String,
+    midias: Vec<Midia>,
+    desenhos: usize, // Cada desenho precisa de um id próprio
+}
+
+impl Montagem {
+    // Registra a mídia (JPEG ou PNG, pela extensão) e devolve o id de relação dela no documento
+    fn adicionar_midia(&mut self, dados: Vec<u8>, extensao: &str) -> String {
+        let numero = self.midias.len() + 1;
+        self.midias.push(Midia { arquivo: format!("imagem{}.{}", numero, extensao), dados });
+        format!("rIdImg{}", numero)
+    }
+
+    fn desenho(&mut self, id_relacao: &str, largura_cm: f64, altura_cm: f64, descricao: &str) -> String {
+        self.desenhos += 1;
+        desenho(id_relacao, self.desenhos, largura_cm, altura_cm, descricao)
+    }
+
+    // Cada fissura tem a própria figura (a foto com a caixa dela), mesmo quando divide a foto com outras
+    fn imagem_anotada(&mut self, caminho: &str, caixas: &[CaixaAnotada]) -> Result<(String, u32, u32)> {
+        let imagem = documento_relatorio::imagem_anotada(caminho, caixas)?;
+        let id = self.adicionar_midia(jpeg(&imagem)?, "jpeg");
+        Ok((id, imagem.width(), imagem.height()))
+    }
+}
+
+fn jpeg(imagem: &image::RgbImage) -> Result<Vec<u8>> {
+    let mut dados = Vec::new();
+    JpegEncoder::new_with_quality(&mut dados, QUALIDADE_JPEG)
+        .encode_image(imagem)
+        .context("Falha ao comprimir imagem")?;
+    Ok(dados)
+}
+
+// Logo embutido com os bytes do arquivo, sem recomprimir (um PNG mantém a transparência)
+struct Logo {
+    dados: Vec<u8>,
+    extensao: &'static str,
+    largura_px: u32,
+    altura_px: u32,
+}
+
+fn ler_logo(caminho: &Path) -> Result<Logo> {
+    let (largura_px, altura_px) = image::image_dimensions(caminho)
+        .with_context(|| format!("Logo ilegível ({})", caminho.display()))?;
+    let dados = std::fs::read(caminho).with_context(|| format!("Falha ao ler {}", caminho.display()))?;
+    let png = caminho.extension().is_some_and(|e| e.eq_ignore_ascii_case("png"));
+    Ok(Logo { dados, extensao: if png { "png" } else { "jpeg" }, largura_px, altura_px })
+}
+
+fn folha_de_rosto(documento: &DocumentoRelatorio, estilos: &Estilos, montagem: &mut Montagem, logo: Option<&Logo>) {
+    let centralizado = |conteudo: &str, estilo: Option<&str>| {
+        let estilo = estilo.map(|e| format!(r#"<w:pStyle w:val="{}"/>"#, e)).unwrap_or_default();
+        format!(r#"<w:p><w:pPr>{}<w:jc w:val="center"/><w:spacing w:before="240" w:after="240"/></w:pPr>{}</w:p>"#, estilo, conteudo)
+    };
+    if let Some(logo) = logo {
+        let (largura, altura) = tamanho_cm(logo.largura_px, logo.altura_px, 6.0, 4.0);
+        let id = montagem.adicionar_midia(logo.dados.clone(), logo.extensao);
+        let desenho = montagem.desenho(&id, largura, altura, "Logo");
+        montagem.corpo.push_str(&centralizado(&desenho, None));
+    }
+    montagem.corpo.push_str(r#"<w:p><w:pPr><w:spacing w:before="2400"/></w:pPr></w:p>"#);
+    montagem.corpo.push_str(&centralizado(&run(&documento.titulo, false, false), Some(&estilos.titulo)));
+    montagem.corpo.push_str(&centralizado(&run(&documento.projeto, false, false), Some(&estilos.subtitulo)));
+    montagem.corpo.push_str(&centralizado(&run(&documento.predio, true, false), None));
+    if !documento.empresa.is_empty() {
+        montagem.corpo.push_str(&centralizado(&run(&documento.empresa, true, false), None));
+    }
+    let mut dados = vec![format!("Data da análise: {}", documento.data_analise)];
+    if !documento.responsavel.is_empty() {
+        dados.insert(0, format!("Responsável técnico: {}", documento.responsavel));
+    }
+    dados.push(format!("Emitido em {}", Local::now().format("%d/%m/%Y")));
+    for linha in dados {
+        montagem.corpo.push_str(&centralizado(&run(&linha, false, false), None));
+    }
+    montagem.corpo.push_str(r#"<w:p><w:r><w:br w:type="page"/></w:r></w:p>"#);
+}
+
+fn montar_corpo(documento: &DocumentoRelatorio, estilos: &Estilos, logo: Option<&Logo>) -> Montagem {
+    let mut montagem = Montagem { corpo: String::new(), midias: Vec::new(), desenhos: 0 };
+    folha_de_rosto(documento, estilos, &mut montagem, logo);
+
+    // O título principal já está na folha de rosto
+    let mut titulo_na_capa = true;
+    for bloco in &documento.blocos {
+        match bloco {
+            Bloco::Titulo { nivel: 1, texto } if titulo_na_capa && *texto == documento.titulo => titulo_na_capa = false,
+            Bloco::Titulo { nivel, texto } => {
+                let estilo = &estilos.titulos[usize::from((*nivel).clamp(1, 4)) - 1];
+                montagem.corpo.push_str(&paragrafo(Some(estilo), &run(texto, false, false)));
+            }
+            Bloco::Paragrafo(trechos) => montagem.corpo.push_str(&paragrafo(None, &runs(trechos))),
+            Bloco::Item(trechos) => montagem.corpo.push_str(&format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="{}"/><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr>{}</w:p>"#,
+                estilos.lista,
+                runs(trechos)
+            )),
+            Bloco::Tabela { cabecalho, linhas, alinhamentos } => {
+                montagem.corpo.push_str(&tabela(&estilos.tabela, cabecalho, linhas, alinhamentos))
+            }
+            Bloco::Imagem { caminho, legenda, caixas } => {
+                match montagem.imagem_anotada(caminho, caixas) {
+                    Ok((id, largura_px, altura_px)) => {
+                        let (largura, altura) = tamanho_cm(largura_px, altura_px, LARGURA_MAXIMA_IMAGEM_CM, ALTURA_MAXIMA_IMAGEM_CM);
+                        let desenho = montagem.desenho(&id, largura, altura, legenda);
+                        montagem.corpo.push_str(&format!(
+                            r#"<w:p><w:pPr><w:keepNext/><w:jc w:val="center"/></w:pPr>{}</w:p>"#,
+                            desenho
+                        ));
+                        montagem.corpo.push_str(&format!(
+                            r#"<w:p><w:pPr><w:pStyle w:val="{}"/><w:jc w:val="center"/></w:pPr>{}</w:p>"#,
+                            estilos.legenda,
+                            run(legenda, false, true)
+                        ));
+                    }
+                    Err(e) => montagem.corpo.push_str(&paragrafo(None, &run(&format!("[Imagem indisponível: {:#}]", e), false, true))),
+                }
+            }
+            Bloco::Separador => montagem.corpo.push_str(
+                r#"<w:p><w:pPr><w:pBdr><w:bottom w:val="single" w:sz="6" w:space="1" w:color="BFBFBF"/></w:pBdr></w:pPr></w:p>"#,
+            ),
+        }
+    }
+    montagem
+}
+
+const NAMESPACES: &str = concat!(
+    r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" "#,
+    r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" "#,
+    r#"xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing""#,
+);
+
+fn documento_xml(corpo: &str) -> String {
+    // A4 com margens de 2 cm; a folha de rosto (titlePg) não leva cabeçalho nem rodapé
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<w:document {ns}><w:body>{corpo}"#,
+            r#"<w:sectPr><w:headerReference w:type="default" r:id="rIdCabecalho"/><w:footerReference w:type="default" r:id="rIdRodape"/>"#,
+            r#"<w:pgSz w:w="11906" w:h="16838"/><w:pgMar w:top="1134" w:right="1134" w:bottom="1134" w:left="1134" w:header="567" w:footer="567" w:gutter="0"/>"#,
+            r#"<w:titlePg/></w:sectPr></w:body></w:document>"#,
+        ),
+        ns = NAMESPACES,
+        corpo = corpo,
+    )
+}
+
+fn cabecalho_xml(documento: &DocumentoRelatorio, logo: Option<(&str, f64, f64)>) -> String {
+    // Id de desenho fora da faixa usada no corpo do documento
+    let logo = logo.map(|(id, largura, altura)| desenho(id, 100000, largura, altura, "Logo")).unwrap_or_default();
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<w:hdr {ns}><w:p><w:pPr><w:pBdr><w:bottom w:val="single" w:sz="4" w:space="1" w:color="BFBFBF"/></w:pBdr>"#,
+            r#"<w:tabs><w:tab w:val="right" w:pos="9638"/></w:tabs></w:pPr>{texto}<w:r><w:tab/></w:r>{logo}</w:p></w:hdr>"#,
+        ),
+        ns = NAMESPACES,
+        texto = run(&documento.cabecalho(), false, false),
+        logo = logo,
+    )
+}
+
+fn rodape_xml(documento: &DocumentoRelatorio) -> String {
+    // Campos PAGE e NUMPAGES: o Word calcula a numeração ao abrir
+    let campo = |nome: &str| {
+        format!(
+            r#"<w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText xml:space="preserve"> {} </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>1</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r>"#,
+            nome
+        )
+    };
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<w:ftr {ns}><w:p><w:pPr><w:tabs><w:tab w:val="right" w:pos="9638"/></w:tabs></w:pPr>"#,
+            r#"{titulo}<w:r><w:tab/></w:r>{pagina}{pagina_campo}{de}{total}</w:p></w:ftr>"#,
+        ),
+        ns = NAMESPACES,
+        titulo = run(&documento.titulo, false, false),
+        pagina = run("Página ", false, false),
+        pagina_campo = campo("PAGE"),
+        de = run(" de ", false, false),
+        total = campo("NUMPAGES"),
+    )
+}
+
+// Estilos padrão quando não há reference.docx
+const ESTILOS_XML: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+    r#"<w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:cs="Calibri"/><w:sz w:val="21"/><w:lang w:val="pt-BR"/></w:rPr></w:rPrDefault>"#,
+    r#"<w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="264" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults>"#,
+    r#"<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:rPr><w:b/><w:color w:val="1F3864"/><w:sz w:val="52"/></w:rPr></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="Subtitle"><w:name w:val="Subtitle"/><w:basedOn w:val="Normal"/><w:rPr><w:color w:val="595959"/><w:sz w:val="32"/></w:rPr></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:color w:val="1F3864"/><w:sz w:val="36"/></w:rPr></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="300" w:after="120"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:color w:val="1F3864"/><w:sz w:val="30"/></w:rPr></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="heading 3"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="80"/><w:outlineLvl w:val="2"/></w:pPr><w:rPr><w:b/><w:color w:val="2F5496"/><w:sz w:val="26"/></w:rPr></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="Heading4"><w:name w:val="heading 4"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="200" w:after="60"/><w:outlineLvl w:val="3"/></w:pPr><w:rPr><w:b/><w:i/><w:color w:val="2F5496"/><w:sz w:val="22"/></w:rPr></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="Caption"><w:name w:val="caption"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:rPr><w:i/><w:color w:val="595959"/><w:sz w:val="18"/></w:rPr></w:style>"#,
+    r#"<w:style w:type="paragraph" w:styleId="ListParagraph"><w:name w:val="List Paragraph"/><w:basedOn w:val="Normal"/><w:pPr><w:spacing w:after="40"/><w:ind w:left="720"/></w:pPr></w:style>"#,
+    r#"<w:style w:type="table" w:default="1" w:styleId="TableNormal"><w:name w:val="Normal Table"/><w:tblPr><w:tblCellMar><w:top w:w="0" w:type="dxa"/><w:left w:w="108" w:type="dxa"/><w:bottom w:w="0" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style>"#,
+    r#"<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:basedOn w:val="TableNormal"/><w:pPr><w:spacing w:after="0"/></w:pPr><w:rPr><w:sz w:val="18"/></w:rPr></w:style>"#,
+    r#"</w:styles>"#,
+);
+
+const NUMERACAO_XML: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+    r#"<w:abstractNum w:abstractNumId="0"><w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/>"#,
+    r#"<w:pPr><w:ind w:left="720" w:hanging="360"/></w:pPr></w:lvl></w:abstractNum>"#,
+    r#"<w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num></w:numbering>"#,
+);
+
+// Ids dos estilos usados no documento, resolvidos no styles.xml que vai para o pacote
+struct Estilos {
+    titulo: String,
+    subtitulo: String,
+    titulos: [String; 4],
+    legenda: String,
+    lista: String,
+    tabela: String,
+}
+
+// Estilos definidos num styles.xml: (id, nome interno, definição completa)
+fn estilos_definidos(xml: &str) -> Vec<(String, String, String)> {
+    let re_estilo = Regex::new(r#"(?s)<w:style\b([^>]*[^/])>(.*?)</w:style>"#).unwrap();
+    let re_id = Regex::new(r#"\bw:styleId="([^"]*)""#).unwrap();
+    let re_nome = Regex::new(r#"<w:name\s+w:val="([^"]*)""#).unwrap();
+    re_estilo
+        .captures_iter(xml)
+        .filter_map(|caps| {
+            let id = re_id.captures(&caps[1])?[1].to_string();
+            let nome = re_nome.captures(&caps[2])?[1].to_string();
+            Some((id, nome, caps[0].to_string()))
+        })
+        .collect()
+}
+
+// Acha cada estilo pelo nome interno; os ausentes entram com a definição padrão (se o id estiver livre)
+fn resolver_estilos(xml: &str) -> (String, Estilos) {
+    let definidos = estilos_definidos(xml);
+    let padrao = estilos_definidos(ESTILOS_XML);
+    let mut faltando = String::new();
+    let mut id = |nome: &str, id_padrao: &str| {
+        if let Some((id, _, _)) = definidos.iter().find(|(_, n, _)| n.eq_ignore_ascii_case(nome)) {
+            return id.clone();
+        }
+        if !definidos.iter().any(|(id, _, _)| id == id_padrao) {
+            if let Some((_, _, definicao)) = padrao.iter().find(|(id, _, _)| id == id_padrao) {
+                faltando.push_str(definicao);
+            }
+        }
+        id_padrao.to_string()
+    };
+    let estilos = Estilos {
+        titulo: id("Title", "Title"),
+        subtitulo: id("Subtitle", "Subtitle"),
+        titulos: [
+            id("heading 1", "Heading1"),
+            id("heading 2", "Heading2"),
+            id("heading 3", "Heading3"),
+            id("heading 4", "Heading4"),
+        ],
+        legenda: id("caption", "Caption"),
+        lista: id("List Paragraph", "ListParagraph"),
+        tabela: id("Table Grid", "TableGrid"),
+    };
+    let xml = if faltando.is_empty() {
+        xml.to_string()
+    } else {
+        xml.replacen("</w:styles>", &format!("{}</w:styles>", faltando), 1)
+    };
+    (xml, estilos)
+}
+
+// Partes aproveitadas do reference.docx: styles.xml e, se houver, o tema (fontes e cores do tema)
+struct Referencia {
+    estilos: String,
+    tema: Option<Vec<u8>>,
+}
+
+// Lê o reference.docx, se houver; os estilos padrão (sem tema) quando não há
+fn referencia(project_dir: &Path) -> Result<Referencia> {
+    let Some(reference) = buscar_reference(project_dir) else {
+        return Ok(Referencia { estilos: ESTILOS_XML.to_string(), tema: None });
+    };
+    let mut pacote = File::open(&reference)
+        .map_err(anyhow::Error::from)
+        .and_then(|arquivo| Ok(ZipArchive::new(arquivo)?))
+        .with_context(|| format!("reference.docx ilegível ({})", reference.display()))?;
+    let mut estilos = String::new();
+    pacote.by_name("word/styles.xml")
+        .map_err(anyhow::Error::from)
+        .and_then(|mut arquivo| Ok(arquivo.read_to_string(&mut estilos)?))
+        .with_context(|| format!("reference.docx sem word/styles.xml legível ({})", reference.display()))?;
+    // O tema é opcional: sem ele o Word usa o tema padrão
+    let tema = match pacote.by_name("word/theme/theme1.xml") {
+        Ok(mut arquivo) => {
+            let mut dados = Vec::new();
+            arquivo.read_to_end(&mut dados)
+                .with_context(|| format!("reference.docx com word/theme/theme1.xml ilegível ({})", reference.display()))?;
+            Some(dados)
+        }
+        Err(_) => None,
+    };
+    Ok(Referencia { estilos, tema })
+}
+
+pub fn gerar(documento: &DocumentoRelatorio, project_dir: &Path, destino: &Path) -> Result<()> {
+    // Antes de criar o arquivo, para não deixar um DOCX pela metade quando o reference.docx falha
+    let referencia = referencia(project_dir)?;
+    let (estilos_xml, estilos) = resolver_estilos(&referencia.estilos);
+    let logo = buscar_logo(project_dir).and_then(|caminho| {
+        ler_logo(&caminho).map_err(|e| eprintln!("Logo ignorado: {:#}", e)).ok()
+    });
+    let montagem = montar_corpo(documento, &estilos, logo.as_ref());
+
+    // Logo do cabeçalho, menor que o da folha de rosto; referenciado só pelas relações do cabeçalho
+    let logo_cabecalho = logo.as_ref().map(|logo| {
+        let (largura, altura) = tamanho_cm(logo.largura_px, logo.altura_px, 3.0, 1.0);
+        (format!("logo-cabecalho.{}", logo.extensao), largura, altura)
+    });
+
+    let mut relacoes_documento: String = montagem.midias
+        .iter()
+        .enumerate()
+        .map(|(i, midia)| format!(
+            r#"<Relationship Id="rIdImg{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/{}"/>"#,
+            i + 1,
+            midia.arquivo
+        ))
+        .collect();
+    let mut tipos_extras = String::new();
+    if referencia.tema.is_some() {
+        relacoes_documento.push_str(
+            r#"<Relationship Id="rIdTema" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme" Target="theme/theme1.xml"/>"#,
+        );
+        tipos_extras.push_str(
+            r#"<Override PartName="/word/theme/theme1.xml" ContentType="application/vnd.openxmlformats-officedocument.theme+xml"/>"#,
+        );
+    }
+
+    let arquivo = File::create(destino).with_context(|| format!("Falha ao criar {}", destino.display()))?;
+    let mut pacote = ZipWriter::new(arquivo);
+    let opcoes = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut gravar = |nome: &str, conteudo: &[u8]| -> Result<()> {
+        pacote.start_file(nome, opcoes)?;
+        pacote.write_all(conteudo)?;
+        Ok(())
+    };
+
+    gravar("[Content_Types].xml", format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+            r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+            r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+            r#"<Default Extension="jpeg" ContentType="image/jpeg"/>"#,
+            r#"<Default Extension="png" ContentType="image/png"/>"#,
+            r#"<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>"#,
+            r#"<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>"#,
+            r#"<Override PartName="/word/numbering.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml"/>"#,
+            r#"<Override PartName="/word/header1.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.header+xml"/>"#,
+            r#"<Override PartName="/word/footer1.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.footer+xml"/>"#,
+            r#"{}</Types>"#,
+        ),
+        tipos_extras
+    ).as_bytes())?;
+    gravar("_rels/.rels", concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+        r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>"#,
+        r#"</Relationships>"#,
+    ).as_bytes())?;
+    gravar("word/_rels/document.xml.rels", format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+            r#"<Relationship Id="rIdEstilos" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+            r#"<Relationship Id="rIdNumeracao" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/numbering" Target="numbering.xml"/>"#,
+            r#"<Relationship Id="rIdCabecalho" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="header1.xml"/>"#,
+            r#"<Relationship Id="rIdRodape" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/footer" Target="footer1.xml"/>"#,
+            r#"{}</Relationships>"#,
+        ),
+        relacoes_documento
+    ).as_bytes())?;
+    gravar("word/document.xml", documento_xml(&montagem.corpo).as_bytes())?;
+    gravar("word/styles.xml", estilos_xml.as_bytes())?;
+    if let Some(tema) = &referencia.tema {
+        gravar("word/theme/theme1.xml", tema)?;
+    }
+    gravar("word/numbering.xml", NUMERACAO_XML.as_bytes())?;
+    gravar("word/header1.xml", cabecalho_xml(documento, logo_cabecalho.as_ref().map(|(_, largura, altura)| ("rIdLogo", *largura, *altura))).as_bytes())?;
+    if let (Some(logo), Some((arquivo, _, _))) = (&logo, &logo_cabecalho) {
+        gravar("word/_rels/header1.xml.rels", format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+                r#"<Relationship Id="rIdLogo" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/{}"/>"#,
+                r#"</Relationships>"#,
+            ),
+            arquivo
+        ).as_bytes())?;
+        gravar(&format!("word/media/{}", arquivo), &logo.dados)?;
+    }
+    gravar("word/footer1.xml", rodape_xml(documento).as_bytes())?;
+    for midia in &montagem.midias {
+        gravar(&format!("word/media/{}", midia.arquivo), &midia.dados)?;
+    }
+    pacote.finish().with_context(|| format!("Falha ao finalizar {}", destino.display()))?;
+    Ok(())
+}
+
+// Resumo da personalização encontrada, para a tela de relatório
+pub fn descricao_personalizacao(project_dir: &Path) -> String {
+    let nome = |p: Option<PathBuf>| p.map(|p| p.display().to_string());
+    format!(
+        "estilos: {}; logo: {}",
+        nome(buscar_reference(project_dir)).unwrap_or_else(|| "padrão".to_string()),
+        nome(buscar_logo(project_dir)).unwrap_or_else(|| "nenhum".to_string())
+    )
+}
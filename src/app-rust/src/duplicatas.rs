@@ -0,0 +1,117 @@
+// duplicatas.rs
+// Detecção de fotos duplicadas/quase duplicadas por hash perceptual (dHash).
+// Os grupos são formados dentro de uma mesma fachada; em cada grupo uma imagem é mantida e as
+// demais ficam marcadas com `duplicata_de` apontando para ela.
+use std::path::Path;
+use std::fs;
+use anyhow::{Result, Context};
+use image::DynamicImage;
+use image::imageops::FilterType;
+use crate::image_processor::Fachada;
+
+// Distância de Hamming máxima (em bits, de 64) para considerar duas imagens quase iguais
+pub const DISTANCIA_MAXIMA_HASH: u32 = 10;
+
+pub fn calcular_hash(path: &Path) -> Result<u64> {
+    let imagem = image::open(path)
+        .with_context(|| format!("Falha ao abrir imagem {}", path.display()))?;
+    Ok(hash_imagem(&imagem))
+}
+
+// Hash de diferença: reduz para 9x8 em tons de cinza e compara cada pixel com o vizinho da direita
+pub fn hash_imagem(imagem: &DynamicImage) -> u64 {
+    let reduzida = imagem.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let esquerda = reduzida.get_pixel(x, y)[0];
+            let direita = reduzida.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | (esquerda > direita) as u64;
+        }
+    }
+    hash
+}
+
+pub fn distancia_hamming(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+// Agrupa as imagens da fachada cujo hash está próximo do primeiro membro do grupo.
+// Retorna apenas grupos com mais de uma imagem (índices em `fachada.imagens`).
+fn agrupar(fachada: &Fachada) -> Vec<Vec<usize>> {
+    let mut grupos: Vec<(u64, Vec<usize>)> = Vec::new();
+    for (i, imagem) in fachada.imagens.iter().enumerate() {
+        let Some(hash) = imagem.phash else { continue };
+        match grupos.iter_mut().find(|(referencia, _)| distancia_hamming(*referencia, hash) <= DISTANCIA_MAXIMA_HASH) {
+            Some((_, membros)) => membros.push(i),
+            None => grupos.push((hash, vec![i])),
+        }
+    }
+    grupos.into_iter().map(|(_, membros)| membros).filter(|m| m.len() > 1).collect()
+}
+
+// Marca as duplicatas da fachada, mantendo em cada grupo a imagem com o maior arquivo
+// (mais detalhe). Remarca a fachada inteira. Retorna quantas imagens ficaram marcadas.
+pub fn marcar_duplicatas(fachada: &mut Fachada) -> usize {
+    for imagem in fachada.imagens.iter_mut() {
+        imagem.duplicata_de = None;
+    }
+
+    let mut marcadas = 0;
+    for grupo in agrupar(fachada) {
+        let tamanho = |i: &usize| fs::metadata(&fachada.imagens[*i].path).map(|m| m.len()).unwrap_or(0);
+        let Some(&mantida) = grupo.iter().max_by_key(|i| tamanho(i)) else { continue };
+        let nome_mantida = fachada.imagens[mantida].file_name.clone();
+        for &i in grupo.iter().filter(|&&i| i != mantida) {
+            fachada.imagens[i].duplicata_de = Some(nome_mantida.clone());
+            marcadas += 1;
+        }
+    }
+    marcadas
+}
+
+// Grupos já marcados: (imagem mantida, duplicatas)
+pub fn grupos_marcados(fachada: &Fachada) -> Vec<(String, Vec<String>)> {
+    let mut grupos: Vec<(String, Vec<String>)> = Vec::new();
+    for imagem in &fachada.imagens {
+        let Some(mantida) = &imagem.duplicata_de else { continue };
+        match grupos.iter_mut().find(|(m, _)| m == mantida) {
+            Some((_, duplicatas)) => duplicatas.push(imagem.file_name.clone()),
+            None => grupos.push((mantida.clone(), vec![imagem.file_name.clone()])),
+        }
+    }
+    grupos
+}
+
+// Passa a manter `file_name` no grupo do qual ele faz parte
+pub fn manter(fachada: &mut Fachada, file_name: &str) {
+    let Some(atual) = fachada.imagens.iter().find(|im| im.file_name == file_name) else { return };
+    let mantida_anterior = atual.duplicata_de.clone().unwrap_or_else(|| file_name.to_string());
+
+    for imagem in fachada.imagens.iter_mut() {
+        let no_grupo = imagem.file_name == mantida_anterior
+            || imagem.duplicata_de.as_deref() == Some(mantida_anterior.as_str());
+        if !no_grupo {
+            continue;
+        }
+        imagem.duplicata_de = if imagem.file_name == file_name { None } else { Some(file_name.to_string()) };
+    }
+}
+
+// Retira a imagem do grupo de duplicatas
+pub fn desmarcar(fachada: &mut Fachada, file_name: &str) {
+    if let Some(imagem) = fachada.imagens.iter_mut().find(|im| im.file_name == file_name) {
+        imagem.duplicata_de = None;
+    }
+}
+
+// Desfaz marcações que apontam para imagens que não estão mais na fachada (movidas ou renomeadas)
+pub fn normalizar(fachada: &mut Fachada) {
+    let nomes: Vec<String> = fachada.imagens.iter().map(|im| im.file_name.clone()).collect();
+    for imagem in fachada.imagens.iter_mut() {
+        if imagem.duplicata_de.as_ref().is_some_and(|m| !nomes.contains(m) || *m == imagem.file_name) {
+            imagem.duplicata_de = None;
+        }
+    }
+}
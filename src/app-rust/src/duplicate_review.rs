@@ -0,0 +1,233 @@
+// duplicate_review.rs
+// Tela de revisão das fotos duplicadas: mostra, por fachada, os grupos de fotos quase iguais,
+// permite escolher qual manter e decidir se as duplicatas ficam fora da detecção e das estatísticas.
+use dioxus::prelude::*;
+use dioxus_router::prelude::*;
+use std::path::PathBuf;
+use anyhow::anyhow;
+use crate::duplicatas;
+use crate::grouping_review::{reorganizar, use_indice, concluir_operacao};
+use crate::project_index::{self, ProjectIndex};
+use crate::Route;
+
+// Grupo exibido na tela: prédio, pasta da fachada, imagem mantida e duplicatas (caminhos)
+#[derive(Debug, Clone, PartialEq)]
+struct GrupoDuplicatas {
+    predio_id: String,
+    predio_nome: String,
+    pasta: String,
+    fachada_nome: String,
+    mantida: PathBuf,
+    duplicatas: Vec<PathBuf>,
+}
+
+fn grupos_do_indice(index: &ProjectIndex) -> Vec<GrupoDuplicatas> {
+    let mut grupos = Vec::new();
+    for predio in &index.predios {
+        for (pasta, fachada) in &predio.fachadas {
+            let caminho = |file_name: &str| {
+                fachada.imagens.iter().find(|im| im.file_name == file_name).map(|im| im.path.clone())
+            };
+            for (mantida, duplicatas) in duplicatas::grupos_marcados(fachada) {
+                let Some(mantida) = caminho(&mantida) else { continue };
+                grupos.push(GrupoDuplicatas {
+                    predio_id: predio.id.clone(),
+                    predio_nome: predio.nome.clone(),
+                    pasta: pasta.clone(),
+                    fachada_nome: fachada.nome.clone(),
+                    mantida,
+                    duplicatas: duplicatas.iter().filter_map(|d| caminho(d)).collect(),
+                });
+            }
+        }
+    }
+    grupos
+}
+
+fn nome_arquivo(path: &PathBuf) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+#[derive(Props, PartialEq, Clone)]
+pub struct DuplicateReviewProps {
+    pub project_name: String,
+}
+
+#[component]
+pub fn DuplicateReview(props: DuplicateReviewProps) -> Element {
+    let project_dir: PathBuf = project_index::project_dir(&props.project_name);
+    let status = use_signal(String::new);
+    let indice = use_indice(&project_dir, status);
+    let navigator = use_navigator();
+
+    let excluir_duplicatas = indice.read().excluir_duplicatas;
+    let grupos = grupos_do_indice(&indice.read());
+    let total_duplicatas: usize = grupos.iter().map(|g| g.duplicatas.len()).sum();
+
+    rsx! {
+        document::Stylesheet { href: asset!("/assets/tailwind.css") }
+
+        div { class: "min-h-screen bg-gray-100 text-gray-900 font-sans",
+            document::Link {
+                href: "https://fonts.googleapis.com/icon?family=Material+Icons",
+                rel: "stylesheet"
+            }
+
+            header { class: "flex items-center justify-between bg-blue-600 text-black p-4 shadow",
+                div { class: "flex items-center gap-4",
+                    i { class: "material-icons", "filter_none" }
+                    h1 { class: "text-xl font-bold", "Fotos Duplicadas: {props.project_name}" }
+                }
+                div { class: "flex items-center gap-2",
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        onclick: {
+                            let project_name = props.project_name.clone();
+                            move |_| { navigator.push(Route::GroupingReview { project_name: project_name.clone() }); }
+                        },
+                        i { class: "material-icons", "photo_library" }
+                        "Agrupamento"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        onclick: {
+                            let project_name = props.project_name.clone();
+                            move |_| { navigator.push(Route::GraphView { project_name: project_name.clone() }); }
+                        },
+                        i { class: "material-icons", "bar_chart" }
+                        "Ver Gráficos"
+                    }
+                }
+            }
+
+            div { class: "container mx-auto px-4 py-8 max-w-6xl space-y-6",
+                p { class: "text-gray-600",
+                    "Fotos quase iguais da mesma fachada são agrupadas. Em cada grupo, escolha a foto a manter; as demais contam como duplicatas."
+                }
+
+                div { class: "bg-white rounded-lg shadow-md p-6 flex flex-wrap items-center gap-4",
+                    label { class: "flex items-center gap-2 text-gray-700 flex-1",
+                        input {
+                            r#type: "checkbox",
+                            checked: excluir_duplicatas,
+                            onchange: {
+                                let project_dir = project_dir.clone();
+                                move |e: FormEvent| {
+                                    let excluir = e.checked();
+                                    let resultado = reorganizar(&project_dir, |index| {
+                                        index.excluir_duplicatas = excluir;
+                                        Ok(())
+                                    })
+                                    .map(|_| if excluir {
+                                        "Duplicatas serão ignoradas na detecção e nas estatísticas.".to_string()
+                                    } else {
+                                        "Duplicatas voltam a ser consideradas na detecção e nas estatísticas.".to_string()
+                                    });
+                                    concluir_operacao(resultado, &project_dir, indice, status);
+                                }
+                            }
+                        }
+                        "Excluir duplicatas da detecção e das estatísticas ({total_duplicatas} foto(s))"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 flex items-center gap-2",
+                        onclick: {
+                            let project_dir = project_dir.clone();
+                            move |_| {
+                                let resultado = reorganizar(&project_dir, |index| Ok(index.marcar_duplicatas()))
+                                    .map(|n| format!("{} duplicata(s) encontrada(s).", n));
+                                concluir_operacao(resultado, &project_dir, indice, status);
+                            }
+                        },
+                        i { class: "material-icons", "refresh" }
+                        "Detectar novamente"
+                    }
+                }
+
+                if !status.read().is_empty() {
+                    p { class: "text-center text-gray-700 py-2 px-4 border border-gray-300 bg-white rounded-md", "{status}" }
+                }
+
+                if grupos.is_empty() {
+                    p { class: "text-center text-gray-500", "Nenhuma foto duplicada encontrada." }
+                }
+
+                for grupo in grupos.into_iter() {
+                    div { key: "{grupo.predio_id}-{grupo.pasta}-{grupo.mantida.display()}", class: "bg-white rounded-lg shadow-md p-6",
+                        h2 { class: "text-lg font-semibold mb-4 flex items-center gap-2",
+                            i { class: "material-icons text-blue-500", "apartment" }
+                            "{grupo.predio_nome} / {grupo.fachada_nome}"
+                        }
+                        div { class: "grid grid-cols-2 sm:grid-cols-3 md:grid-cols-4 lg:grid-cols-6 gap-4",
+                            div {
+                                div { class: "aspect-w-4 aspect-h-3 bg-gray-200 rounded-lg overflow-hidden ring-4 ring-green-500",
+                                    img {
+                                        src: "file://{grupo.mantida.display()}",
+                                        class: "w-full h-full object-cover",
+                                        alt: "{nome_arquivo(&grupo.mantida)}"
+                                    }
+                                }
+                                p { class: "mt-2 text-sm text-gray-600 truncate", "{nome_arquivo(&grupo.mantida)}" }
+                                p { class: "text-xs text-green-600 font-semibold", "Mantida" }
+                            }
+                            for duplicata in grupo.duplicatas.into_iter() {
+                                div { key: "{duplicata.display()}",
+                                    div { class: "aspect-w-4 aspect-h-3 bg-gray-200 rounded-lg overflow-hidden opacity-60",
+                                        img {
+                                            src: "file://{duplicata.display()}",
+                                            class: "w-full h-full object-cover",
+                                            alt: "{nome_arquivo(&duplicata)}"
+                                        }
+                                    }
+                                    p { class: "mt-2 text-sm text-gray-600 truncate", "{nome_arquivo(&duplicata)}" }
+                                    div { class: "flex flex-col gap-1 mt-1",
+                                        button {
+                                            class: "px-2 py-1 text-xs bg-blue-600 text-white rounded-md hover:bg-blue-700",
+                                            onclick: {
+                                                let project_dir = project_dir.clone();
+                                                let (predio_id, pasta) = (grupo.predio_id.clone(), grupo.pasta.clone());
+                                                let file_name = nome_arquivo(&duplicata);
+                                                move |_| {
+                                                    let resultado = reorganizar(&project_dir, |index| {
+                                                        let fachada = index.predio_mut(&predio_id)
+                                                            .and_then(|p| p.fachadas.get_mut(&pasta))
+                                                            .ok_or_else(|| anyhow!("Fachada {} não encontrada", pasta))?;
+                                                        duplicatas::manter(fachada, &file_name);
+                                                        Ok(())
+                                                    })
+                                                    .map(|_| format!("{} passa a ser a foto mantida do grupo.", file_name));
+                                                    concluir_operacao(resultado, &project_dir, indice, status);
+                                                }
+                                            },
+                                            "Manter esta"
+                                        }
+                                        button {
+                                            class: "px-2 py-1 text-xs bg-gray-200 text-gray-700 rounded-md hover:bg-gray-300",
+                                            onclick: {
+                                                let project_dir = project_dir.clone();
+                                                let (predio_id, pasta) = (grupo.predio_id.clone(), grupo.pasta.clone());
+                                                let file_name = nome_arquivo(&duplicata);
+                                                move |_| {
+                                                    let resultado = reorganizar(&project_dir, |index| {
+                                                        let fachada = index.predio_mut(&predio_id)
+                                                            .and_then(|p| p.fachadas.get_mut(&pasta))
+                                                            .ok_or_else(|| anyhow!("Fachada {} não encontrada", pasta))?;
+                                                        duplicatas::desmarcar(fachada, &file_name);
+                                                        Ok(())
+                                                    })
+                                                    .map(|_| format!("{} não é mais tratada como duplicata.", file_name));
+                                                    concluir_operacao(resultado, &project_dir, indice, status);
+                                                }
+                                            },
+                                            "Não é duplicata"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// edicao_relatorio.rs
+// Seções editáveis do relatório. O template marca com comentários HTML os trechos que o app não tem
+// como preencher (conclusões, recomendações):
+//
+//     <!-- secao:conclusao_geral -->
+//     texto escrito pelo engenheiro
+//     <!-- fim:conclusao_geral -->
+//
+// Ao gerar uma nova versão, o texto entre os marcadores é levado do Markdown atual para o novo; o
+// restante volta a refletir os dados do projeto. Os comentários não aparecem no HTML nem no pandoc.
+use std::collections::BTreeMap;
+
+fn inicio_secao(linha: &str) -> Option<&str> {
+    linha.trim().strip_prefix("<!-- secao:")?.strip_suffix("-->").map(str::trim)
+}
+
+fn fim_secao(linha: &str, nome: &str) -> bool {
+    linha.trim() == format!("<!-- fim:{} -->", nome)
+}
+
+// Texto de cada seção marcada (sem as linhas em branco das pontas)
+pub fn extrair_secoes(markdown: &str) -> BTreeMap<String, String> {
+    let mut secoes = BTreeMap::new();
+    let mut linhas = markdown.lines();
+    while let Some(linha) = linhas.next() {
+        let Some(nome) = inicio_secao(linha) else { continue };
+        let conteudo: Vec<&str> = linhas.by_ref().take_while(|l| !fim_secao(l, nome)).collect();
+        secoes.insert(nome.to_string(), conteudo.join("\n").trim_matches('\n').to_string());
+    }
+    secoes
+}
+
+// Substitui o conteúdo das seções do Markdown pelas versões dadas; seções em branco são ignoradas
+// para não apagar o que o template gerou
+pub fn aplicar_secoes(markdown: &str, secoes: &BTreeMap<String, String>) -> String {
+    let mut saida: Vec<&str> = Vec::new();
+    let mut linhas = markdown.lines();
+    while let Some(linha) = linhas.next() {
+        saida.push(linha);
+        let Some(nome) = inicio_secao(linha) else { continue };
+        let substituto = secoes.get(nome).filter(|texto| !texto.trim().is_empty());
+        if let Some(texto) = substituto {
+            saida.extend(texto.lines());
+        }
+        // O conteúdo gerado só fica quando não há substituto; o marcador de fim sempre fica
+        for interna in linhas.by_ref() {
+            if fim_secao(interna, nome) {
+                saida.push(interna);
+                break;
+            }
+            if substituto.is_none() {
+                saida.push(interna);
+            }
+        }
+    }
+    let mut resultado = saida.join("\n");
+    if markdown.ends_with('\n') {
+        resultado.push('\n');
+    }
+    resultado
+}
@@ -0,0 +1,268 @@
+// exportacao_dataset.rs
+// Exporta as detecções revisadas (aceitas, reclassificadas e desenhadas à mão) como dataset de
+// treino: formato YOLO (images/ e labels/ divididos em train/val, com data.yaml) e XML Pascal VOC
+// compatível com o labelImg, para que as correções de campo alimentem o próximo modelo.
+//
+// Só entram imagens com a revisão concluída: alguma decisão e nenhuma detecção pendente (uma
+// fissura pendente sairia sem rótulo, como falso negativo no treino). Uma imagem revisada em que
+// tudo foi rejeitado vira exemplo negativo (arquivo de rótulos vazio).
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::fs;
+use anyhow::{Result, Context, anyhow, bail};
+use crate::deteccoes;
+use crate::manual_processor::{ImageAnalysisResult, FissuraData};
+use crate::project_index::{ProjectIndex, caminho_relativo_imagens};
+use crate::project_settings::ProjectSettings;
+use crate::anotacoes::normalizar_classe;
+use crate::revisao::{RevisaoProjeto, StatusRevisao};
+use crate::taxonomia::Taxonomia;
+
+// Fração aproximada das imagens que vai para validação
+pub const FRACAO_VALIDACAO_PADRAO: f64 = 0.2;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResumoExportacao {
+    pub destino: PathBuf,
+    pub imagens_treino: usize,
+    pub imagens_validacao: usize,
+    pub caixas: usize,
+    pub ignoradas_sem_caixa: usize, // Detecções aceitas sem bbox (resultados antigos)
+    pub imagens_pendentes: usize, // Imagens revisadas em parte, deixadas de fora
+}
+
+impl ResumoExportacao {
+    pub fn descricao(&self) -> String {
+        let mut texto = format!(
+            "Dataset exportado para {}: {} imagem(ns) de treino, {} de validação, {} caixa(s).",
+            self.destino.display(), self.imagens_treino, self.imagens_validacao, self.caixas
+        );
+        if self.ignoradas_sem_caixa > 0 {
+            texto.push_str(&format!(" {} detecção(ões) sem caixa foram ignoradas.", self.ignoradas_sem_caixa));
+        }
+        if self.imagens_pendentes > 0 {
+            texto.push_str(&format!(
+                " {} imagem(ns) com detecções ainda pendentes de revisão ficaram de fora.",
+                self.imagens_pendentes
+            ));
+        }
+        texto
+    }
+}
+
+// Imagem pronta para exportar: nome único no dataset e caixas em pixels
+struct Amostra {
+    origem: PathBuf,
+    nome: String,
+    largura: u32,
+    altura: u32,
+    caixas: Vec<(usize, [f64; 4])>,
+}
+
+// Ordem das classes: as da taxonomia do modelo, na ordem dos índices (o modelo retreinado mantém
+// os mesmos ids), depois as demais em ordem alfabética
+fn classes_do_dataset(resultados: &[ImageAnalysisResult], taxonomia: &Taxonomia) -> Vec<String> {
+    let mut extras: Vec<String> = resultados
+        .iter()
+        .flat_map(|r| r.fissura.iter().map(|f| normalizar_classe(&f.name)))
+        .collect();
+    extras.sort();
+    taxonomia.com_extras(extras)
+}
+
+fn aceita(fissura: &FissuraData) -> bool {
+    fissura.revisao.as_ref().is_some_and(|r| r.status == StatusRevisao::Aceita)
+}
+
+// Nome do arquivo no dataset: caminho relativo achatado, para não colidir entre fachadas
+fn nome_unico(image_path: &Path) -> String {
+    caminho_relativo_imagens(image_path)
+        .unwrap_or_else(|| image_path.file_name().map(PathBuf::from).unwrap_or_default())
+        .to_string_lossy()
+        .replace(['/', '\\', ' '], "_")
+}
+
+// Divisão determinística (FNV-1a do nome), para que reexportar não embaralhe treino e validação
+fn vai_para_validacao(nome: &str, fracao_validacao: f64) -> bool {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in nome.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    (hash % 1000) as f64 / 1000.0 < fracao_validacao
+}
+
+// Detecções do modelo na imagem (inclusive as abaixo da política) ainda sem decisão do revisor
+fn tem_pendentes(bruta: Option<&ImageAnalysisResult>, revisao: &RevisaoProjeto) -> bool {
+    let Some(bruta) = bruta else { return false };
+    let revisao_imagem = revisao.imagem(Path::new(&bruta.path));
+    bruta.fissura.iter().any(|f| {
+        revisao_imagem
+            .and_then(|r| r.decisao(f))
+            .is_none_or(|d| d.status == StatusRevisao::Pendente)
+    })
+}
+
+fn amostras(
+    brutas: &[ImageAnalysisResult],
+    resultados: &[ImageAnalysisResult],
+    revisao: &RevisaoProjeto,
+    classes: &[String],
+    resumo: &mut ResumoExportacao,
+) -> Result<Vec<Amostra>> {
+    let brutas: HashMap<&str, &ImageAnalysisResult> = brutas.iter().map(|r| (r.path.as_str(), r)).collect();
+    let mut amostras = Vec::new();
+    for resultado in resultados {
+        let origem = PathBuf::from(&resultado.path);
+        let revisada = revisao.imagem(&origem).is_some_and(|r| !r.decisoes.is_empty() || !r.caixas_manuais.is_empty());
+        if !revisada {
+            continue;
+        }
+        if tem_pendentes(brutas.get(resultado.path.as_str()).copied(), revisao) {
+            resumo.imagens_pendentes += 1;
+            continue;
+        }
+        let (largura, altura) = match (resultado.width, resultado.height) {
+            (Some(w), Some(h)) => (w, h),
+            _ => image::image_dimensions(&origem)
+                .with_context(|| format!("Falha ao ler dimensões de {}", origem.display()))?,
+        };
+        let mut caixas = Vec::new();
+        for fissura in resultado.fissura.iter().filter(|f| aceita(f)) {
+            let Some(bbox) = fissura.bbox else {
+                resumo.ignoradas_sem_caixa += 1;
+                continue;
+            };
+            let classe = normalizar_classe(&fissura.name);
+            let id = classes.iter().position(|c| *c == classe)
+                .ok_or_else(|| anyhow!("Classe '{}' de {} não está nas classes do dataset", classe, origem.display()))?;
+            caixas.push((id, bbox));
+        }
+        amostras.push(Amostra { nome: nome_unico(&origem), origem, largura, altura, caixas });
+    }
+    Ok(amostras)
+}
+
+fn linha_yolo(id: usize, bbox: &[f64; 4], largura: u32, altura: u32) -> String {
+    let (w, h) = (largura as f64, altura as f64);
+    let x0 = bbox[0].clamp(0.0, w);
+    let y0 = bbox[1].clamp(0.0, h);
+    let x1 = bbox[2].clamp(0.0, w);
+    let y1 = bbox[3].clamp(0.0, h);
+    format!(
+        "{} {:.6} {:.6} {:.6} {:.6}",
+        id,
+        (x0 + x1) / 2.0 / w,
+        (y0 + y1) / 2.0 / h,
+        (x1 - x0) / w,
+        (y1 - y0) / h
+    )
+}
+
+fn escapar_xml(texto: &str) -> String {
+    texto
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Mesmo layout que o labelImg grava ao salvar em Pascal VOC
+fn xml_voc(amostra: &Amostra, pasta: &str, caminho_imagem: &Path, classes: &[String]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<annotation>\n");
+    xml.push_str(&format!("\t<folder>{}</folder>\n", escapar_xml(pasta)));
+    xml.push_str(&format!("\t<filename>{}</filename>\n", escapar_xml(&amostra.nome)));
+    xml.push_str(&format!("\t<path>{}</path>\n", escapar_xml(&caminho_imagem.to_string_lossy())));
+    xml.push_str("\t<source>\n\t\t<database>Unknown</database>\n\t</source>\n");
+    xml.push_str(&format!(
+        "\t<size>\n\t\t<width>{}</width>\n\t\t<height>{}</height>\n\t\t<depth>3</depth>\n\t</size>\n",
+        amostra.largura, amostra.altura
+    ));
+    xml.push_str("\t<segmented>0</segmented>\n");
+    for (id, bbox) in &amostra.caixas {
+        xml.push_str("\t<object>\n");
+        xml.push_str(&format!("\t\t<name>{}</name>\n", escapar_xml(&classes[*id])));
+        xml.push_str("\t\t<pose>Unspecified</pose>\n\t\t<truncated>0</truncated>\n\t\t<difficult>0</difficult>\n");
+        xml.push_str(&format!(
+            "\t\t<bndbox>\n\t\t\t<xmin>{}</xmin>\n\t\t\t<ymin>{}</ymin>\n\t\t\t<xmax>{}</xmax>\n\t\t\t<ymax>{}</ymax>\n\t\t</bndbox>\n",
+            bbox[0].max(0.0).round() as i64,
+            bbox[1].max(0.0).round() as i64,
+            bbox[2].min(amostra.largura as f64).round() as i64,
+            bbox[3].min(amostra.altura as f64).round() as i64
+        ));
+        xml.push_str("\t</object>\n");
+    }
+    xml.push_str("</annotation>\n");
+    xml
+}
+
+// Mesmo formato do Dataset/dataset.yaml usado pelo yolo_train.py
+fn data_yaml(destino: &Path, classes: &[String]) -> String {
+    let mut yaml = format!(
+        "path: {}  # Root directory of your dataset\ntrain: images/train  # Path to training images\nval: images/val      # Path to validation images\n\nnames:\n",
+        destino.display()
+    );
+    for (id, classe) in classes.iter().enumerate() {
+        yaml.push_str(&format!("  {}: {}\n", id, classe));
+    }
+    yaml
+}
+
+pub fn exportar(project_dir: &Path, destino: &Path, fracao_validacao: f64) -> Result<ResumoExportacao> {
+    let brutas = deteccoes::carregar_brutas(project_dir)?;
+    let index = ProjectIndex::abrir(project_dir)?;
+    let settings = ProjectSettings::load(project_dir)?;
+    let revisao = RevisaoProjeto::load(project_dir)?;
+    let resultados = deteccoes::filtrar(&brutas, &index, &settings.politica, &revisao);
+
+    let classes = classes_do_dataset(&resultados, &Taxonomia::do_projeto(project_dir));
+    let mut resumo = ResumoExportacao { destino: destino.to_path_buf(), ..Default::default() };
+    let amostras = amostras(&brutas, &resultados, &revisao, &classes, &mut resumo)?;
+    if amostras.is_empty() {
+        bail!("Nenhuma imagem com a revisão concluída para exportar. Revise todas as detecções de ao menos uma imagem antes de gerar o dataset.");
+    }
+
+    for divisao in ["train", "val"] {
+        for pasta in ["images", "labels", "annotations"] {
+            let dir = destino.join(pasta).join(divisao);
+            fs::create_dir_all(&dir).with_context(|| format!("Falha ao criar {}", dir.display()))?;
+        }
+    }
+
+    for amostra in &amostras {
+        let divisao = if vai_para_validacao(&amostra.nome, fracao_validacao) {
+            resumo.imagens_validacao += 1;
+            "val"
+        } else {
+            resumo.imagens_treino += 1;
+            "train"
+        };
+        let imagem_destino = destino.join("images").join(divisao).join(&amostra.nome);
+        fs::copy(&amostra.origem, &imagem_destino)
+            .with_context(|| format!("Falha ao copiar {} para {}", amostra.origem.display(), imagem_destino.display()))?;
+
+        let stem = Path::new(&amostra.nome).file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let linhas: Vec<String> = amostra.caixas
+            .iter()
+            .map(|(id, bbox)| linha_yolo(*id, bbox, amostra.largura, amostra.altura))
+            .collect();
+        let label_path = destino.join("labels").join(divisao).join(format!("{}.txt", stem));
+        fs::write(&label_path, linhas.join("\n"))
+            .with_context(|| format!("Falha ao salvar {}", label_path.display()))?;
+
+        let xml_path = destino.join("annotations").join(divisao).join(format!("{}.xml", stem));
+        fs::write(&xml_path, xml_voc(amostra, divisao, &imagem_destino, &classes))
+            .with_context(|| format!("Falha ao salvar {}", xml_path.display()))?;
+
+        resumo.caixas += amostra.caixas.len();
+    }
+
+    fs::write(destino.join("data.yaml"), data_yaml(destino, &classes))
+        .with_context(|| format!("Falha ao salvar data.yaml em {}", destino.display()))?;
+    // classes.txt na ordem dos ids, como o labelImg espera para o modo YOLO
+    fs::write(destino.join("classes.txt"), classes.join("\n"))
+        .with_context(|| format!("Falha ao salvar classes.txt em {}", destino.display()))?;
+
+    Ok(resumo)
+}
@@ -0,0 +1,189 @@
+// gazetteer.rs
+// Geocodificação reversa offline: sugere um endereço para o centroide de cada prédio
+// a partir de uma lista de endereços importada (CSV ou extrato .osm do OpenStreetMap).
+use std::collections::HashMap;
+use std::path::Path;
+use std::fs;
+use anyhow::{Result, Context, anyhow};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use crate::image_processor::{Location, haversine_distance};
+
+pub const GAZETTEER_FILE_NAME: &str = "gazetteer.csv";
+
+// Distância máxima (metros) para aceitar um endereço como sugestão
+pub const DISTANCIA_MAXIMA_SUGESTAO: f64 = 150.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnderecoReferencia {
+    pub endereco: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl EnderecoReferencia {
+    fn location(&self) -> Location {
+        Location { latitude: self.latitude, longitude: self.longitude }
+    }
+}
+
+// Lê um CSV com cabeçalho contendo colunas de endereço, latitude e longitude
+// (aceita "endereco"/"endereço"/"address", "latitude"/"lat" e "longitude"/"lon"/"lng")
+pub fn carregar_csv(path: &Path) -> Result<Vec<EnderecoReferencia>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .with_context(|| format!("Falha ao abrir {}", path.display()))?;
+
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.trim().to_lowercase()).collect();
+    let coluna = |nomes: &[&str]| headers.iter().position(|h| nomes.contains(&h.as_str()));
+    let col_endereco = coluna(&["endereco", "endereço", "address"])
+        .ok_or_else(|| anyhow!("Coluna de endereço não encontrada em {}", path.display()))?;
+    let col_lat = coluna(&["latitude", "lat"])
+        .ok_or_else(|| anyhow!("Coluna de latitude não encontrada em {}", path.display()))?;
+    let col_lon = coluna(&["longitude", "lon", "lng"])
+        .ok_or_else(|| anyhow!("Coluna de longitude não encontrada em {}", path.display()))?;
+
+    let mut enderecos = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let endereco = record.get(col_endereco).unwrap_or_default().trim().to_string();
+        let lat = record.get(col_lat).and_then(|v| v.trim().parse::<f64>().ok());
+        let lon = record.get(col_lon).and_then(|v| v.trim().parse::<f64>().ok());
+        if let (false, Some(latitude), Some(longitude)) = (endereco.is_empty(), lat, lon) {
+            enderecos.push(EnderecoReferencia { endereco, latitude, longitude });
+        }
+    }
+    Ok(enderecos)
+}
+
+fn decodificar_xml(texto: &str) -> String {
+    texto.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+// Monta o endereço a partir das tags addr:* de um elemento (exige ao menos a rua)
+fn endereco_das_tags(corpo: &str, re_tag: &Regex) -> Option<String> {
+    let mut rua = None;
+    let mut numero = None;
+    let mut cidade = None;
+    for tag in re_tag.captures_iter(corpo) {
+        let valor = decodificar_xml(&tag[2]);
+        match &tag[1] {
+            "addr:street" => rua = Some(valor),
+            "addr:housenumber" => numero = Some(valor),
+            "addr:city" => cidade = Some(valor),
+            _ => {}
+        }
+    }
+
+    let mut endereco = rua?;
+    if let Some(numero) = numero {
+        endereco = format!("{}, {}", endereco, numero);
+    }
+    if let Some(cidade) = cidade {
+        endereco = format!("{} - {}", endereco, cidade);
+    }
+    Some(endereco)
+}
+
+// Lê os endereços (tags addr:*) de um extrato .osm (XML): nós usam a própria coordenada
+// e vias (contornos de edificações) usam o centroide dos seus nós
+pub fn carregar_osm(path: &Path) -> Result<Vec<EnderecoReferencia>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Falha ao ler {}", path.display()))?;
+
+    // Aceita tanto <node .../> quanto <node ...>...</node>
+    let re_node = Regex::new(r#"(?s)<node\b([^>]*?)(?:/>|>(.*?)</node>)"#).unwrap();
+    let re_way = Regex::new(r#"(?s)<way\b[^>]*[^/]>(.*?)</way>"#).unwrap();
+    let re_id = Regex::new(r#"\bid="(-?\d+)""#).unwrap();
+    let re_lat = Regex::new(r#"\blat="(-?[\d.]+)""#).unwrap();
+    let re_lon = Regex::new(r#"\blon="(-?[\d.]+)""#).unwrap();
+    let re_nd = Regex::new(r#"<nd\s+ref="(-?\d+)""#).unwrap();
+    let re_tag = Regex::new(r#"<tag\s+k="([^"]+)"\s+v="([^"]*)""#).unwrap();
+
+    let mut enderecos = Vec::new();
+    let mut coordenadas: HashMap<i64, Location> = HashMap::new();
+    for caps in re_node.captures_iter(&content) {
+        let atributos = &caps[1];
+        let lat = re_lat.captures(atributos).and_then(|c| c[1].parse::<f64>().ok());
+        let lon = re_lon.captures(atributos).and_then(|c| c[1].parse::<f64>().ok());
+        let (Some(latitude), Some(longitude)) = (lat, lon) else { continue };
+
+        if let Some(id) = re_id.captures(atributos).and_then(|c| c[1].parse::<i64>().ok()) {
+            coordenadas.insert(id, Location { latitude, longitude });
+        }
+        let Some(corpo) = caps.get(2) else { continue };
+        if let Some(endereco) = endereco_das_tags(corpo.as_str(), &re_tag) {
+            enderecos.push(EnderecoReferencia { endereco, latitude, longitude });
+        }
+    }
+
+    for caps in re_way.captures_iter(&content) {
+        let corpo = &caps[1];
+        let Some(endereco) = endereco_das_tags(corpo, &re_tag) else { continue };
+
+        let mut refs: Vec<i64> = re_nd.captures_iter(corpo)
+            .filter_map(|c| c[1].parse::<i64>().ok())
+            .collect();
+        // Vias fechadas repetem o primeiro nó no final
+        if refs.len() > 1 && refs.first() == refs.last() {
+            refs.pop();
+        }
+        let pontos: Vec<&Location> = refs.iter().filter_map(|id| coordenadas.get(id)).collect();
+        if pontos.is_empty() {
+            continue;
+        }
+        let n = pontos.len() as f64;
+        let latitude = pontos.iter().map(|p| p.latitude).sum::<f64>() / n;
+        let longitude = pontos.iter().map(|p| p.longitude).sum::<f64>() / n;
+        enderecos.push(EnderecoReferencia { endereco, latitude, longitude });
+    }
+    Ok(enderecos)
+}
+
+// Importa um arquivo (.csv ou .osm) e salva a lista normalizada no projeto
+pub fn importar(origem: &Path, project_dir: &Path) -> Result<usize> {
+    let extensao = origem.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    let enderecos = match extensao.as_str() {
+        "osm" | "xml" => carregar_osm(origem)?,
+        _ => carregar_csv(origem)?,
+    };
+    if enderecos.is_empty() {
+        return Err(anyhow!("Nenhum endereço com coordenadas encontrado em {}", origem.display()));
+    }
+
+    let destino = project_dir.join(GAZETTEER_FILE_NAME);
+    let mut writer = csv::Writer::from_path(&destino)
+        .with_context(|| format!("Falha ao criar {}", destino.display()))?;
+    for endereco in &enderecos {
+        writer.serialize(endereco)?;
+    }
+    writer.flush()?;
+    Ok(enderecos.len())
+}
+
+// Carrega a lista de endereços já importada para o projeto (vazia se não houver)
+pub fn carregar_do_projeto(project_dir: &Path) -> Vec<EnderecoReferencia> {
+    let path = project_dir.join(GAZETTEER_FILE_NAME);
+    if !path.exists() {
+        return Vec::new();
+    }
+    carregar_csv(&path).unwrap_or_else(|e| {
+        eprintln!("Erro ao carregar gazetteer do projeto: {}", e);
+        Vec::new()
+    })
+}
+
+// Endereço mais próximo do ponto, se estiver dentro da distância máxima
+pub fn sugerir_endereco<'a>(enderecos: &'a [EnderecoReferencia], ponto: &Location, distancia_maxima: f64) -> Option<&'a EnderecoReferencia> {
+    enderecos
+        .iter()
+        .map(|e| (e, haversine_distance(&e.location(), ponto)))
+        .filter(|(_, d)| *d <= distancia_maxima)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(e, _)| e)
+}
@@ -0,0 +1,520 @@
+// geometria.rs
+// Medição das fissuras dentro de cada caixa detectada: segmenta os pixels da fissura (mais escuros
+// que o fundo, limiar de Otsu), afina a máscara até o esqueleto (Zhang-Suen) e estima comprimento,
+// largura média e máxima (transformada de distância) e orientação dominante (componente principal
+// do esqueleto). Com a escala do projeto as medidas viram milímetros e a fissura recebe a classe de
+// abertura (capilar, fissura, trinca, rachadura).
+//
+// As medidas em pixels ficam em Projects/<projeto>/measurements.json, para cada caixa ser medida uma
+// única vez, na etapa de processamento; a conversão para milímetros é feita ao carregar, então mudar
+// a escala não exige medir de novo. Caixas manuais da revisão são medidas quando desenhadas.
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use anyhow::{Result, Context};
+use image::GrayImage;
+use serde::{Serialize, Deserialize};
+use crate::manual_processor::ImageAnalysisResult;
+use crate::project_index::caminho_relativo_imagens;
+use crate::project_settings::ConfiguracaoEscala;
+
+pub const MEASUREMENTS_FILE_NAME: &str = "measurements.json";
+
+// Fração máxima da caixa ocupada pela fissura; acima disso a segmentação pegou o fundo
+const FRACAO_MAXIMA_FISSURA: f64 = 0.6;
+// Diferença mínima de tom (0 - 255) entre fissura e fundo para confiar na segmentação
+const CONTRASTE_MINIMO: f64 = 10.0;
+// Componentes menores que esta fração do maior são tratados como ruído (poros, sujeira)
+const FRACAO_MINIMA_COMPONENTE: f64 = 0.1;
+
+// Medidas em pixels da imagem original
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MedidaPixels {
+    pub comprimento: f64,
+    pub largura_media: f64,
+    pub largura_maxima: f64,
+    pub orientacao: f64, // Graus no sentido anti-horário; 0 = horizontal, 90 = vertical
+    pub area: u32, // Pixels segmentados como fissura
+}
+
+// Classes de abertura usuais em laudos de inspeção, pela abertura máxima
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClasseAbertura {
+    Capilar,
+    Fissura,
+    Trinca,
+    Rachadura,
+}
+
+impl ClasseAbertura {
+    pub fn pela_abertura(abertura_mm: f64) -> Self {
+        if abertura_mm < 0.2 {
+            ClasseAbertura::Capilar
+        } else if abertura_mm < 0.5 {
+            ClasseAbertura::Fissura
+        } else if abertura_mm < 1.5 {
+            ClasseAbertura::Trinca
+        } else {
+            ClasseAbertura::Rachadura
+        }
+    }
+
+    pub fn descricao(&self) -> &'static str {
+        match self {
+            ClasseAbertura::Capilar => "Capilar (abertura < 0,2 mm)",
+            ClasseAbertura::Fissura => "Fissura (0,2 a 0,5 mm)",
+            ClasseAbertura::Trinca => "Trinca (0,5 a 1,5 mm)",
+            ClasseAbertura::Rachadura => "Rachadura (≥ 1,5 mm)",
+        }
+    }
+}
+
+// Geometria anexada às detecções carregadas por `deteccoes::carregar`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GeometriaFissura {
+    pub pixels: MedidaPixels,
+    #[serde(default)]
+    pub mm_por_pixel: Option<f64>,
+    #[serde(default)]
+    pub classe_abertura: Option<ClasseAbertura>,
+}
+
+impl GeometriaFissura {
+    pub fn nova(pixels: MedidaPixels, mm_por_pixel: Option<f64>) -> Self {
+        Self {
+            classe_abertura: mm_por_pixel.map(|escala| ClasseAbertura::pela_abertura(pixels.largura_maxima * escala)),
+            pixels,
+            mm_por_pixel,
+        }
+    }
+
+    pub fn comprimento_mm(&self) -> Option<f64> {
+        self.mm_por_pixel.map(|escala| self.pixels.comprimento * escala)
+    }
+
+    pub fn largura_media_mm(&self) -> Option<f64> {
+        self.mm_por_pixel.map(|escala| self.pixels.largura_media * escala)
+    }
+
+    pub fn largura_maxima_mm(&self) -> Option<f64> {
+        self.mm_por_pixel.map(|escala| self.pixels.largura_maxima * escala)
+    }
+
+    // Ex: "Diagonal (45°)"
+    pub fn orientacao_descricao(&self) -> String {
+        let graus = self.pixels.orientacao;
+        let direcao = if !(22.5..157.5).contains(&graus) {
+            "Horizontal"
+        } else if (67.5..112.5).contains(&graus) {
+            "Vertical"
+        } else {
+            "Diagonal"
+        };
+        format!("{} ({:.0}°)", direcao, graus)
+    }
+
+    // Ex: "152,3 mm" ou "412 px" sem escala
+    pub fn comprimento_descricao(&self) -> String {
+        match self.comprimento_mm() {
+            Some(mm) => format!("{} mm", decimal(mm, 1)),
+            None => format!("{:.0} px", self.pixels.comprimento),
+        }
+    }
+
+    // Ex: "0,42 mm (máx. 0,80 mm)"
+    pub fn largura_descricao(&self) -> String {
+        match (self.largura_media_mm(), self.largura_maxima_mm()) {
+            (Some(media), Some(maxima)) => format!("{} mm (máx. {} mm)", decimal(media, 2), decimal(maxima, 2)),
+            _ => format!("{} px (máx. {} px)", decimal(self.pixels.largura_media, 1), decimal(self.pixels.largura_maxima, 1)),
+        }
+    }
+}
+
+// Número com vírgula decimal, como nos laudos
+fn decimal(valor: f64, casas: usize) -> String {
+    format!("{:.*}", casas, valor).replace('.', ",")
+}
+
+// Medida guardada de uma caixa; None = a fissura não pôde ser segmentada
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct MedicaoSalva {
+    bbox: [f64; 4],
+    medida: Option<MedidaPixels>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+struct MedicoesProjeto {
+    // Chave: caminho da imagem relativo à pasta images, como em review.json
+    #[serde(default)]
+    imagens: BTreeMap<String, Vec<MedicaoSalva>>,
+}
+
+impl MedicoesProjeto {
+    fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(MEASUREMENTS_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Falha ao ler {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Falha ao parsear {}", path.display()))
+    }
+
+    fn save(&self, project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(MEASUREMENTS_FILE_NAME);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Falha ao salvar {}", path.display()))
+    }
+
+    fn buscar(&self, chave: &str, bbox: &[f64; 4]) -> Option<&MedicaoSalva> {
+        self.imagens
+            .get(chave)?
+            .iter()
+            .find(|m| m.bbox.iter().zip(bbox).all(|(a, b)| (a - b).abs() < 0.5))
+    }
+}
+
+// Toda alteração do measurements.json (ler, mudar e gravar) acontece com esta trava: a medição das
+// caixas desenhadas na revisão roda em segundo plano e pode cruzar com o processamento ou com a
+// reorganização do projeto
+static TRAVA_MEDICOES: Mutex<()> = Mutex::new(());
+
+fn travar_medicoes() -> MutexGuard<'static, ()> {
+    TRAVA_MEDICOES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn chave(image_path: &Path) -> String {
+    caminho_relativo_imagens(image_path)
+        .unwrap_or_else(|| image_path.to_path_buf())
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+// Limiar de Otsu e as médias das duas classes (escura, clara)
+fn otsu(valores: &[u8]) -> (u8, f64, f64) {
+    let mut histograma = [0usize; 256];
+    for v in valores {
+        histograma[*v as usize] += 1;
+    }
+    let total = valores.len() as f64;
+    let soma_total: f64 = histograma.iter().enumerate().map(|(i, n)| i as f64 * *n as f64).sum();
+    let (mut melhor, mut melhor_variancia) = (0u8, -1.0);
+    let (mut peso_escuro, mut soma_escuro) = (0.0, 0.0);
+    let mut medias = (0.0, 0.0);
+    for (limiar, n) in histograma.iter().enumerate() {
+        peso_escuro += *n as f64;
+        soma_escuro += limiar as f64 * *n as f64;
+        let peso_claro = total - peso_escuro;
+        if peso_escuro == 0.0 || peso_claro == 0.0 {
+            continue;
+        }
+        let media_escuro = soma_escuro / peso_escuro;
+        let media_claro = (soma_total - soma_escuro) / peso_claro;
+        let variancia = peso_escuro * peso_claro * (media_escuro - media_claro).powi(2);
+        if variancia > melhor_variancia {
+            melhor = limiar as u8;
+            melhor_variancia = variancia;
+            medias = (media_escuro, media_claro);
+        }
+    }
+    (melhor, medias.0, medias.1)
+}
+
+// Mantém só os componentes (vizinhança 8) com tamanho relevante em relação ao maior
+fn remover_ruido(mascara: &mut [bool], largura: usize, altura: usize) {
+    let mut rotulos = vec![0usize; mascara.len()];
+    let mut tamanhos = vec![0usize]; // Rótulo 0 = fundo
+    for inicio in 0..mascara.len() {
+        if !mascara[inicio] || rotulos[inicio] != 0 {
+            continue;
+        }
+        let rotulo = tamanhos.len();
+        let mut tamanho = 0;
+        let mut fila = VecDeque::from([inicio]);
+        rotulos[inicio] = rotulo;
+        while let Some(i) = fila.pop_front() {
+            tamanho += 1;
+            let (x, y) = ((i % largura) as i64, (i / largura) as i64);
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= largura as i64 || ny >= altura as i64 {
+                    continue;
+                }
+                let j = ny as usize * largura + nx as usize;
+                if mascara[j] && rotulos[j] == 0 {
+                    rotulos[j] = rotulo;
+                    fila.push_back(j);
+                }
+            }
+        }
+        tamanhos.push(tamanho);
+    }
+    let maior = tamanhos.iter().copied().max().unwrap_or(0);
+    for (i, pixel) in mascara.iter_mut().enumerate() {
+        if *pixel && (tamanhos[rotulos[i]] as f64) < maior as f64 * FRACAO_MINIMA_COMPONENTE {
+            *pixel = false;
+        }
+    }
+}
+
+// Distância (em pixels) de cada pixel da fissura até o fundo, chanfro 3-4
+fn transformada_distancia(mascara: &[bool], largura: usize, altura: usize) -> Vec<f64> {
+    const INFINITO: u32 = u32::MAX / 2;
+    let mut d: Vec<u32> = mascara.iter().map(|m| if *m { INFINITO } else { 0 }).collect();
+    let valor = |d: &[u32], x: i64, y: i64| -> u32 {
+        if x < 0 || y < 0 || x >= largura as i64 || y >= altura as i64 { INFINITO } else { d[y as usize * largura + x as usize] }
+    };
+    for y in 0..altura as i64 {
+        for x in 0..largura as i64 {
+            let i = y as usize * largura + x as usize;
+            if d[i] == 0 {
+                continue;
+            }
+            d[i] = d[i]
+                .min(valor(&d, x - 1, y) + 3)
+                .min(valor(&d, x, y - 1) + 3)
+                .min(valor(&d, x - 1, y - 1) + 4)
+                .min(valor(&d, x + 1, y - 1) + 4);
+        }
+    }
+    for y in (0..altura as i64).rev() {
+        for x in (0..largura as i64).rev() {
+            let i = y as usize * largura + x as usize;
+            if d[i] == 0 {
+                continue;
+            }
+            d[i] = d[i]
+                .min(valor(&d, x + 1, y) + 3)
+                .min(valor(&d, x, y + 1) + 3)
+                .min(valor(&d, x + 1, y + 1) + 4)
+                .min(valor(&d, x - 1, y + 1) + 4);
+        }
+    }
+    // Fissura que não encosta no fundo dentro da caixa: limita pela metade do menor lado
+    let limite = largura.min(altura) as f64 / 2.0;
+    d.into_iter().map(|v| if v >= INFINITO { limite } else { (v as f64 / 3.0).min(limite) }).collect()
+}
+
+// Afinamento de Zhang-Suen: reduz a máscara a um esqueleto de 1 pixel de largura
+fn esqueleto(mascara: &[bool], largura: usize, altura: usize) -> Vec<bool> {
+    let mut pixels = mascara.to_vec();
+    let ligado = |p: &[bool], x: usize, y: usize, dx: i64, dy: i64| -> bool {
+        let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+        nx >= 0 && ny >= 0 && (nx as usize) < largura && (ny as usize) < altura && p[ny as usize * largura + nx as usize]
+    };
+    loop {
+        let mut mudou = false;
+        for passo in 0..2 {
+            let mut remover = Vec::new();
+            for y in 0..altura {
+                for x in 0..largura {
+                    if !pixels[y * largura + x] {
+                        continue;
+                    }
+                    // P2..P9 no sentido horário a partir de cima
+                    let v: Vec<bool> = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)]
+                        .iter()
+                        .map(|(dx, dy)| ligado(&pixels, x, y, *dx, *dy))
+                        .collect();
+                    let vizinhos = v.iter().filter(|b| **b).count();
+                    let transicoes = (0..8).filter(|i| !v[*i] && v[(*i + 1) % 8]).count();
+                    let (p2, p4, p6, p8) = (v[0], v[2], v[4], v[6]);
+                    // Passo 1: P2·P4·P6 = 0 e P4·P6·P8 = 0; passo 2: P2·P4·P8 = 0 e P2·P6·P8 = 0
+                    let condicao = if passo == 0 {
+                        !(p4 && p6 && (p2 || p8))
+                    } else {
+                        !(p2 && p8 && (p4 || p6))
+                    };
+                    if (2..=6).contains(&vizinhos) && transicoes == 1 && condicao {
+                        remover.push(y * largura + x);
+                    }
+                }
+            }
+            mudou |= !remover.is_empty();
+            for i in remover {
+                pixels[i] = false;
+            }
+        }
+        if !mudou {
+            return pixels;
+        }
+    }
+}
+
+// Comprimento do esqueleto: passos ortogonais valem 1 e diagonais √2 (diagonais já cobertas por
+// dois passos ortogonais não são contadas de novo)
+fn comprimento(esqueleto: &[bool], largura: usize, altura: usize) -> f64 {
+    let em = |x: i64, y: i64| x >= 0 && y >= 0 && (x as usize) < largura && (y as usize) < altura && esqueleto[y as usize * largura + x as usize];
+    let mut total = 0.0;
+    for y in 0..altura as i64 {
+        for x in 0..largura as i64 {
+            if !em(x, y) {
+                continue;
+            }
+            if em(x + 1, y) { total += 1.0 }
+            if em(x, y + 1) { total += 1.0 }
+            if em(x + 1, y + 1) && !em(x + 1, y) && !em(x, y + 1) { total += std::f64::consts::SQRT_2 }
+            if em(x - 1, y + 1) && !em(x - 1, y) && !em(x, y + 1) { total += std::f64::consts::SQRT_2 }
+        }
+    }
+    // Esqueleto de um único ponto ainda tem a espessura de um pixel
+    total.max(1.0)
+}
+
+// Ângulo do eixo principal dos pontos, em graus (0 - 180, anti-horário, y da imagem para baixo)
+fn orientacao(pontos: &[(f64, f64)]) -> f64 {
+    let n = pontos.len() as f64;
+    let (mx, my) = pontos.iter().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    let (mx, my) = (mx / n, my / n);
+    let (mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0);
+    for (x, y) in pontos {
+        sxx += (x - mx).powi(2);
+        syy += (y - my).powi(2);
+        sxy += (x - mx) * (y - my);
+    }
+    let angulo_imagem = 0.5 * (2.0 * sxy).atan2(sxx - syy);
+    (180.0 - angulo_imagem.to_degrees()).rem_euclid(180.0)
+}
+
+// Mede a fissura dentro da caixa [x1, y1, x2, y2] (pixels da imagem); None se não der para
+// separar a fissura do fundo
+pub fn medir(imagem: &GrayImage, bbox: &[f64; 4]) -> Option<MedidaPixels> {
+    let x0 = bbox[0].max(0.0).floor() as u32;
+    let y0 = bbox[1].max(0.0).floor() as u32;
+    let x1 = (bbox[2].ceil() as u32).min(imagem.width());
+    let y1 = (bbox[3].ceil() as u32).min(imagem.height());
+    if x1 < x0 + 3 || y1 < y0 + 3 {
+        return None;
+    }
+    let (largura, altura) = ((x1 - x0) as usize, (y1 - y0) as usize);
+    let valores: Vec<u8> = (y0..y1).flat_map(|y| (x0..x1).map(move |x| imagem.get_pixel(x, y).0[0])).collect();
+
+    let (limiar, media_escura, media_clara) = otsu(&valores);
+    if media_clara - media_escura < CONTRASTE_MINIMO {
+        return None;
+    }
+    let mut mascara: Vec<bool> = valores.iter().map(|v| *v <= limiar).collect();
+    remover_ruido(&mut mascara, largura, altura);
+    let area = mascara.iter().filter(|m| **m).count();
+    if area == 0 || area as f64 > mascara.len() as f64 * FRACAO_MAXIMA_FISSURA {
+        return None;
+    }
+
+    let distancias = transformada_distancia(&mascara, largura, altura);
+    let esqueleto = esqueleto(&mascara, largura, altura);
+    let pontos: Vec<(f64, f64)> = esqueleto
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| **e)
+        .map(|(i, _)| ((i % largura) as f64, (i / largura) as f64))
+        .collect();
+    if pontos.is_empty() {
+        return None;
+    }
+    let comprimento = comprimento(&esqueleto, largura, altura);
+    let distancia_maxima = esqueleto
+        .iter()
+        .zip(&distancias)
+        .filter(|(e, _)| **e)
+        .map(|(_, d)| *d)
+        .fold(0.0, f64::max);
+
+    Some(MedidaPixels {
+        comprimento,
+        largura_media: area as f64 / comprimento,
+        largura_maxima: (2.0 * distancia_maxima - 1.0).max(1.0),
+        orientacao: if pontos.len() > 1 { orientacao(&pontos) } else { 0.0 },
+        area: area as u32,
+    })
+}
+
+// Mede as caixas que ainda não estão no measurements.json e grava o resultado. Roda na etapa de
+// processamento (e ao desenhar caixas na revisão), nunca ao abrir gráficos e relatórios. A medição
+// é feita sem a trava; o arquivo é relido com ela para só acrescentar o que continua faltando.
+// Devolve quantas caixas foram gravadas nesta chamada.
+pub fn medir_pendentes(project_dir: &Path, resultados: &[ImageAnalysisResult]) -> Result<usize> {
+    let medicoes = MedicoesProjeto::load(project_dir)?;
+    let mut medidas: Vec<(String, MedicaoSalva)> = Vec::new();
+    for resultado in resultados {
+        let image_path = Path::new(&resultado.path);
+        let chave = chave(image_path);
+        let faltando: Vec<[f64; 4]> = resultado
+            .fissura
+            .iter()
+            .filter_map(|f| f.bbox)
+            .filter(|bbox| medicoes.buscar(&chave, bbox).is_none())
+            .collect();
+        if faltando.is_empty() {
+            continue;
+        }
+        // Imagem que não abre (movida, apagada) fica sem medida e é tentada de novo depois
+        let Ok(imagem) = image::open(image_path) else { continue };
+        let imagem = imagem.to_luma8();
+        for bbox in faltando {
+            medidas.push((chave.clone(), MedicaoSalva { bbox, medida: medir(&imagem, &bbox) }));
+        }
+    }
+    if medidas.is_empty() {
+        return Ok(0);
+    }
+
+    let _trava = travar_medicoes();
+    let mut medicoes = MedicoesProjeto::load(project_dir)?;
+    let mut novas = 0;
+    for (chave, medicao) in medidas {
+        if medicoes.buscar(&chave, &medicao.bbox).is_none() {
+            medicoes.imagens.entry(chave).or_default().push(medicao);
+            novas += 1;
+        }
+    }
+    if novas > 0 {
+        medicoes.save(project_dir)?;
+    }
+    Ok(novas)
+}
+
+// Anexa às detecções com caixa a geometria já medida, na escala do projeto. Só lê o
+// measurements.json: caixa sem medida salva fica sem geometria.
+pub fn anexar(project_dir: &Path, resultados: &mut [ImageAnalysisResult], escala: &ConfiguracaoEscala) -> Result<()> {
+    let medicoes = MedicoesProjeto::load(project_dir)?;
+    for resultado in resultados.iter_mut() {
+        let image_path = Path::new(&resultado.path);
+        let chave = chave(image_path);
+        let largura_imagem = match resultado.width {
+            Some(largura) => Some(largura),
+            None => image::image_dimensions(image_path).ok().map(|(largura, _)| largura),
+        };
+        let mm_por_pixel = largura_imagem.and_then(|largura| escala.mm_por_pixel(largura));
+        for fissura in resultado.fissura.iter_mut() {
+            fissura.geometria = fissura
+                .bbox
+                .and_then(|bbox| medicoes.buscar(&chave, &bbox))
+                .and_then(|salva| salva.medida.clone())
+                .map(|medida| GeometriaFissura::nova(medida, mm_por_pixel));
+        }
+    }
+    Ok(())
+}
+
+// Acompanha as imagens movidas pela reorganização do projeto, como a revisão e as detecções
+pub fn atualizar_caminhos(project_dir: &Path, movimentos: &[(PathBuf, PathBuf)]) -> Result<()> {
+    if movimentos.is_empty() || !project_dir.join(MEASUREMENTS_FILE_NAME).exists() {
+        return Ok(());
+    }
+    let _trava = travar_medicoes();
+    let mut medicoes = MedicoesProjeto::load(project_dir)?;
+    let mut mudou = false;
+    for (origem, destino) in movimentos {
+        if let Some(salvas) = medicoes.imagens.remove(&chave(origem)) {
+            medicoes.imagens.insert(chave(destino), salvas);
+            mudou = true;
+        }
+    }
+    if mudou {
+        medicoes.save(project_dir)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,544 @@
+// graficos.rs
+// Gráficos de fissuras: distribuição por classe (rosca) e contagens por prédio ou fachada (barras
+// agrupadas). A tela de gráficos usa o SVG animado, no tema escuro do app; os relatórios usam o SVG
+// estático, em fundo branco e com legenda, e o PNG para os formatos que não aceitam SVG (PDF, DOCX).
+// SVG e PNG saem do mesmo desenho, uma lista de formas simples, para não divergirem.
+use std::f64::consts::PI;
+use std::fs;
+use std::io::Cursor;
+use std::sync::OnceLock;
+use anyhow::{Result, Context};
+use image::{ImageOutputFormat, Rgb, RgbImage};
+use rusttype::{point, Font, Scale};
+use crate::anotacoes::normalizar_classe;
+use crate::manual_processor::ImageAnalysisResult;
+use crate::taxonomia::Taxonomia;
+
+// Pixels do PNG por unidade do SVG: nítido na impressão sem inflar o arquivo
+const ESCALA_PNG: f64 = 2.0;
+const ALTURA_BARRA_MAXIMA: f64 = 200.0;
+const BASE_BARRAS: f64 = 250.0;
+
+// Fontes para os textos do PNG, na ordem de preferência; sem nenhuma o PNG sai sem textos
+const FONTES: &[&str] = &[
+    concat!(env!("CARGO_MANIFEST_DIR"), "/src/Template/fonte.ttf"),
+    "C:\\Windows\\Fonts\\segoeui.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+];
+
+// Classe mostrada nos gráficos: rótulo e cor vêm da taxonomia do modelo
+#[derive(Debug, Clone)]
+pub struct ClasseGrafico {
+    pub rotulo: String,
+    pub cor: String,
+    pub total: u32,
+}
+
+// Um grupo de barras (um prédio ou uma fachada)
+#[derive(Debug, Clone)]
+pub struct SerieGrafico {
+    pub nome: String,
+    pub contagens: Vec<u32>, // Mesma ordem das classes do gráfico
+}
+
+impl SerieGrafico {
+    pub fn nova(nome: String, classes: usize) -> Self {
+        Self { nome, contagens: vec![0; classes] }
+    }
+
+    // Soma as fissuras do resultado nas classes do gráfico (`chaves`, normalizadas)
+    pub fn somar(&mut self, chaves: &[String], resultado: &ImageAnalysisResult) {
+        for fissura in &resultado.fissura {
+            let chave = normalizar_classe(&fissura.name);
+            if let Some(posicao) = chaves.iter().position(|c| *c == chave) {
+                self.contagens[posicao] += 1;
+            }
+        }
+    }
+}
+
+// Classes do gráfico para as chaves dadas, com totais zerados
+pub fn classes_grafico(taxonomia: &Taxonomia, chaves: &[String]) -> Vec<ClasseGrafico> {
+    chaves
+        .iter()
+        .map(|chave| ClasseGrafico { rotulo: taxonomia.rotulo(chave), cor: taxonomia.cor(chave), total: 0 })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Estilo {
+    Tela,      // Tema escuro do app, com animação, gradientes e sombra
+    Relatorio, // Fundo branco, estático, com legenda e valores nas barras
+}
+
+impl Estilo {
+    fn texto(self) -> &'static str {
+        match self {
+            Estilo::Tela => "#f0f0f0",
+            Estilo::Relatorio => "#333333",
+        }
+    }
+
+    fn fundo(self) -> &'static str {
+        match self {
+            Estilo::Tela => "#242526",
+            Estilo::Relatorio => "#ffffff",
+        }
+    }
+
+    fn contorno(self) -> &'static str {
+        match self {
+            Estilo::Tela => "#121212",
+            Estilo::Relatorio => "#ffffff",
+        }
+    }
+}
+
+enum Preenchimento {
+    Classe(usize),
+    Cor(&'static str),
+}
+
+enum Forma {
+    // Fatia de `inicio` a `fim` graus, no sentido horário a partir do topo
+    Fatia { cx: f64, cy: f64, r: f64, inicio: f64, fim: f64, classe: usize, dica: String },
+    Circulo { cx: f64, cy: f64, r: f64, cor: &'static str },
+    // Barras (`cresce`) sobem a partir da base na animação da tela
+    Retangulo { x: f64, y: f64, largura: f64, altura: f64, preenchimento: Preenchimento, cresce: bool, dica: Option<String> },
+    // `y` é a linha do meio do texto
+    Texto { x: f64, y: f64, tamanho: f64, centralizado: bool, negrito: bool, cor: &'static str, texto: String },
+}
+
+struct Desenho {
+    largura: f64,
+    altura: f64,
+    gradiente_vertical: bool,
+    formas: Vec<Forma>,
+}
+
+impl Desenho {
+    fn texto(&mut self, x: f64, y: f64, tamanho: f64, centralizado: bool, cor: &'static str, texto: String) {
+        self.formas.push(Forma::Texto { x, y, tamanho, centralizado, negrito: false, cor, texto });
+    }
+
+    // Legenda das classes em linhas a partir de (x, y), quebrando antes de `limite`; devolve o y
+    // abaixo da última linha
+    fn legenda(&mut self, itens: &[(usize, String)], x: f64, y: f64, limite: f64, cor: &'static str) -> f64 {
+        let (mut cursor_x, mut cursor_y) = (x, y);
+        for (classe, texto) in itens {
+            let largura = 22.0 + largura_texto(texto, 12.0) + 18.0;
+            if cursor_x > x && cursor_x + largura > limite {
+                cursor_x = x;
+                cursor_y += 22.0;
+            }
+            self.formas.push(Forma::Retangulo {
+                x: cursor_x,
+                y: cursor_y - 7.0,
+                largura: 14.0,
+                altura: 14.0,
+                preenchimento: Preenchimento::Classe(*classe),
+                cresce: false,
+                dica: None,
+            });
+            self.texto(cursor_x + 20.0, cursor_y, 12.0, false, cor, texto.clone());
+            cursor_x += largura;
+        }
+        cursor_y + 22.0
+    }
+}
+
+// Largura aproximada de um texto em fonte sem serifa, para a legenda e os rótulos
+fn largura_texto(texto: &str, tamanho: f64) -> f64 {
+    texto.chars().count() as f64 * tamanho * 0.55
+}
+
+fn abreviar(texto: &str, largura: f64, tamanho: f64) -> String {
+    let maximo = (largura / (tamanho * 0.55)).floor().max(3.0) as usize;
+    if texto.chars().count() <= maximo {
+        return texto.to_string();
+    }
+    let mut curto: String = texto.chars().take(maximo - 1).collect();
+    curto.push('…');
+    curto
+}
+
+fn desenho_donut(classes: &[ClasseGrafico], estilo: Estilo) -> Desenho {
+    let total: u32 = classes.iter().map(|c| c.total).sum();
+    let legenda = estilo == Estilo::Relatorio && total > 0;
+    let mut desenho = Desenho {
+        largura: if legenda { 800.0 } else { 500.0 },
+        altura: 500.0,
+        gradiente_vertical: false,
+        formas: Vec::new(),
+    };
+    if total == 0 {
+        desenho.texto(250.0, 250.0, 20.0, true, estilo.texto(), "Sem dados para Donut".to_string());
+        return desenho;
+    }
+    let (cx, cy) = (250.0, 250.0);
+    let mut angulo = 0.0;
+    for (i, classe) in classes.iter().enumerate().filter(|(_, c)| c.total > 0) {
+        let fim = angulo + classe.total as f64 / total as f64 * 360.0;
+        desenho.formas.push(Forma::Fatia {
+            cx,
+            cy,
+            r: 200.0,
+            inicio: angulo,
+            fim,
+            classe: i,
+            dica: format!("{}: {}", classe.rotulo, classe.total),
+        });
+        angulo = fim;
+    }
+    desenho.formas.push(Forma::Circulo { cx, cy, r: 120.0, cor: estilo.fundo() });
+    desenho.formas.push(Forma::Texto {
+        x: cx,
+        y: cy - 14.0,
+        tamanho: 40.0,
+        centralizado: true,
+        negrito: true,
+        cor: estilo.texto(),
+        texto: total.to_string(),
+    });
+    desenho.texto(cx, cy + 24.0, 18.0, true, estilo.texto(), "Total Fissuras".to_string());
+
+    if legenda {
+        let itens: Vec<(usize, String)> = classes
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.total > 0)
+            .map(|(i, c)| (i, format!("{}: {} ({:.0}%)", c.rotulo, c.total, c.total as f64 / total as f64 * 100.0)))
+            .collect();
+        // Uma classe por linha, centralizadas na altura da rosca
+        let mut y = cy - (itens.len() as f64 - 1.0) * 13.0;
+        for item in &itens {
+            y = desenho.legenda(std::slice::from_ref(item), 500.0, y, 0.0, estilo.texto()) + 4.0;
+        }
+    }
+    desenho
+}
+
+fn desenho_barras(series: &[SerieGrafico], classes: &[ClasseGrafico], estilo: Estilo) -> Desenho {
+    let relatorio = estilo == Estilo::Relatorio;
+    if series.is_empty() {
+        let mut desenho = Desenho { largura: 600.0, altura: 450.0, gradiente_vertical: true, formas: Vec::new() };
+        desenho.texto(300.0, 225.0, 20.0, true, estilo.texto(), "Sem dados para Barras".to_string());
+        return desenho;
+    }
+    // Barras mais finas conforme entram classes, para cada grupo continuar legível
+    let largura_barra = (60 / classes.len().max(1) as i32).clamp(10, 30) as f64;
+    let largura_grupo = classes.len() as f64 * (largura_barra + 2.0);
+    let espacamento = (largura_grupo + 20.0).max(80.0);
+    let largura_barras = 60.0 + series.len() as f64 * espacamento;
+    let mut desenho = Desenho {
+        largura: if relatorio { largura_barras.max(500.0) } else { largura_barras },
+        altura: 450.0,
+        gradiente_vertical: true,
+        formas: Vec::new(),
+    };
+
+    // Com poucos grupos o relatório alarga o desenho para a legenda; as barras ficam centralizadas
+    let deslocamento = (desenho.largura - largura_barras) / 2.0;
+    let maximo = series.iter().flat_map(|s| s.contagens.iter().copied()).max().unwrap_or(0) as f64;
+    if relatorio {
+        desenho.formas.push(Forma::Retangulo {
+            x: deslocamento + 50.0,
+            y: BASE_BARRAS,
+            largura: largura_barras - 40.0,
+            altura: 1.0,
+            preenchimento: Preenchimento::Cor("#999999"),
+            cresce: false,
+            dica: None,
+        });
+    }
+    for (i, serie) in series.iter().enumerate() {
+        let x_base = deslocamento + 60.0 + i as f64 * espacamento;
+        for (j, (classe, contagem)) in classes.iter().zip(&serie.contagens).enumerate() {
+            let altura = if maximo == 0.0 { 0.0 } else { *contagem as f64 / maximo * ALTURA_BARRA_MAXIMA };
+            let x = x_base + j as f64 * (largura_barra + 2.0);
+            desenho.formas.push(Forma::Retangulo {
+                x,
+                y: BASE_BARRAS - altura,
+                largura: largura_barra,
+                altura,
+                preenchimento: Preenchimento::Classe(j),
+                cresce: true,
+                dica: Some(format!("{}: {}", classe.rotulo, contagem)),
+            });
+            if relatorio && *contagem > 0 {
+                desenho.texto(x + largura_barra / 2.0, BASE_BARRAS - altura - 8.0, 10.0, true, estilo.texto(), contagem.to_string());
+            }
+        }
+        // Nome do grupo, centralizado sob as barras
+        desenho.texto(
+            x_base + largura_grupo / 2.0,
+            BASE_BARRAS + 16.0,
+            10.0,
+            true,
+            estilo.texto(),
+            abreviar(&serie.nome, espacamento - 6.0, 10.0),
+        );
+    }
+
+    if relatorio {
+        let itens: Vec<(usize, String)> = classes.iter().enumerate().map(|(i, c)| (i, c.rotulo.clone())).collect();
+        let largura = desenho.largura;
+        let fim = desenho.legenda(&itens, 50.0, BASE_BARRAS + 50.0, largura - 20.0, estilo.texto());
+        desenho.altura = fim.max(BASE_BARRAS + 80.0);
+    }
+    desenho
+}
+
+fn escapar(texto: &str) -> String {
+    texto.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn polar_para_cartesiano(cx: f64, cy: f64, r: f64, angulo: f64) -> (f64, f64) {
+    let radianos = (angulo - 90.0) * PI / 180.0;
+    (cx + r * radianos.cos(), cy + r * radianos.sin())
+}
+
+fn caminho_fatia(cx: f64, cy: f64, r: f64, inicio: f64, fim: f64) -> String {
+    let (x1, y1) = polar_para_cartesiano(cx, cy, r, fim);
+    let (x2, y2) = polar_para_cartesiano(cx, cy, r, inicio);
+    let arco_grande = if fim - inicio > 180.0 { 1 } else { 0 };
+    format!("M {x1} {y1} A {r} {r} 0 {arco_grande} 0 {x2} {y2} L {cx} {cy} Z")
+}
+
+// Um gradiente por classe, da cor dela a um tom translúcido
+fn gradientes_classes(classes: &[ClasseGrafico], vertical: bool) -> String {
+    let (x2, y2) = if vertical { ("0%", "100%") } else { ("100%", "100%") };
+    let mut defs = String::from("<defs>");
+    for (i, classe) in classes.iter().enumerate() {
+        defs.push_str(&format!(
+            r###"<linearGradient id="grad_{i}" x1="0%" y1="0%" x2="{x2}" y2="{y2}">
+                <stop offset="0%" style="stop-color:{cor}; stop-opacity:1" />
+                <stop offset="100%" style="stop-color:{cor}; stop-opacity:0.7" />
+            </linearGradient>"###,
+            cor = classe.cor
+        ));
+    }
+    defs.push_str("</defs>");
+    defs
+}
+
+fn svg(desenho: &Desenho, classes: &[ClasseGrafico], estilo: Estilo) -> String {
+    let tela = estilo == Estilo::Tela;
+    let (largura, altura) = (desenho.largura, desenho.altura);
+    let mut svg = format!(
+        r#"<svg width="{largura}" height="{altura}" viewBox="0 0 {largura} {altura}" xmlns="http://www.w3.org/2000/svg">"#
+    );
+    if tela {
+        svg.push_str(&gradientes_classes(classes, desenho.gradiente_vertical));
+    } else {
+        svg.push_str(&format!(r#"<rect width="{largura}" height="{altura}" fill="{}"/>"#, estilo.fundo()));
+    }
+    let cor_classe = |i: usize| {
+        if tela {
+            format!("url(#grad_{i})")
+        } else {
+            classes.get(i).map(|c| c.cor.clone()).unwrap_or_default()
+        }
+    };
+
+    for forma in &desenho.formas {
+        match forma {
+            Forma::Fatia { cx, cy, r, inicio, fim, classe, dica } => {
+                let dica = escapar(dica);
+                let contorno = estilo.contorno();
+                let preenchimento = cor_classe(*classe);
+                if fim - inicio >= 360.0 - f64::EPSILON {
+                    // Uma classe só preenche a rosca; um arco de 0 a 360 graus se anularia
+                    svg.push_str(&format!(
+                        r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="{preenchimento}" stroke="{contorno}" stroke-width="2"><title>{dica}</title></circle>"#
+                    ));
+                } else if tela {
+                    let caminho = caminho_fatia(*cx, *cy, *r, *inicio, *fim);
+                    svg.push_str(&format!(
+                        r###"<path d="{caminho}" fill="{preenchimento}" stroke="{contorno}" stroke-width="2" style="filter: drop-shadow(0px 2px 5px rgba(0,0,0,0.6)); opacity: 0;">
+                            <animate attributeName="opacity" from="0" to="1" dur="1s" fill="freeze" />
+                            <title>{dica}</title>
+                        </path>"###
+                    ));
+                } else {
+                    let caminho = caminho_fatia(*cx, *cy, *r, *inicio, *fim);
+                    svg.push_str(&format!(
+                        r#"<path d="{caminho}" fill="{preenchimento}" stroke="{contorno}" stroke-width="2"><title>{dica}</title></path>"#
+                    ));
+                }
+            }
+            Forma::Circulo { cx, cy, r, cor } => {
+                svg.push_str(&format!(r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="{cor}"/>"#));
+            }
+            Forma::Retangulo { x, y, largura, altura, preenchimento, cresce, dica } => {
+                let (preenchimento, arredondado) = match preenchimento {
+                    Preenchimento::Classe(i) => (cor_classe(*i), 3),
+                    Preenchimento::Cor(cor) => (cor.to_string(), 0),
+                };
+                let dica = dica.as_deref().map(|d| format!("<title>{}</title>", escapar(d))).unwrap_or_default();
+                if tela && *cresce {
+                    let base = y + altura;
+                    svg.push_str(&format!(
+                        r###"<rect x="{x}" y="{y}" width="{largura}" height="0" fill="{preenchimento}" rx="{arredondado}">
+                            <animate attributeName="height" from="0" to="{altura}" dur="0.8s" fill="freeze" />
+                            <animate attributeName="y" from="{base}" to="{y}" dur="0.8s" fill="freeze" />
+                            {dica}
+                        </rect>"###
+                    ));
+                } else {
+                    svg.push_str(&format!(
+                        r#"<rect x="{x}" y="{y}" width="{largura}" height="{altura}" fill="{preenchimento}" rx="{arredondado}">{dica}</rect>"#
+                    ));
+                }
+            }
+            Forma::Texto { x, y, tamanho, centralizado, negrito, cor, texto } => {
+                let ancora = if *centralizado { "middle" } else { "start" };
+                let peso = if *negrito { r#" font-weight="bold""# } else { "" };
+                // Na tela a fonte vem da página; fora dela o SVG precisa indicar uma
+                let familia = if tela { "" } else { r#" font-family="'Segoe UI', Arial, 'DejaVu Sans', sans-serif""# };
+                svg.push_str(&format!(
+                    r#"<text x="{x}" y="{y}" font-size="{tamanho}"{peso}{familia} text-anchor="{ancora}" fill="{cor}" dominant-baseline="middle">{}</text>"#,
+                    escapar(texto)
+                ));
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn fonte() -> Option<&'static Font<'static>> {
+    static FONTE: OnceLock<Option<Font<'static>>> = OnceLock::new();
+    FONTE
+        .get_or_init(|| {
+            let fonte = FONTES.iter().find_map(|caminho| fs::read(caminho).ok().and_then(|dados| Font::from_bytes(dados).ok()));
+            if fonte.is_none() {
+                eprintln!("Nenhuma fonte encontrada para os gráficos em PNG; eles sairão sem textos");
+            }
+            fonte
+        })
+        .as_ref()
+}
+
+fn cor_rgb(css: &str) -> Rgb<u8> {
+    let hex = css.trim().trim_start_matches('#');
+    let hex = if hex.len() == 3 { hex.chars().flat_map(|c| [c, c]).collect() } else { hex.to_string() };
+    match u32::from_str_radix(&hex, 16) {
+        Ok(valor) if hex.len() == 6 => Rgb([(valor >> 16) as u8, (valor >> 8) as u8, valor as u8]),
+        _ => Rgb([127, 140, 141]),
+    }
+}
+
+fn misturar(imagem: &mut RgbImage, x: i64, y: i64, cor: Rgb<u8>, cobertura: f64) {
+    if x < 0 || y < 0 || x >= imagem.width() as i64 || y >= imagem.height() as i64 || cobertura <= 0.0 {
+        return;
+    }
+    let pixel = imagem.get_pixel_mut(x as u32, y as u32);
+    for (canal, alvo) in pixel.0.iter_mut().zip(cor.0) {
+        *canal = (*canal as f64 * (1.0 - cobertura) + alvo as f64 * cobertura).round() as u8;
+    }
+}
+
+// Pinta a região `dentro` (em unidades do SVG) dentro da caixa dada, com 4 amostras por pixel
+fn pintar(imagem: &mut RgbImage, caixa: [f64; 4], cor: Rgb<u8>, dentro: impl Fn(f64, f64) -> bool) {
+    let [x1, y1, x2, y2] = caixa.map(|v| v * ESCALA_PNG);
+    for y in (y1.floor() as i64)..(y2.ceil() as i64) {
+        for x in (x1.floor() as i64)..(x2.ceil() as i64) {
+            let amostras = [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)];
+            let acertos = amostras
+                .iter()
+                .filter(|(dx, dy)| dentro((x as f64 + dx) / ESCALA_PNG, (y as f64 + dy) / ESCALA_PNG))
+                .count();
+            misturar(imagem, x, y, cor, acertos as f64 / amostras.len() as f64);
+        }
+    }
+}
+
+fn escrever(imagem: &mut RgbImage, fonte: &Font, forma: (f64, f64, f64, bool), cor: Rgb<u8>, texto: &str) {
+    let (x, y, tamanho, centralizado) = forma;
+    let escala = Scale::uniform((tamanho * ESCALA_PNG) as f32);
+    let largura = fonte
+        .layout(texto, escala, point(0.0, 0.0))
+        .last()
+        .map_or(0.0, |g| g.position().x + g.unpositioned().h_metrics().advance_width);
+    let metricas = fonte.v_metrics(escala);
+    let inicio_x = (x * ESCALA_PNG) as f32 - if centralizado { largura / 2.0 } else { 0.0 };
+    let base = (y * ESCALA_PNG) as f32 + (metricas.ascent + metricas.descent) / 2.0;
+    for glifo in fonte.layout(texto, escala, point(inicio_x, base)) {
+        let Some(caixa) = glifo.pixel_bounding_box() else { continue };
+        glifo.draw(|gx, gy, cobertura| {
+            misturar(imagem, (caixa.min.x + gx as i32) as i64, (caixa.min.y + gy as i32) as i64, cor, cobertura as f64);
+        });
+    }
+}
+
+// PNG do desenho no estilo do relatório (fundo branco)
+fn png(desenho: &Desenho, classes: &[ClasseGrafico]) -> Result<Vec<u8>> {
+    let largura = (desenho.largura * ESCALA_PNG).ceil() as u32;
+    let altura = (desenho.altura * ESCALA_PNG).ceil() as u32;
+    let mut imagem = RgbImage::from_pixel(largura, altura, cor_rgb(Estilo::Relatorio.fundo()));
+    let cor_classe = |i: usize| classes.get(i).map_or(Rgb([127, 140, 141]), |c| cor_rgb(&c.cor));
+
+    for forma in &desenho.formas {
+        match forma {
+            Forma::Fatia { cx, cy, r, inicio, fim, classe, .. } => {
+                let (cx, cy, r, inicio, fim) = (*cx, *cy, *r, *inicio, *fim);
+                pintar(&mut imagem, [cx - r, cy - r, cx + r, cy + r], cor_classe(*classe), |x, y| {
+                    let (dx, dy) = (x - cx, y - cy);
+                    // Ângulo no sentido horário a partir do topo, como nos caminhos do SVG
+                    let angulo = dx.atan2(-dy).to_degrees().rem_euclid(360.0);
+                    dx * dx + dy * dy <= r * r && angulo >= inicio && angulo < fim
+                });
+            }
+            Forma::Circulo { cx, cy, r, cor } => {
+                let (cx, cy, r) = (*cx, *cy, *r);
+                pintar(&mut imagem, [cx - r, cy - r, cx + r, cy + r], cor_rgb(cor), |x, y| {
+                    (x - cx).powi(2) + (y - cy).powi(2) <= r * r
+                });
+            }
+            Forma::Retangulo { x, y, largura, altura, preenchimento, .. } => {
+                let cor = match preenchimento {
+                    Preenchimento::Classe(i) => cor_classe(*i),
+                    Preenchimento::Cor(cor) => cor_rgb(cor),
+                };
+                let caixa = [*x, *y, x + largura, y + altura];
+                pintar(&mut imagem, caixa, cor, |px, py| px >= caixa[0] && px < caixa[2] && py >= caixa[1] && py < caixa[3]);
+            }
+            Forma::Texto { x, y, tamanho, centralizado, cor, texto, .. } => {
+                if let Some(fonte) = fonte() {
+                    escrever(&mut imagem, fonte, (*x, *y, *tamanho, *centralizado), cor_rgb(cor), texto);
+                }
+            }
+        }
+    }
+
+    let mut dados = Vec::new();
+    imagem
+        .write_to(&mut Cursor::new(&mut dados), ImageOutputFormat::Png)
+        .context("Falha ao codificar o gráfico em PNG")?;
+    Ok(dados)
+}
+
+// Rosca com a distribuição das fissuras por classe
+pub fn svg_donut(classes: &[ClasseGrafico], estilo: Estilo) -> String {
+    svg(&desenho_donut(classes, estilo), classes, estilo)
+}
+
+// Barras agrupadas: um grupo por série, uma barra por classe
+pub fn svg_barras(series: &[SerieGrafico], classes: &[ClasseGrafico], estilo: Estilo) -> String {
+    svg(&desenho_barras(series, classes, estilo), classes, estilo)
+}
+
+pub fn png_donut(classes: &[ClasseGrafico]) -> Result<Vec<u8>> {
+    png(&desenho_donut(classes, Estilo::Relatorio), classes)
+}
+
+pub fn png_barras(series: &[SerieGrafico], classes: &[ClasseGrafico]) -> Result<Vec<u8>> {
+    png(&desenho_barras(series, classes, Estilo::Relatorio), classes)
+}
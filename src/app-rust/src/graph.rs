@@ -1,292 +1,218 @@
 use dioxus::prelude::*;
-use std::f64::consts::PI;
-use serde::Deserialize;
-use std::fs::File;
-use std::io::{self, BufReader};
 use crate::Route;
 use dioxus_router::prelude::*;
 use std::collections::HashMap;
 use std::path::Path;
-use std::env; // For checking Current Working Directory
-
-// --- Structs for parsing detection_results.json ---
-#[derive(Deserialize, Debug, Clone)]
-struct DetectionFissura {
-    name: String,
-    confidence: f64, // Confidence is f64 in the JSON
-}
-
-#[derive(Deserialize, Debug, Clone)]
-struct ImageDetectionData {
-    path: String,
-    fissura: Vec<DetectionFissura>,
-}
-// Type alias for the root JSON structure (a list of ImageDetectionData)
-// type DetectionResults = Vec<ImageDetectionData>; // This will be the return type of our JSON reader
-
-// --- Structs for aggregated data for bar chart ---
-#[derive(Debug, Clone)]
-struct BuildingFissuraSummary {
-    building_name: String,
-    termica_count: u32,
-    retracao_count: u32,
-}
-
-// --- Error type for JSON reading ---
-#[derive(Debug)]
-enum JsonReadError {
-    Io(io::Error),
-    Json(serde_json::Error),
-    PathError(String),
+use crate::project_index::{self, ProjectIndex};
+use crate::project_settings::{ProjectSettings, PoliticaDeteccao};
+use crate::deteccoes;
+use crate::manual_processor::ImageAnalysisResult;
+use crate::revisao::RevisaoProjeto;
+use crate::avaliacao::{self, MetricasClasse, MatrizConfusao};
+use rfd::AsyncFileDialog;
+use crate::modelos::RegistroModelos;
+use crate::taxonomia::Taxonomia;
+use crate::classificacao::{self, ResumoClassificacao};
+use crate::report::RESUMO_PROJETO;
+use crate::graficos::{self, ClasseGrafico, SerieGrafico, Estilo};
+
+// Persists the policy edited on this screen into the project settings
+fn salvar_politica(project_dir: &Path, politica: Signal<PoliticaDeteccao>, mut erro: Signal<Option<String>>) {
+    let resultado = ProjectSettings::load(project_dir).and_then(|mut settings| {
+        settings.politica = politica();
+        settings.save(project_dir)
+    });
+    erro.set(resultado.err().map(|e| format!("Erro ao salvar política de detecção: {}", e)));
 }
 
-impl From<io::Error> for JsonReadError {
-    fn from(err: io::Error) -> JsonReadError {
-        JsonReadError::Io(err)
-    }
-}
-
-impl From<serde_json::Error> for JsonReadError {
-    fn from(err: serde_json::Error) -> JsonReadError {
-        JsonReadError::Json(err)
-    }
+// Fallback for images not tracked by the project index
+// Assumes path structure like ".../images/Prédio X/Fachada Y/image.jpg"
+fn extract_building_name_from_path(image_path_str: &str) -> Option<String> {
+    let image_path = Path::new(image_path_str);
+    // Go up from file: Fachada Y -> Prédio X -> images
+    image_path.parent()?.parent()?.file_name()?.to_str().map(String::from)
 }
 
-// --- Function to read and parse detection_results.json ---
-fn ler_json_detection_results(project_name: &str) -> Result<Vec<ImageDetectionData>, JsonReadError> {
-    // --- BEGIN CWD DEBUG ---
-    let cwd_string = match env::current_dir() {
-        Ok(cwd) => {
-            let s = cwd.display().to_string();
-            println!("[RUST graph.rs] Current Working Directory: {}", s);
-            s
-        }
-        Err(e) => {
-            eprintln!("[RUST graph.rs] Failed to get Current Working Directory: {}", e);
-            return Err(JsonReadError::PathError("Failed to get CWD".to_string()));
-        }
-    };
-    // --- END CWD DEBUG ---
-
-    // Construct path relative to the CWD printed above.
-    // If CWD is ".../src/app-rust/src", then "../Projects/..." should be correct
-    // if the target is ".../src/app-rust/Projects/..."
-    let json_path_str = format!("../Projects/{}/detection_results.json", project_name);
-    
-    // Resolve this potentially relative path against the CWD to get an absolute path for File::open
-    // std::fs::canonicalize can also be used for more robust absolute path resolution.
-    let absolute_json_path = Path::new(&cwd_string).join(&json_path_str);
-    // It's often better to canonicalize to resolve symlinks, .., etc., fully.
-    let canonical_path = match absolute_json_path.canonicalize() {
-        Ok(p) => p,
-        Err(e) => {
-            // If canonicalize fails, it often means the path doesn't exist or isn't accessible at some point.
-            // Fallback to the joined path for the error message, but the open will likely fail.
-            eprintln!("[RUST graph.rs] Error canonicalizing path '{:?}': {}. Using non-canonical path for open attempt.", absolute_json_path, e);
-            // return Err(JsonReadError::PathError(format!("Error canonicalizing path {:?}: {}", absolute_json_path, e)));
-            // For the purpose of File::open, the non-canonicalized absolute_json_path might still be what we need if canonicalize fails due to non-existence
-             absolute_json_path.clone() // Use the joined path if canonicalization fails (e.g. because file doesn't exist yet for canonicalize to work on the final component)
+// Chart classes (the taxonomy's, then any other class in `classes`) and per-building counts.
+// The donut totals follow `predio` (a building id) when given; the bars always compare all buildings.
+fn agregar_graficos(
+    resultados: &[ImageAnalysisResult],
+    classes: Vec<String>,
+    index: &ProjectIndex,
+    taxonomia: &Taxonomia,
+    predio: Option<&str>,
+) -> (Vec<ClasseGrafico>, Vec<SerieGrafico>) {
+    let chaves_grafico = taxonomia.com_extras(classes);
+    let mut classes_grafico = graficos::classes_grafico(taxonomia, &chaves_grafico);
+    let mut building_fissura_map: HashMap<String, SerieGrafico> = HashMap::new();
+
+    for item_data in resultados {
+        let building_id = index
+            .localizar(Path::new(&item_data.path))
+            .map(|(predio, _, _)| predio.id.clone())
+            .or_else(|| extract_building_name_from_path(&item_data.path));
+
+        let mut current_image = SerieGrafico::nova(String::new(), chaves_grafico.len());
+        current_image.somar(&chaves_grafico, item_data);
+        if predio.is_none_or(|id| building_id.as_deref() == Some(id)) {
+            for (classe, contagem) in classes_grafico.iter_mut().zip(&current_image.contagens) {
+                classe.total += contagem;
+            }
         }
-    };
 
+        // Aggregate for bar chart by building
+        if let Some(building_name) = building_id {
+            let summary = building_fissura_map
+                .entry(building_name.clone())
+                .or_insert_with(|| SerieGrafico::nova(building_name, chaves_grafico.len()));
+            for (total, contagem) in summary.contagens.iter_mut().zip(&current_image.contagens) {
+                *total += contagem;
+            }
+        }
+    }
 
-    println!("[RUST graph.rs] Constructed relative path (from CWD): {}", json_path_str);
-    println!("[RUST graph.rs] Resolved absolute path for open: {:?}", absolute_json_path); // Path before canonicalization
-    println!("[RUST graph.rs] Canonical path (if successful): {:?}", canonical_path); // Path after canonicalization, might differ
-    
-    // Try opening with the path that seems most likely to be what File::open expects, which is the directly joined one. 
-    // Canonicalize is good for verifying, but File::open takes the direct path.
-    let file = File::open(&absolute_json_path).map_err(|e| { 
-        eprintln!("[RUST graph.rs] Error opening JSON file at '{:?}': {}", absolute_json_path, e);
-        JsonReadError::Io(e)
-    })?;
-    let reader = BufReader::new(file);
-    let results: Vec<ImageDetectionData> = serde_json::from_reader(reader).map_err(|e| {
-        eprintln!("[RUST graph.rs] Error parsing JSON from '{:?}': {}", absolute_json_path, e);
-        JsonReadError::Json(e)
-    })?;
-    Ok(results)
-}
-
-//  Helpers do gráfico do Donut 
-
-fn polar_to_cartesian(cx: f64, cy: f64, r: f64, angle_deg: f64) -> (f64, f64) {
-    let angle_rad = (angle_deg - 90.0) * PI / 180.0;
-    (cx + r * angle_rad.cos(), cy + r * angle_rad.sin())
-}
-
-fn describe_arc(cx: f64, cy: f64, r: f64, start_angle: f64, end_angle: f64) -> String {
-    let (x1, y1) = polar_to_cartesian(cx, cy, r, end_angle);
-    let (x2, y2) = polar_to_cartesian(cx, cy, r, start_angle);
-    let large_arc_flag = if end_angle - start_angle > 180.0 { 1 } else { 0 };
-
-    format!(
-        "M {x1} {y1} A {r} {r} 0 {large_arc_flag} 0 {x2} {y2} L {cx} {cy} Z"
-    )
+    // Folder names are stable building IDs; the charts show the display names from the project index
+    let building_summaries: Vec<SerieGrafico> = building_fissura_map
+        .into_values()
+        .map(|mut summary| {
+            summary.nome = index.nome_exibicao(&summary.nome);
+            summary
+        })
+        .collect();
+    (classes_grafico, building_summaries)
 }
 
-fn donut_segment(
-    cx: f64,
-    cy: f64,
-    r: f64,
-    start_angle: f64,
-    end_angle: f64,
-    color_id: &str,
-    label: &str
-) -> String {
-    let path = describe_arc(cx, cy, r, start_angle, end_angle);
-    format!(
-        r###"<path d="{path}" fill="url(#{color_id})" stroke="#121212" stroke-width="2" style="filter: drop-shadow(0px 2px 5px rgba(0,0,0,0.6)); opacity: 0;">
-            <animate attributeName="opacity" from="0" to="1" dur="1s" fill="freeze" />
-            <title>{label}</title>
-        </path>"###
-    )
-}
+//  Gráficos da avaliação do modelo
 
-fn gerar_svg_donut(total_termica: u32, total_retracao: u32) -> String {
-    let total_fissuras = total_termica + total_retracao;
-    if total_fissuras == 0 {
-        return r##"<svg width="500" height="500" viewBox="0 0 500 500" xmlns="http://www.w3.org/2000/svg">
-                   <text x="250" y="250" font-size="20" text-anchor="middle" fill="#ffffff" dominant-baseline="middle">Sem dados para Donut</text>
+// Barras agrupadas de precisão, recall e F1 por classe (0 - 100%)
+fn gerar_svg_metricas(metricas: &[MetricasClasse], taxonomia: &Taxonomia) -> String {
+    if metricas.is_empty() {
+        return r##"<svg width="600" height="300" viewBox="0 0 600 300" xmlns="http://www.w3.org/2000/svg">
+                   <text x="300" y="150" font-size="20" text-anchor="middle" fill="#ffffff" dominant-baseline="middle">Sem anotações para avaliar</text>
                  </svg>"##.to_string();
     }
-    let angle_termica = (total_termica as f64 / total_fissuras as f64) * 360.0;
-
-    let cx = 250.0;
-    let cy = 250.0;
-    let raio_externo = 200.0;
-    let raio_interno = 120.0;
-
-    let label_termica = format!("Térmica: {}", total_termica);
-    let label_retracao = format!("Retração: {}", total_retracao);
-
-    let mut svg = String::new();
-    svg.push_str(r#"<svg width="500" height="500" viewBox="0 0 500 500" xmlns="http://www.w3.org/2000/svg">"#);
-
-    svg.push_str(
-        r###"
-        <defs>
-            <linearGradient id="grad_red" x1="0%" y1="0%" x2="100%" y2="100%">
-                <stop offset="0%" style="stop-color:#ff5a5f; stop-opacity:1" />
-                <stop offset="100%" style="stop-color:#d62828; stop-opacity:1" />
-            </linearGradient>
-            <linearGradient id="grad_blue" x1="0%" y1="0%" x2="100%" y2="100%">
-                <stop offset="0%" style="stop-color:#0077ff; stop-opacity:1" />
-                <stop offset="100%" style="stop-color:#0055aa; stop-opacity:1" />
-            </linearGradient>
-        </defs>
-        "###
+    let largura_barra = 22;
+    let espacamento = 100;
+    let base = 240.0;
+    let altura_max = 200.0;
+    let largura_total = 60 + metricas.len() as i32 * espacamento;
+    let mut svg = format!(
+        r###"<svg width="{largura_total}" height="300" viewBox="0 0 {largura_total} 300" xmlns="http://www.w3.org/2000/svg">"###
     );
-
-    svg.push_str(&donut_segment(cx, cy, raio_externo, 0.0, angle_termica, "grad_red", &label_termica));
-    svg.push_str(&donut_segment(cx, cy, raio_externo, angle_termica, 360.0, "grad_blue", &label_retracao));
-
-    svg.push_str(&format!(
-        r###"<circle cx="{cx}" cy="{cy}" r="{r}" fill="#242526"/>"###,
-        cx = cx,
-        cy = cy,
-        r = raio_interno
-    ));
-
-    svg.push_str(&format!(
-        r###"<text x="{cx}" y="{cy}" font-size="20" text-anchor="middle" fill="#ffffff" dominant-baseline="middle">Total Fissuras</text>"###,
-        cx = cx,
-        cy = cy
-    ));
-
+    // Linhas de referência a cada 25%
+    for passo in 0..=4 {
+        let y = base - altura_max * passo as f64 / 4.0;
+        svg.push_str(&format!(
+            r###"<line x1="50" y1="{y}" x2="{largura_total}" y2="{y}" stroke="#4a4b4c" stroke-width="1" />
+                <text x="44" y="{y}" font-size="10" text-anchor="end" fill="#b0b3b8" dominant-baseline="middle">{p}%</text>"###,
+            p = passo * 25
+        ));
+    }
+    for (i, m) in metricas.iter().enumerate() {
+        let x_base = 60 + i as i32 * espacamento;
+        for (j, (valor, cor, nome)) in [(m.precisao, "#ff5a5f", "Precisão"), (m.recall, "#0077ff", "Recall"), (m.f1, "#2ecc71", "F1")].iter().enumerate() {
+            let h = valor * altura_max;
+            svg.push_str(&format!(
+                r###"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="{cor}" rx="3"><title>{nome}: {pct:.1}%</title></rect>"###,
+                x = x_base + j as i32 * (largura_barra + 2),
+                y = base - h,
+                w = largura_barra,
+                pct = valor * 100.0
+            ));
+        }
+        svg.push_str(&format!(
+            r###"<text x="{x}" y="258" font-size="11" text-anchor="middle" fill="#f0f0f0">{classe}</text>
+                <text x="{x}" y="274" font-size="10" text-anchor="middle" fill="#b0b3b8">AP@0.5 {ap:.2}</text>"###,
+            x = x_base + (largura_barra * 3 + 4) / 2,
+            classe = taxonomia.rotulo(&m.classe),
+            ap = m.ap50
+        ));
+    }
     svg.push_str("</svg>");
     svg
 }
 
-// Helper to extract building name like "Prédio X" from path
-// Assumes path structure like ".../images/Prédio X/Fachada Y/image.jpg"
-fn extract_building_name_from_path(image_path_str: &str) -> Option<String> {
-    let image_path = Path::new(image_path_str);
-    // Go up from file: Fachada Y -> Prédio X -> images
-    image_path.parent()?.parent()?.file_name()?.to_str().map(String::from)
-}
-
-//  Gráfico de Barras 
-
-fn gerar_svg_barras(building_summaries: &[BuildingFissuraSummary]) -> String {
-    if building_summaries.is_empty() {
-        return r##"<svg width="600" height="450" viewBox="0 0 600 450" xmlns="http://www.w3.org/2000/svg">
-                   <text x="300" y="225" font-size="20" text-anchor="middle" fill="#ffffff" dominant-baseline="middle">Sem dados para Barras</text>
-                 </svg>"##.to_string();
+// Mapa de calor da matriz de confusão: linhas = classe anotada, colunas = classe detectada
+fn gerar_svg_matriz_confusao(matriz: &MatrizConfusao) -> String {
+    let n = matriz.rotulos.len();
+    if n <= 1 {
+        return String::new();
     }
-    let altura_total = 450;
-    let largura_barra = 30;
-    let espacamento = 80;
-    let largura_total_svg = 60 + building_summaries.len() as i32 * espacamento;
-
-    let max_count_val = building_summaries.iter()
-        .flat_map(|s| vec![s.termica_count, s.retracao_count])
-        .max()
-        .unwrap_or(1) as f64;
-    let max_bar_height = 200.0; // Max height for a bar
-
+    let celula = 56;
+    let margem = 110;
+    let lado = margem + n as i32 * celula + 10;
+    let maximo = matriz.valores.iter().flatten().copied().max().unwrap_or(0).max(1) as f64;
     let mut svg = format!(
-        r###"<svg width="{largura_total_svg}" height="{altura_total}" viewBox="0 0 {largura_total_svg} {altura_total}" xmlns="http://www.w3.org/2000/svg">"###
-    );
-
-    svg.push_str(
-        r###"
-        <defs>
-            <linearGradient id="grad_red" x1="0%" y1="0%" x2="0%" y2="100%">
-                <stop offset="0%" style="stop-color:#ff5a5f; stop-opacity:1" />
-                <stop offset="100%" style="stop-color:#d62828; stop-opacity:1" />
-            </linearGradient>
-            <linearGradient id="grad_blue" x1="0%" y1="0%" x2="0%" y2="100%">
-                <stop offset="0%" style="stop-color:#0077ff; stop-opacity:1" />
-                <stop offset="100%" style="stop-color:#0055aa; stop-opacity:1" />
-            </linearGradient>
-        </defs>
-        "###
+        r###"<svg width="{lado}" height="{lado}" viewBox="0 0 {lado} {lado}" xmlns="http://www.w3.org/2000/svg">"###
     );
-
-    for (i, summary) in building_summaries.iter().enumerate() {
-        let x_base = 60 + i as i32 * espacamento;
-        let h_termica = if max_count_val == 0.0 { 0.0 } else { (summary.termica_count as f64 / max_count_val * max_bar_height) };
-        let h_retracao = if max_count_val == 0.0 { 0.0 } else { (summary.retracao_count as f64 / max_count_val * max_bar_height) };
-
-        // Térmica bar
-        svg.push_str(&format!(
-            r###"<rect x="{x}" y="{y}" width="{w}" height="0" fill="url(#grad_red)" rx="3">
-                    <animate attributeName="height" from="0" to="{h}" dur="0.8s" fill="freeze" />
-                    <animate attributeName="y" from="{y_plus}" to="{y}" dur="0.8s" fill="freeze" />
-                    <title>Térmica: {val}</title>
-                </rect>"###,
-            x = x_base,
-            y = 250.0 - h_termica,
-            y_plus = 250.0,
-            w = largura_barra,
-            h = h_termica,
-            val = summary.termica_count
-        ));
-
-        // Retração bar
+    for (i, rotulo) in matriz.rotulos.iter().enumerate() {
+        let centro = margem + i as i32 * celula + celula / 2;
         svg.push_str(&format!(
-            r###"<rect x="{x}" y="{y}" width="{w}" height="0" fill="url(#grad_blue)" rx="3">
-                    <animate attributeName="height" from="0" to="{h}" dur="0.8s" fill="freeze" />
-                    <animate attributeName="y" from="{y_plus}" to="{y}" dur="0.8s" fill="freeze" />
-                    <title>Retração: {val}</title>
-                </rect>"###,
-            x = x_base + largura_barra + 2, // Small gap between bars
-            y = 250.0 - h_retracao,
-            y_plus = 250.0,
-            w = largura_barra,
-            h = h_retracao,
-            val = summary.retracao_count
+            r###"<text x="{x}" y="{y}" font-size="11" text-anchor="middle" fill="#f0f0f0">{rotulo}</text>
+                <text x="{xl}" y="{centro}" font-size="11" text-anchor="end" fill="#f0f0f0" dominant-baseline="middle">{rotulo}</text>"###,
+            x = centro,
+            y = margem - 8,
+            xl = margem - 8
         ));
+    }
+    for (i, linha) in matriz.valores.iter().enumerate() {
+        for (j, valor) in linha.iter().enumerate() {
+            let intensidade = *valor as f64 / maximo;
+            // Diagonal (acerto) em azul, fora dela (erro) em vermelho
+            let cor = if i == j { format!("rgba(0, 119, 255, {:.2})", 0.15 + 0.85 * intensidade) } else { format!("rgba(255, 90, 95, {:.2})", 0.1 + 0.9 * intensidade) };
+            svg.push_str(&format!(
+                r###"<rect x="{x}" y="{y}" width="{c}" height="{c}" fill="{cor}" stroke="#242526" />
+                    <text x="{tx}" y="{ty}" font-size="13" text-anchor="middle" fill="#ffffff" dominant-baseline="middle">{valor}</text>"###,
+                x = margem + j as i32 * celula,
+                y = margem + i as i32 * celula,
+                c = celula,
+                tx = margem + j as i32 * celula + celula / 2,
+                ty = margem + i as i32 * celula + celula / 2
+            ));
+        }
+    }
+    svg.push_str(&format!(
+        r###"<text x="{x}" y="14" font-size="12" text-anchor="middle" fill="#b0b3b8">Detectada</text>
+            <text x="12" y="{y}" font-size="12" text-anchor="middle" fill="#b0b3b8" transform="rotate(-90 12 {y})">Anotada</text>"###,
+        x = margem + n as i32 * celula / 2,
+        y = margem + n as i32 * celula / 2
+    ));
+    svg.push_str("</svg>");
+    svg
+}
 
-        // Building name label
+// Por classe: imagens atribuídas pelo classificador e caixas encontradas pelo detector
+fn gerar_svg_classificacao(resumo: &[ResumoClassificacao], taxonomia: &Taxonomia) -> String {
+    let largura_barra = 26;
+    let espacamento = 90;
+    let base = 220.0;
+    let altura_max = 180.0;
+    let largura_total = 60 + resumo.len() as i32 * espacamento;
+    let maximo = resumo.iter().flat_map(|r| [r.imagens, r.caixas]).max().unwrap_or(0).max(1) as f64;
+    let mut svg = format!(
+        r###"<svg width="{largura_total}" height="260" viewBox="0 0 {largura_total} 260" xmlns="http://www.w3.org/2000/svg">"###
+    );
+    for (i, r) in resumo.iter().enumerate() {
+        let x_base = 60 + i as i32 * espacamento;
+        let rotulo = taxonomia.rotulo(&r.classe);
+        for (j, (valor, cor, nome)) in [(r.imagens, taxonomia.cor(&r.classe), "Imagens (classificador)"), (r.caixas, "#b0b3b8".to_string(), "Caixas (detector)")].iter().enumerate() {
+            let h = *valor as f64 / maximo * altura_max;
+            svg.push_str(&format!(
+                r###"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="{cor}" rx="3"><title>{rotulo} · {nome}: {valor}</title></rect>
+                    <text x="{tx}" y="{ty}" font-size="10" text-anchor="middle" fill="#f0f0f0">{valor}</text>"###,
+                x = x_base + j as i32 * (largura_barra + 2),
+                y = base - h,
+                w = largura_barra,
+                tx = x_base + j as i32 * (largura_barra + 2) + largura_barra / 2,
+                ty = base - h - 4.0
+            ));
+        }
         svg.push_str(&format!(
-            r###"<text x="{x_text}" y="270" font-size="10" text-anchor="middle" fill="#f0f0f0">{name}</text>"###,
-            x_text = x_base + largura_barra + 1, // Centered under the pair of bars
-            name = summary.building_name
+            r###"<text x="{x}" y="{y}" font-size="11" text-anchor="middle" fill="#f0f0f0">{rotulo}</text>"###,
+            x = x_base + largura_barra + 1,
+            y = base + 18.0
         ));
     }
-
     svg.push_str("</svg>");
     svg
 }
@@ -298,150 +224,464 @@ pub struct GraphViewProps {
 
 #[component]
 pub fn GraphView(props: GraphViewProps) -> Element {
-    match ler_json_detection_results(&props.project_name) {
-        Ok(detection_data) => {
-            let mut total_termica_overall = 0u32;
-            let mut total_retracao_overall = 0u32;
-            let mut building_fissura_map: HashMap<String, BuildingFissuraSummary> = HashMap::new();
-
-            for item_data in detection_data {
-                let mut current_image_termica = 0u32;
-                let mut current_image_retracao = 0u32;
-                for fissura_item in item_data.fissura {
-                    if fissura_item.name.to_lowercase() == "termica" {
-                        total_termica_overall += 1;
-                        current_image_termica +=1;
-                    } else if fissura_item.name.to_lowercase() == "retracao" || fissura_item.name.to_lowercase() == "retraçao" {
-                        total_retracao_overall += 1;
-                        current_image_retracao +=1;
+    let navigator = use_navigator();
+    let project_dir = project_index::project_dir(&props.project_name);
+
+    // Raw results and the index are loaded once; the detection policy is applied on every render,
+    // so the charts follow the policy controls below
+    let brutas = use_signal({
+        let project_dir = project_dir.clone();
+        move || deteccoes::carregar_brutas(&project_dir).map_err(|e| format!("{:#}", e))
+    });
+    let index = use_signal({
+        let project_dir = project_dir.clone();
+        move || ProjectIndex::abrir(&project_dir).unwrap_or_default()
+    });
+    let mut politica = use_signal({
+        let project_dir = project_dir.clone();
+        move || ProjectSettings::load(&project_dir).unwrap_or_default().politica
+    });
+    let revisao = use_signal({
+        let project_dir = project_dir.clone();
+        move || RevisaoProjeto::load(&project_dir).unwrap_or_default()
+    });
+    // Segunda etapa (classificador); vazio se o projeto não roda a classificação
+    let classificacoes = use_signal({
+        let project_dir = project_dir.clone();
+        move || classificacao::carregar(&project_dir).map_err(|e| format!("{:#}", e))
+    });
+    let confianca_classificacao = use_signal({
+        let project_dir = project_dir.clone();
+        move || ProjectSettings::load(&project_dir).unwrap_or_default().classificacao.confianca_minima
+    });
+    let erro_politica = use_signal(|| None::<String>);
+    // Prédio escolhido para o donut e para o relatório; None = projeto inteiro
+    let mut predio_selecionado = use_signal(|| None::<String>);
+    let mut status_avaliacao = use_signal(String::new);
+    let modelo_ativo = use_signal({
+        let project_dir = project_dir.clone();
+        move || {
+            let settings = ProjectSettings::load(&project_dir).unwrap_or_default();
+            RegistroModelos::load().ok().and_then(|registro| registro.resolver(settings.modelo_ativo.as_deref()).ok().cloned())
+        }
+    });
+
+    let brutas_lidas = brutas.read();
+    let detection_data = match &*brutas_lidas {
+        Ok(detection_data) => detection_data,
+        Err(error_message) => {
+            return rsx! {
+                div {
+                    style: "padding: 20px; color: red; text-align: center; font-family: 'Segoe UI', sans-serif; background-color: #242526; min-height: 100vh; display: flex; flex-direction: column; justify-content: center; align-items: center;",
+                    h1 { "Erro ao carregar dados para o gráfico" },
+                    p { "{error_message}" },
+                    p { "Verifique se o arquivo 'Projects/{props.project_name}/detection_results.json' existe e está no formato correto."}
+                }
+            };
+        }
+    };
+
+    let index = index.read();
+    let aviso_modelo = deteccoes::aviso_modelo(detection_data, modelo_ativo.read().as_ref());
+    let classes = deteccoes::classes_presentes(detection_data);
+    let total_brutas: usize = detection_data.iter().map(|item| item.fissura.len()).sum();
+    let filtradas = deteccoes::filtrar(detection_data, &index, &politica.read(), &revisao.read());
+    let total_consideradas: usize = filtradas.iter().map(|item| item.fissura.len()).sum();
+    let (resumo_classificacao, imagens_classificadas, erro_classificacao) = match &*classificacoes.read() {
+        Ok(lista) => (classificacao::resumir(lista, &filtradas, &index, confianca_classificacao()), lista.len(), None),
+        Err(e) => (Vec::new(), 0, Some(e.clone())),
+    };
+
+    let taxonomia = Taxonomia::do_projeto(&project_dir);
+    let (classes_grafico, building_summaries) = agregar_graficos(&filtradas, classes, &index, &taxonomia, predio_selecionado().as_deref());
+
+    let opcoes_predios: Vec<(String, String)> = index.predios.iter().map(|p| (p.id.clone(), p.nome.clone())).collect();
+    let titulo_donut = match predio_selecionado() {
+        Some(id) => format!("Distribuição de Fissuras — {}", index.nome_exibicao(&id)),
+        None => "Distribuição Total de Fissuras".to_string(),
+    };
+    let donut_svg = graficos::svg_donut(&classes_grafico, Estilo::Tela);
+    let barras_svg = graficos::svg_barras(&building_summaries, &classes_grafico, Estilo::Tela);
+    let resumo_classes = classes_grafico
+        .iter()
+        .map(|c| format!("{}: {}", c.rotulo, c.total))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    // Avaliação contra as anotações de referência, no ponto de operação da política atual
+    let avaliacao = avaliacao::avaliar(detection_data, &index, &politica.read());
+    let metricas_svg = gerar_svg_metricas(&avaliacao.classes, &taxonomia);
+    let matriz_svg = gerar_svg_matriz_confusao(&MatrizConfusao {
+        rotulos: avaliacao.matriz.rotulos.iter().map(|r| taxonomia.rotulo(r)).collect(),
+        valores: avaliacao.matriz.valores.clone(),
+    });
+    let confianca_padrao = (politica.read().confianca_minima_padrao * 100.0).round();
+    let iou_nms = politica.read().iou_nms;
+
+    rsx! {
+        div {
+            style: "
+                background-color: #242526;
+                color: #f0f0f0;
+                font-family: 'Segoe UI', sans-serif;
+                min-height: 100vh;
+                display: flex;
+                justify-content: center;
+                align-items: center;
+                padding: 40px;
+                position: relative;
+            ",
+
+            // Botão home
+            button {
+                onclick: move |_| {
+                    navigator.push(Route::HomePage {});  
+                },
+                style: "
+                    position: absolute;
+                    top: 20px;
+                    left: 20px;
+                    background-color: #ff5a5f;
+                    color: white;
+                    border: none;
+                    padding: 10px 16px;
+                    border-radius: 6px;
+                    cursor: pointer;
+                    font-size: 14px;
+                ",
+                "← Início"
+            }
+
+            // Botão de revisão do agrupamento
+            button {
+                onclick: {
+                    let project_name = props.project_name.clone();
+                    move |_| {
+                        navigator.push(Route::GroupingReview { project_name: project_name.clone() });
+                    }
+                },
+                style: "
+                    position: absolute;
+                    top: 20px;
+                    left: 130px;
+                    background-color: #0077ff;
+                    color: white;
+                    border: none;
+                    padding: 10px 16px;
+                    border-radius: 6px;
+                    cursor: pointer;
+                    font-size: 14px;
+                ",
+                "Revisar agrupamento"
+            }
+
+            // Botão da revisão humana das detecções
+            button {
+                onclick: {
+                    let project_name = props.project_name.clone();
+                    move |_| {
+                        navigator.push(Route::DetectionReview { project_name: project_name.clone() });
                     }
+                },
+                style: "
+                    position: absolute;
+                    top: 20px;
+                    left: 310px;
+                    background-color: #0077ff;
+                    color: white;
+                    border: none;
+                    padding: 10px 16px;
+                    border-radius: 6px;
+                    cursor: pointer;
+                    font-size: 14px;
+                ",
+                "Revisar detecções"
+            }
+
+            div {
+                style: "
+                    display: flex;
+                    justify-content: space-between;
+                    align-items: flex-start;
+                    gap: 40px;
+                    flex-wrap: wrap;
+                    max-width: 1400px;
+                ",
+
+                h1 {
+                    style: "font-size: 32px; color: #ff5a5f; margin-bottom: 10px; text-align: center; width: 100%;",
+                    "Gráficos das Fissuras (Projeto: {props.project_name})"
                 }
-                
-                // Aggregate for bar chart by building
-                if let Some(building_name) = extract_building_name_from_path(&item_data.path) {
-                    let summary = building_fissura_map.entry(building_name.clone()).or_insert_with(|| BuildingFissuraSummary {
-                        building_name,
-                        termica_count: 0,
-                        retracao_count: 0,
-                    });
-                    summary.termica_count += current_image_termica;
-                    summary.retracao_count += current_image_retracao;
+
+                if let Some(aviso) = aviso_modelo {
+                    p {
+                        style: "width: 100%; text-align: center; color: #ffcc00; background-color: #3a3b3c; border-radius: 6px; padding: 10px;",
+                        "⚠ {aviso}"
+                    }
                 }
-            }
-            
-            let building_summaries: Vec<BuildingFissuraSummary> = building_fissura_map.values().cloned().collect();
 
-            let donut_svg = gerar_svg_donut(total_termica_overall, total_retracao_overall);
-            let barras_svg = gerar_svg_barras(&building_summaries);
-            let navigator = use_navigator();
+                // gráfico Donut
+                div {
+                    style: "flex: 1; min-width: 400px; text-align: center;",
+                    h2 { style: "font-size: 24px; color: #ffffff;", "{titulo_donut}" }
+                    div { dangerous_inner_html: donut_svg }
+                    p { style: "margin-top: 10px; font-size: 14px;", "{resumo_classes}" }
+                    div {
+                        style: "margin-top: 10px; font-size: 14px;",
+                        for classe in classes_grafico.iter() {
+                            span { key: "{classe.rotulo}", style: "color: {classe.cor}; margin-right: 10px;", "⬤ {classe.rotulo}" }
+                        }
+                    }
+                }
 
-            rsx! {
+                // Barras e  botão 
                 div {
-                    style: "
-                        background-color: #242526;
-                        color: #f0f0f0;
-                        font-family: 'Segoe UI', sans-serif;
-                        min-height: 100vh;
-                        display: flex;
-                        justify-content: center;
-                        align-items: center;
-                        padding: 40px;
-                        position: relative;
-                    ",
-
-                    // Botão home
+                    style: "flex: 1; min-width: 600px; position: relative;",
+                    h2 { style: "font-size: 24px; color: #ffffff;", "Fissuras por Edifício" }
+                    div {
+                        style: "margin-top: 10px; overflow-x: auto;",
+                        div { dangerous_inner_html: barras_svg }
+                    }
+
+                    div {
+                        style: "margin-top: 10px; margin-bottom: 60px; display: flex; align-items: center; gap: 10px; font-size: 14px;",
+                        label { "Prédio:" }
+                        select {
+                            style: "background-color: #3a3b3c; color: #f0f0f0; border: 1px solid #555; border-radius: 4px; padding: 6px;",
+                            value: predio_selecionado().unwrap_or_default(),
+                            onchange: move |e| {
+                                let valor = e.value();
+                                predio_selecionado.set(if valor.is_empty() { None } else { Some(valor) });
+                            },
+                            option { value: "", "Todos os prédios (resumo do projeto)" }
+                            for (id, nome) in opcoes_predios.iter() {
+                                option { key: "{id}", value: "{id}", "{nome}" }
+                            }
+                        }
+                    }
+
                     button {
                         onclick: move |_| {
-                            navigator.push(Route::HomePage {});  
+                            let building_name = predio_selecionado().unwrap_or_else(|| RESUMO_PROJETO.to_string());
+                            navigator.push(Route::ReportView { project_name: props.project_name.clone(), building_name });
                         },
                         style: "
                             position: absolute;
-                            top: 20px;
-                            left: 20px;
-                            background-color: #ff5a5f;
+                            bottom: 10px;
+                            right: 20px;
+                            background-color: #0077ff;
                             color: white;
                             border: none;
                             padding: 10px 16px;
                             border-radius: 6px;
                             cursor: pointer;
-                            font-size: 14px;
+                            font-size: 16px;
                         ",
-                        "← Início"
+                        "Visualizar relatório detalhado →"
                     }
+                }
 
+                // Classificador x detector: só aparece quando o projeto rodou a classificação
+                if imagens_classificadas > 0 {
                     div {
-                        style: "
-                            display: flex;
-                            justify-content: space-between;
-                            align-items: flex-start;
-                            gap: 40px;
-                            flex-wrap: wrap;
-                            max-width: 1400px;
-                        ",
-
-                        h1 {
-                            style: "font-size: 32px; color: #ff5a5f; margin-bottom: 10px; text-align: center; width: 100%;",
-                            "Gráficos das Fissuras (Projeto: {props.project_name})"
+                        style: "width: 100%; background-color: #3a3b3c; border-radius: 8px; padding: 20px; font-size: 14px;",
+                        h2 { style: "font-size: 20px; color: #ffffff; margin-top: 0;", "Classificação das Imagens" }
+                        p { style: "color: #b0b3b8;",
+                            "{imagens_classificadas} imagem(ns) classificada(s) · probabilidade mínima: {(confianca_classificacao() * 100.0).round()}%"
                         }
-
-                        // gráfico Donut
+                        div { dangerous_inner_html: gerar_svg_classificacao(&resumo_classificacao, &taxonomia) }
                         div {
-                            style: "flex: 1; min-width: 400px; text-align: center;",
-                            h2 { style: "font-size: 24px; color: #ffffff;", "Distribuição Total de Fissuras" }
-                            div { dangerous_inner_html: donut_svg }
-                            p { style: "margin-top: 10px; font-size: 14px;", "Térmicas: {total_termica_overall} | Retração: {total_retracao_overall}" }
-                            div {
-                                style: "margin-top: 10px; font-size: 14px;",
-                                span { style: "color: #ff5a5f; margin-right: 10px;", "⬤ Térmica" }
-                                span { style: "color: #0077ff;", "⬤ Retração" }
-                            }
+                            style: "margin-top: 6px;",
+                            span { style: "margin-right: 10px;", "Barra colorida: imagens com o tipo de fissura (classificador)" }
+                            span { style: "color: #b0b3b8;", "⬤ Caixas detectadas (detector)" }
                         }
+                    }
+                }
+                if let Some(erro) = erro_classificacao {
+                    p { style: "width: 100%; color: #ff5a5f;", "Erro ao carregar a classificação: {erro}" }
+                }
 
-                        // Barras e  botão 
+                // Avaliação do modelo: só aparece quando há anotações de referência importadas
+                if avaliacao.imagens_avaliadas > 0 {
+                    div {
+                        style: "width: 100%; background-color: #3a3b3c; border-radius: 8px; padding: 20px; font-size: 14px;",
+                        h2 { style: "font-size: 20px; color: #ffffff; margin-top: 0;", "Avaliação do Modelo" }
+                        p { style: "color: #b0b3b8;",
+                            "{avaliacao.imagens_avaliadas} imagem(ns) com anotações de referência · mAP@0.5: {avaliacao.map50:.3} · IoU mínimo para acerto: {avaliacao::IOU_MINIMO}"
+                        }
                         div {
-                            style: "flex: 1; min-width: 600px; position: relative;",
-                            h2 { style: "font-size: 24px; color: #ffffff;", "Fissuras por Edifício" }
+                            style: "display: flex; gap: 40px; flex-wrap: wrap; align-items: flex-start;",
                             div {
-                                style: "margin-top: 10px; overflow-x: auto;",
-                                div { dangerous_inner_html: barras_svg }
+                                h3 { style: "color: #ffffff;", "Precisão, recall e F1 por classe" }
+                                div { dangerous_inner_html: metricas_svg }
+                                div {
+                                    style: "margin-top: 6px;",
+                                    span { style: "color: #ff5a5f; margin-right: 10px;", "⬤ Precisão" }
+                                    span { style: "color: #0077ff; margin-right: 10px;", "⬤ Recall" }
+                                    span { style: "color: #2ecc71;", "⬤ F1" }
+                                }
                             }
-
-                            button {
-                                onclick: move |_| {
-                                    let building_name = "Galpão_3".to_string(); 
-                                    navigator.push(Route::ReportView { project_name: props.project_name.clone(), building_name });
-                                },
-                                style: "
-                                    position: absolute;
-                                    bottom: 10px;
-                                    right: 20px;
-                                    background-color: #0077ff;
-                                    color: white;
-                                    border: none;
-                                    padding: 10px 16px;
-                                    border-radius: 6px;
-                                    cursor: pointer;
-                                    font-size: 16px;
-                                ",
-                                "Visualizar relatório detalhado →"
+                            div {
+                                h3 { style: "color: #ffffff;", "Matriz de confusão" }
+                                div { dangerous_inner_html: matriz_svg }
+                            }
+                        }
+                        table {
+                            style: "margin-top: 15px; border-collapse: collapse; width: 100%;",
+                            tr {
+                                for titulo in ["Classe", "Anotações", "VP", "FP", "FN", "Precisão", "Recall", "F1", "AP@0.5"] {
+                                    th { style: "text-align: left; padding: 4px 8px; border-bottom: 1px solid #4a4b4c;", "{titulo}" }
+                                }
+                            }
+                            for m in avaliacao.classes.iter() {
+                                tr { key: "{m.classe}",
+                                    td { style: "padding: 4px 8px;", "{taxonomia.rotulo(&m.classe)}" }
+                                    td { style: "padding: 4px 8px;", "{m.anotacoes}" }
+                                    td { style: "padding: 4px 8px;", "{m.verdadeiros_positivos}" }
+                                    td { style: "padding: 4px 8px;", "{m.falsos_positivos}" }
+                                    td { style: "padding: 4px 8px;", "{m.falsos_negativos}" }
+                                    td { style: "padding: 4px 8px;", "{m.precisao * 100.0:.1}%" }
+                                    td { style: "padding: 4px 8px;", "{m.recall * 100.0:.1}%" }
+                                    td { style: "padding: 4px 8px;", "{m.f1 * 100.0:.1}%" }
+                                    td { style: "padding: 4px 8px;", "{m.ap50:.3}" }
+                                }
                             }
                         }
+                        button {
+                            onclick: {
+                                let avaliacao = avaliacao.clone();
+                                move |_| {
+                                    let avaliacao = avaliacao.clone();
+                                    spawn(async move {
+                                        let Some(arquivo) = AsyncFileDialog::new()
+                                            .add_filter("CSV", &["csv"])
+                                            .set_file_name("avaliacao_modelo.csv")
+                                            .save_file()
+                                            .await
+                                        else {
+                                            return;
+                                        };
+                                        match avaliacao.exportar_csv(arquivo.path()) {
+                                            Ok(()) => status_avaliacao.set(format!("Avaliação exportada para {}", arquivo.path().display())),
+                                            Err(e) => status_avaliacao.set(format!("Erro ao exportar avaliação: {:#}", e)),
+                                        }
+                                    });
+                                }
+                            },
+                            style: "
+                                margin-top: 15px;
+                                background-color: #0077ff;
+                                color: white;
+                                border: none;
+                                padding: 10px 16px;
+                                border-radius: 6px;
+                                cursor: pointer;
+                                font-size: 14px;
+                            ",
+                            "Exportar CSV"
+                        }
+                        if !status_avaliacao.read().is_empty() {
+                            p { style: "color: #b0b3b8;", "{status_avaliacao}" }
+                        }
                     }
                 }
-            }
-        }
-        Err(e) => {
-            let error_message = match e {
-                JsonReadError::Io(io_err) => format!("Erro de I/O ao ler arquivo JSON: {}. Verifique o caminho e permissões.", io_err),
-                JsonReadError::Json(json_err) => format!("Erro ao parsear JSON: {}. Verifique o formato do arquivo.", json_err),
-                JsonReadError::PathError(path_err) => format!("Erro no caminho do arquivo: {}", path_err),
-            };
-            rsx! {
+
+                // Política de detecção: alterações são salvas no projeto e refletem nos gráficos na hora
                 div {
-                    style: "padding: 20px; color: red; text-align: center; font-family: 'Segoe UI', sans-serif; background-color: #242526; min-height: 100vh; display: flex; flex-direction: column; justify-content: center; align-items: center;",
-                    h1 { "Erro ao carregar dados para o gráfico" },
-                    p { "{error_message}" },
-                    p { "Verifique se o arquivo 'Projects/{props.project_name}/detection_results.json' existe e está no formato correto."}
+                    style: "width: 100%; background-color: #3a3b3c; border-radius: 8px; padding: 20px; font-size: 14px;",
+                    h2 { style: "font-size: 20px; color: #ffffff; margin-top: 0;", "Política de Detecção" }
+                    p { style: "color: #b0b3b8;", "Detecções consideradas: {total_consideradas} de {total_brutas}" }
+
+                    div {
+                        style: "display: flex; gap: 30px; flex-wrap: wrap; align-items: center; margin-bottom: 15px;",
+                        label {
+                            "Confiança mínima padrão (%): "
+                            input {
+                                style: "width: 70px; margin-left: 6px;",
+                                r#type: "number",
+                                min: "0",
+                                max: "100",
+                                step: "5",
+                                value: "{confianca_padrao}",
+                                onchange: {
+                                    let project_dir = project_dir.clone();
+                                    move |e: FormEvent| {
+                                        if let Ok(valor) = e.value().parse::<f64>() {
+                                            politica.write().confianca_minima_padrao = (valor / 100.0).clamp(0.0, 1.0);
+                                            salvar_politica(&project_dir, politica, erro_politica);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        label {
+                            "IoU para suprimir caixas sobrepostas (NMS): "
+                            input {
+                                style: "width: 70px; margin-left: 6px;",
+                                r#type: "number",
+                                min: "0",
+                                max: "1",
+                                step: "0.05",
+                                value: "{iou_nms}",
+                                onchange: {
+                                    let project_dir = project_dir.clone();
+                                    move |e: FormEvent| {
+                                        if let Ok(valor) = e.value().parse::<f64>() {
+                                            politica.write().iou_nms = valor.clamp(0.0, 1.0);
+                                            salvar_politica(&project_dir, politica, erro_politica);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    for classe in chaves_grafico.iter().cloned() {
+                        div {
+                            key: "{classe}",
+                            style: "display: flex; gap: 30px; align-items: center; margin-bottom: 8px;",
+                            span { style: "width: 120px; font-weight: bold; color: {taxonomia.cor(&classe)};", title: "{classe}", "{taxonomia.rotulo(&classe)}" }
+                            label {
+                                "Confiança mínima (%): "
+                                input {
+                                    style: "width: 70px; margin-left: 6px;",
+                                    r#type: "number",
+                                    min: "0",
+                                    max: "100",
+                                    step: "5",
+                                    value: "{(politica.read().confianca_minima_para(&classe) * 100.0).round()}",
+                                    onchange: {
+                                        let project_dir = project_dir.clone();
+                                        let classe = classe.clone();
+                                        move |e: FormEvent| {
+                                            if let Ok(valor) = e.value().parse::<f64>() {
+                                                politica.write().confianca_minima.insert(classe.clone(), (valor / 100.0).clamp(0.0, 1.0));
+                                                salvar_politica(&project_dir, politica, erro_politica);
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            label {
+                                input {
+                                    r#type: "checkbox",
+                                    checked: politica.read().ignora(&classe),
+                                    onchange: {
+                                        let project_dir = project_dir.clone();
+                                        let classe = classe.clone();
+                                        move |e: FormEvent| {
+                                            politica.write().definir_ignorada(&classe, e.checked());
+                                            salvar_politica(&project_dir, politica, erro_politica);
+                                        }
+                                    }
+                                }
+                                " Ignorar classe"
+                            }
+                        }
+                    }
+
+                    if let Some(erro) = erro_politica() {
+                        p { style: "color: #ff5a5f;", "{erro}" }
+                    }
                 }
             }
         }
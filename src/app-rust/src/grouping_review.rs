@@ -0,0 +1,509 @@
+// grouping_review.rs
+// Tela de revisão do agrupamento automático: permite mover fotos entre fachadas/prédios,
+// mesclar, separar e renomear prédios. As alterações são feitas no índice do projeto e depois
+// projetadas nas pastas (e no detection_results.json) por `ProjectIndex::aplicar_layout`.
+use dioxus::prelude::*;
+use dioxus_router::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, anyhow};
+use rfd::AsyncFileDialog;
+use crate::image_processor::{Predio, Fachada, sanitize_filename};
+use crate::project_index::{self, ProjectIndex, nome_fachada_padrao};
+use crate::gazetteer::{self, DISTANCIA_MAXIMA_SUGESTAO};
+use crate::Route;
+
+// Imagem sendo arrastada na tela
+#[derive(Debug, Clone, PartialEq)]
+struct ImagemArrastada {
+    predio_id: String,
+    pasta: String,
+    file_name: String,
+}
+
+// Abre o índice, aplica a alteração, move os arquivos de acordo e salva
+pub(crate) fn reorganizar<T>(project_dir: &Path, alteracao: impl FnOnce(&mut ProjectIndex) -> Result<T>) -> Result<T> {
+    let mut index = ProjectIndex::abrir(project_dir)?;
+    let resultado = alteracao(&mut index)?;
+    let layout = index.aplicar_layout(project_dir);
+    index.save(project_dir)?;
+    layout?;
+    Ok(resultado)
+}
+
+fn predio_mut<'a>(index: &'a mut ProjectIndex, id: &str) -> Result<&'a mut Predio> {
+    index.predio_mut(id).ok_or_else(|| anyhow!("Prédio {} não está no índice do projeto", id))
+}
+
+fn mover_imagem(index: &mut ProjectIndex, origem: &ImagemArrastada, predio_destino: &str, pasta_destino: &str) -> Result<()> {
+    if origem.predio_id == predio_destino && origem.pasta == pasta_destino {
+        return Ok(());
+    }
+
+    let predio_origem = predio_mut(index, &origem.predio_id)?;
+    let fachada_origem = predio_origem.fachadas.get_mut(&origem.pasta)
+        .ok_or_else(|| anyhow!("Fachada {} não encontrada", origem.pasta))?;
+    let pos = fachada_origem.imagens.iter().position(|im| im.file_name == origem.file_name)
+        .ok_or_else(|| anyhow!("Imagem {} não encontrada", origem.file_name))?;
+    let mut imagem = fachada_origem.imagens.remove(pos);
+    imagem.duplicata_de = None; // grupos de duplicatas são por fachada
+    predio_origem.recalcular_centroide();
+
+    let destino = predio_mut(index, predio_destino)?;
+    imagem.file_name = ProjectIndex::nome_livre(destino.fachadas.get(pasta_destino), &imagem.file_name);
+    destino.fachadas
+        .entry(pasta_destino.to_string())
+        .or_insert_with(|| Fachada { nome: nome_fachada_padrao(pasta_destino), imagens: Vec::new() })
+        .imagens
+        .push(imagem);
+    destino.recalcular_centroide();
+    Ok(())
+}
+
+fn mesclar_predios(index: &mut ProjectIndex, origem: &str, destino: &str) -> Result<()> {
+    if origem == destino {
+        return Err(anyhow!("Selecione dois prédios diferentes para mesclar"));
+    }
+    let pos = index.predios.iter().position(|p| p.id == origem)
+        .ok_or_else(|| anyhow!("Prédio {} não está no índice do projeto", origem))?;
+    let predio_origem = index.predios.remove(pos);
+
+    let predio_destino = predio_mut(index, destino)?;
+    for (pasta, fachada) in predio_origem.fachadas {
+        let fachada_destino = predio_destino.fachadas
+            .entry(pasta)
+            .or_insert_with(|| Fachada { nome: fachada.nome.clone(), imagens: Vec::new() });
+        for mut imagem in fachada.imagens {
+            imagem.file_name = ProjectIndex::nome_livre(Some(fachada_destino), &imagem.file_name);
+            fachada_destino.imagens.push(imagem);
+        }
+    }
+    predio_destino.recalcular_centroide();
+    if predio_destino.endereco.is_none() {
+        predio_destino.endereco = predio_origem.endereco;
+    }
+    Ok(())
+}
+
+// Separa uma fachada em um novo prédio, retornando o id criado
+fn separar_fachada(index: &mut ProjectIndex, predio: &str, pasta: &str) -> Result<String> {
+    let novo_id = format!("Predio-{}", index.proximo_numero_predio());
+    let predio_origem = predio_mut(index, predio)?;
+    let fachada = predio_origem.fachadas.remove(pasta)
+        .ok_or_else(|| anyhow!("Fachada {} não encontrada", pasta))?;
+    predio_origem.recalcular_centroide();
+
+    let mut novo = Predio {
+        id: novo_id.clone(),
+        nome: project_index::nome_padrao(&novo_id),
+        centroide: None,
+        endereco: None,
+        fachadas: [(pasta.to_string(), fachada)].into_iter().collect(),
+        todas_imagens_no_predio: Vec::new(),
+    };
+    novo.recalcular_centroide();
+    index.predios.push(novo);
+    Ok(novo_id)
+}
+
+// Renomeia a fachada; a pasta passa a ser "fachada-" + o nome sanitizado (se já existir, as fachadas são unidas)
+fn renomear_fachada(index: &mut ProjectIndex, predio: &str, pasta: &str, nova: &str) -> Result<()> {
+    let nova = nova.trim();
+    let nome_pasta = sanitize_filename(nova);
+    if nome_pasta.is_empty() {
+        return Err(anyhow!("Nome de fachada inválido"));
+    }
+    let nova_pasta = format!("fachada-{}", nome_pasta);
+    let predio = predio_mut(index, predio)?;
+    let mut fachada = predio.fachadas.remove(pasta)
+        .ok_or_else(|| anyhow!("Fachada {} não encontrada", pasta))?;
+    fachada.nome = nova.to_string();
+
+    match predio.fachadas.get_mut(&nova_pasta) {
+        Some(existente) => {
+            for mut imagem in fachada.imagens {
+                imagem.file_name = ProjectIndex::nome_livre(Some(existente), &imagem.file_name);
+                existente.imagens.push(imagem);
+            }
+        }
+        None => {
+            predio.fachadas.insert(nova_pasta, fachada);
+        }
+    }
+    Ok(())
+}
+
+// Altera o nome de exibição do prédio (a pasta mantém o id estável)
+fn renomear_predio(index: &mut ProjectIndex, id: &str, nome: &str) -> Result<()> {
+    let nome = nome.trim();
+    if nome.is_empty() {
+        return Err(anyhow!("Nome de prédio inválido"));
+    }
+    predio_mut(index, id)?.nome = nome.to_string();
+    Ok(())
+}
+
+fn definir_endereco(index: &mut ProjectIndex, id: &str, endereco: &str) -> Result<()> {
+    let endereco = endereco.trim();
+    predio_mut(index, id)?.endereco = if endereco.is_empty() { None } else { Some(endereco.to_string()) };
+    Ok(())
+}
+
+// Preenche o endereço a partir do gazetteer importado: de um prédio (`somente`) ou de todos os que ainda não têm
+fn sugerir_enderecos(project_dir: &Path, index: &mut ProjectIndex, somente: Option<&str>) -> Result<usize> {
+    let enderecos = gazetteer::carregar_do_projeto(project_dir);
+    if enderecos.is_empty() {
+        return Err(anyhow!("Nenhuma lista de endereços importada para este projeto"));
+    }
+    let mut preenchidos = 0;
+    for predio in index.predios.iter_mut().filter(|p| somente.is_none_or(|id| p.id == id)) {
+        // Sem um prédio específico, só preenche os que ainda não têm endereço
+        if somente.is_none() && predio.endereco.is_some() {
+            continue;
+        }
+        let Some(centroide) = predio.centroide else { continue };
+        if let Some(sugestao) = gazetteer::sugerir_endereco(&enderecos, &centroide, DISTANCIA_MAXIMA_SUGESTAO) {
+            predio.endereco = Some(sugestao.endereco.clone());
+            preenchidos += 1;
+        }
+    }
+    Ok(preenchidos)
+}
+
+pub(crate) fn carregar_indice(project_dir: &Path) -> (ProjectIndex, Option<String>) {
+    match ProjectIndex::abrir(project_dir) {
+        Ok(index) => (index, None),
+        Err(e) => (ProjectIndex::default(), Some(format!("Erro ao abrir índice do projeto: {}", e))),
+    }
+}
+
+// Índice do projeto para as telas de revisão. Carregado num efeito, depois da primeira renderização,
+// para o erro de leitura ir para o status fora da inicialização dos sinais.
+pub(crate) fn use_indice(project_dir: &Path, mut status: Signal<String>) -> Signal<ProjectIndex> {
+    let mut indice = use_signal(ProjectIndex::default);
+    let project_dir = project_dir.to_path_buf();
+    use_effect(move || {
+        let (index, erro) = carregar_indice(&project_dir);
+        if let Some(erro) = erro {
+            status.set(erro);
+        }
+        indice.set(index);
+    });
+    indice
+}
+
+// Mostra o resultado de uma operação e recarrega o índice
+pub(crate) fn concluir_operacao(
+    resultado: Result<String>,
+    project_dir: &Path,
+    mut indice: Signal<ProjectIndex>,
+    mut status: Signal<String>,
+) {
+    let (index, erro_indice) = carregar_indice(project_dir);
+    match (resultado, erro_indice) {
+        (Err(e), _) => status.set(format!("Erro: {}", e)),
+        (Ok(_), Some(erro)) => status.set(erro),
+        (Ok(msg), None) => status.set(msg),
+    }
+    indice.set(index);
+}
+
+#[derive(Props, PartialEq, Clone)]
+pub struct GroupingReviewProps {
+    pub project_name: String,
+}
+
+#[component]
+pub fn GroupingReview(props: GroupingReviewProps) -> Element {
+    let project_dir: PathBuf = project_index::project_dir(&props.project_name);
+    let mut status = use_signal(String::new);
+    let indice = use_indice(&project_dir, status);
+    let mut arrastando = use_signal(|| None::<ImagemArrastada>);
+    // Prédio de destino escolhido para a mesclagem, por prédio de origem
+    let mut mesclar_destino = use_signal(HashMap::<String, String>::new);
+    let navigator = use_navigator();
+
+    let predios = indice.read().predios.clone();
+    let opcoes_mesclar: Vec<(String, String)> = predios.iter().map(|p| (p.id.clone(), p.nome.clone())).collect();
+
+    rsx! {
+        document::Stylesheet { href: asset!("/assets/tailwind.css") }
+
+        div { class: "min-h-screen bg-gray-100 text-gray-900 font-sans",
+            document::Link {
+                href: "https://fonts.googleapis.com/icon?family=Material+Icons",
+                rel: "stylesheet"
+            }
+
+            header { class: "flex items-center justify-between bg-blue-600 text-black p-4 shadow",
+                div { class: "flex items-center gap-4",
+                    i { class: "material-icons", "photo_library" }
+                    h1 { class: "text-xl font-bold", "Revisão do Agrupamento: {props.project_name}" }
+                }
+                div { class: "flex items-center gap-2",
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        onclick: {
+                            let project_name = props.project_name.clone();
+                            move |_| { navigator.push(Route::DuplicateReview { project_name: project_name.clone() }); }
+                        },
+                        i { class: "material-icons", "filter_none" }
+                        "Duplicatas"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        onclick: {
+                            let project_name = props.project_name.clone();
+                            move |_| { navigator.push(Route::QualityReview { project_name: project_name.clone() }); }
+                        },
+                        i { class: "material-icons", "high_quality" }
+                        "Qualidade"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        onclick: {
+                            let project_name = props.project_name.clone();
+                            move |_| { navigator.push(Route::GraphView { project_name: project_name.clone() }); }
+                        },
+                        i { class: "material-icons", "bar_chart" }
+                        "Ver Gráficos"
+                    }
+                }
+            }
+
+            div { class: "container mx-auto px-4 py-8 max-w-6xl space-y-6",
+                p { class: "text-gray-600",
+                    "Arraste as fotos entre fachadas ou prédios. As pastas e os resultados de detecção são atualizados automaticamente."
+                }
+
+                div { class: "flex flex-wrap gap-4",
+                    button {
+                        class: "px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 flex items-center gap-2",
+                        onclick: {
+                            let project_dir = project_dir.clone();
+                            move |_| {
+                                let project_dir = project_dir.clone();
+                                spawn(async move {
+                                    if let Some(file) = AsyncFileDialog::new()
+                                        .add_filter("Endereços", &["csv", "osm", "xml"])
+                                        .pick_file()
+                                        .await
+                                    {
+                                        let resultado = gazetteer::importar(file.path(), &project_dir)
+                                            .map(|n| format!("{} endereços importados.", n));
+                                        concluir_operacao(resultado, &project_dir, indice, status);
+                                    }
+                                });
+                            }
+                        },
+                        i { class: "material-icons", "upload_file" }
+                        "Importar endereços (CSV/OSM)"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 flex items-center gap-2",
+                        onclick: {
+                            let project_dir = project_dir.clone();
+                            move |_| {
+                                let resultado = reorganizar(&project_dir, |index| sugerir_enderecos(&project_dir, index, None))
+                                    .map(|n| format!("Endereço sugerido para {} prédio(s).", n));
+                                concluir_operacao(resultado, &project_dir, indice, status);
+                            }
+                        },
+                        i { class: "material-icons", "place" }
+                        "Sugerir endereços (prédios sem endereço)"
+                    }
+                }
+
+                if !status.read().is_empty() {
+                    p { class: "text-center text-gray-700 py-2 px-4 border border-gray-300 bg-white rounded-md", "{status}" }
+                }
+
+                if predios.is_empty() {
+                    p { class: "text-center text-gray-500", "Nenhum prédio encontrado em {project_dir.join(\"images\").display()}." }
+                }
+
+                for predio in predios.into_iter() {
+                    div { key: "{predio.id}", class: "bg-white rounded-lg shadow-md p-6",
+                        div { class: "flex flex-wrap items-center gap-4 mb-4",
+                            i { class: "material-icons text-blue-500", "apartment" }
+                            input {
+                                class: "flex-1 px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                r#type: "text",
+                                value: "{predio.nome}",
+                                onchange: {
+                                    let project_dir = project_dir.clone();
+                                    let id = predio.id.clone();
+                                    move |e: FormEvent| {
+                                        let novo = e.value();
+                                        let resultado = reorganizar(&project_dir, |index| renomear_predio(index, &id, &novo))
+                                            .map(|_| format!("Prédio {} renomeado para {}", id, novo));
+                                        concluir_operacao(resultado, &project_dir, indice, status);
+                                    }
+                                }
+                            }
+                            span { class: "text-xs text-gray-400", "ID: {predio.id}" }
+                            select {
+                                class: "px-4 py-2 border border-gray-300 rounded-md bg-white",
+                                value: mesclar_destino.read().get(&predio.id).cloned().unwrap_or_default(),
+                                onchange: {
+                                    let id = predio.id.clone();
+                                    move |e: FormEvent| {
+                                        mesclar_destino.write().insert(id.clone(), e.value());
+                                    }
+                                },
+                                option { value: "", "Mesclar com..." }
+                                for (outro_id, outro_nome) in opcoes_mesclar.iter().filter(|(id, _)| *id != predio.id) {
+                                    option { key: "{outro_id}", value: "{outro_id}", "{outro_nome}" }
+                                }
+                            }
+                            button {
+                                class: "px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 flex items-center gap-2",
+                                onclick: {
+                                    let project_dir = project_dir.clone();
+                                    let origem = predio.id.clone();
+                                    move |_| {
+                                        let destino = mesclar_destino.read().get(&origem).cloned().unwrap_or_default();
+                                        if destino.is_empty() {
+                                            status.set("Selecione o prédio de destino para mesclar.".to_string());
+                                            return;
+                                        }
+                                        let resultado = reorganizar(&project_dir, |index| mesclar_predios(index, &origem, &destino))
+                                            .map(|_| format!("Prédio {} mesclado em {}", origem, destino));
+                                        concluir_operacao(resultado, &project_dir, indice, status);
+                                        // O prédio de origem deixa de existir: sai também como destino das outras linhas
+                                        mesclar_destino.write().retain(|id, destino| *id != origem && *destino != origem);
+                                    }
+                                },
+                                i { class: "material-icons", "merge_type" }
+                                "Mesclar"
+                            }
+                        }
+
+                        div { class: "flex flex-wrap items-center gap-4 mb-4",
+                            i { class: "material-icons text-gray-500", "place" }
+                            input {
+                                class: "flex-1 px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                r#type: "text",
+                                placeholder: "Endereço do prédio",
+                                value: "{predio.endereco.clone().unwrap_or_default()}",
+                                onchange: {
+                                    let project_dir = project_dir.clone();
+                                    let id = predio.id.clone();
+                                    move |e: FormEvent| {
+                                        let resultado = reorganizar(&project_dir, |index| definir_endereco(index, &id, &e.value()))
+                                            .map(|_| format!("Endereço do prédio {} atualizado", id));
+                                        concluir_operacao(resultado, &project_dir, indice, status);
+                                    }
+                                }
+                            }
+                            button {
+                                class: "px-4 py-2 bg-gray-200 text-gray-700 rounded-md hover:bg-gray-300 flex items-center gap-2",
+                                onclick: {
+                                    let project_dir = project_dir.clone();
+                                    let id = predio.id.clone();
+                                    move |_| {
+                                        let resultado = reorganizar(&project_dir, |index| sugerir_enderecos(&project_dir, index, Some(&id)))
+                                            .and_then(|n| {
+                                                if n == 0 {
+                                                    Err(anyhow!("Nenhum endereço próximo ao centroide do prédio {}", id))
+                                                } else {
+                                                    Ok(format!("Endereço sugerido para o prédio {}", id))
+                                                }
+                                            });
+                                        concluir_operacao(resultado, &project_dir, indice, status);
+                                    }
+                                },
+                                i { class: "material-icons", "travel_explore" }
+                                "Sugerir"
+                            }
+                        }
+
+                        div { class: "space-y-4",
+                            for (pasta, fachada) in predio.fachadas.into_iter() {
+                                div {
+                                    key: "{predio.id}-{pasta}",
+                                    class: "bg-gray-50 rounded-lg p-4 border-2 border-dashed border-gray-300",
+                                    ondragover: move |e| e.prevent_default(),
+                                    ondrop: {
+                                        let project_dir = project_dir.clone();
+                                        let predio_id = predio.id.clone();
+                                        let pasta = pasta.clone();
+                                        move |e: DragEvent| {
+                                            e.prevent_default();
+                                            if let Some(imagem) = arrastando.take() {
+                                                let resultado = reorganizar(&project_dir, |index| mover_imagem(index, &imagem, &predio_id, &pasta))
+                                                    .map(|_| format!("Imagem movida para {}/{}", predio_id, pasta));
+                                                concluir_operacao(resultado, &project_dir, indice, status);
+                                            }
+                                        }
+                                    },
+                                    div { class: "flex flex-wrap items-center gap-4 mb-4",
+                                        input {
+                                            class: "flex-1 px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                            r#type: "text",
+                                            value: "{fachada.nome}",
+                                            onchange: {
+                                                let project_dir = project_dir.clone();
+                                                let predio_id = predio.id.clone();
+                                                let pasta = pasta.clone();
+                                                move |e: FormEvent| {
+                                                    let nova = e.value();
+                                                    let resultado = reorganizar(&project_dir, |index| renomear_fachada(index, &predio_id, &pasta, &nova))
+                                                        .map(|_| format!("Fachada {} renomeada para {}", pasta, nova));
+                                                    concluir_operacao(resultado, &project_dir, indice, status);
+                                                }
+                                            }
+                                        }
+                                        span { class: "text-xs text-gray-400", "Pasta: {pasta}" }
+                                        button {
+                                            class: "px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 flex items-center gap-2",
+                                            onclick: {
+                                                let project_dir = project_dir.clone();
+                                                let predio_id = predio.id.clone();
+                                                let pasta = pasta.clone();
+                                                move |_| {
+                                                    let resultado = reorganizar(&project_dir, |index| separar_fachada(index, &predio_id, &pasta))
+                                                        .map(|novo| format!("Fachada {} separada no prédio {}", pasta, novo));
+                                                    concluir_operacao(resultado, &project_dir, indice, status);
+                                                }
+                                            },
+                                            i { class: "material-icons", "call_split" }
+                                            "Separar em novo prédio"
+                                        }
+                                    }
+
+                                    div { class: "grid grid-cols-2 sm:grid-cols-3 md:grid-cols-4 lg:grid-cols-6 gap-4",
+                                        for imagem in fachada.imagens.into_iter() {
+                                            div {
+                                                key: "{imagem.path.display()}",
+                                                class: "cursor-move",
+                                                draggable: "true",
+                                                ondragstart: {
+                                                    let arrastada = ImagemArrastada {
+                                                        predio_id: predio.id.clone(),
+                                                        pasta: pasta.clone(),
+                                                        file_name: imagem.file_name.clone(),
+                                                    };
+                                                    move |_| arrastando.set(Some(arrastada.clone()))
+                                                },
+                                                ondragend: move |_| arrastando.set(None),
+                                                div { class: "aspect-w-4 aspect-h-3 bg-gray-200 rounded-lg overflow-hidden",
+                                                    img {
+                                                        src: "file://{imagem.path.display()}",
+                                                        class: "w-full h-full object-cover",
+                                                        alt: "{imagem.file_name}"
+                                                    }
+                                                }
+                                                p { class: "mt-2 text-sm text-gray-600 truncate", "{imagem.file_name}" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
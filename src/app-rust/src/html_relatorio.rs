@@ -0,0 +1,131 @@
+// html_relatorio.rs
+// Exportação do relatório como um HTML autocontido, para abrir em qualquer navegador ou arquivar.
+// O Markdown é renderizado como na tela de relatório, com o report_page.css embutido e uma folha de
+// estilo de impressão; as imagens vão anotadas com as caixas detectadas, em base64 dentro do
+// próprio arquivo ou, quando passariam do limite abaixo, numa pasta ao lado dele. Os gráficos da
+// seção de estatísticas entram pelo SVG salvo ao lado do PNG, que continua nítido com zoom.
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use anyhow::{Result, Context};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::Local;
+use image::codecs::jpeg::JpegEncoder;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use crate::documento_relatorio;
+
+// Acima disso as imagens vão para a pasta <arquivo>_imagens/ em vez de embutidas
+const LIMITE_EMBUTIDO_BYTES: usize = 20 * 1024 * 1024;
+const QUALIDADE_JPEG: u8 = 85;
+
+const CSS_EXPORTACAO: &str = r#"
+body { background: #ffffff; }
+.text-viewer { margin: 2em auto; }
+.text-content img { max-width: 100%; height: auto; }
+.rodape-exportacao { text-align: center; color: #777; font-size: 0.8em; margin: 2em 0; }
+
+@media print {
+    @page { size: A4; margin: 2cm; }
+    .text-viewer { margin: 0; display: block; }
+    .text-content { border: none; box-shadow: none; padding: 0; max-width: none; font-size: 10pt; }
+    h1, h2, h3, h4 { break-after: avoid; }
+    img, figure, table, tr { break-inside: avoid; }
+    img { -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+    .rodape-exportacao { display: none; }
+}
+"#;
+
+fn escapar(texto: &str) -> String {
+    texto.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Cada imagem do Markdown, uma vez por caminho, com o tipo MIME: os gráficos como o SVG salvo ao
+// lado do PNG e as demais como JPEG anotado
+fn imagens_anotadas(project_dir: &Path, markdown: &str) -> Result<HashMap<String, (&'static str, Vec<u8>)>> {
+    let caixas = documento_relatorio::caixas_do_projeto(project_dir)?;
+    let mut imagens = HashMap::new();
+    for evento in Parser::new(markdown) {
+        let Event::Start(Tag::Image(_, destino, _)) = evento else { continue };
+        if imagens.contains_key(destino.as_ref()) {
+            continue;
+        }
+        let caminho = Path::new(destino.as_ref());
+        if caminho.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")) {
+            if let Ok(svg) = fs::read(caminho.with_extension("svg")) {
+                imagens.insert(destino.to_string(), ("image/svg+xml", svg));
+                continue;
+            }
+        }
+        let caixas_imagem = caixas.get(destino.as_ref()).map(|c| c.as_slice()).unwrap_or_default();
+        let jpeg = documento_relatorio::imagem_anotada(&destino, caixas_imagem).and_then(|imagem| {
+            let mut dados = Vec::new();
+            JpegEncoder::new_with_quality(&mut dados, QUALIDADE_JPEG).encode_image(&imagem)?;
+            Ok(dados)
+        });
+        match jpeg {
+            Ok(dados) => {
+                imagens.insert(destino.to_string(), ("image/jpeg", dados));
+            }
+            // Fica o caminho original no HTML; a imagem só aparece se existir na máquina de quem abrir
+            Err(e) => eprintln!("Imagem mantida sem anotação no HTML ({}): {:#}", destino, e),
+        }
+    }
+    Ok(imagens)
+}
+
+pub fn gerar(project_dir: &Path, markdown: &str, destino: &Path) -> Result<()> {
+    let imagens = imagens_anotadas(project_dir, markdown)?;
+    let embutir = imagens.values().map(|(_, dados)| dados.len()).sum::<usize>() <= LIMITE_EMBUTIDO_BYTES;
+
+    // Endereço de cada imagem no HTML: data URI ou arquivo na pasta ao lado
+    let mut enderecos: HashMap<String, String> = HashMap::new();
+    if embutir {
+        for (caminho, (tipo, dados)) in &imagens {
+            enderecos.insert(caminho.clone(), format!("data:{};base64,{}", tipo, STANDARD.encode(dados)));
+        }
+    } else {
+        let nome_pasta = format!("{}_imagens", destino.file_stem().unwrap_or_default().to_string_lossy());
+        let pasta = destino.with_file_name(&nome_pasta);
+        fs::create_dir_all(&pasta).with_context(|| format!("Falha ao criar {}", pasta.display()))?;
+        for (i, (caminho, (tipo, dados))) in imagens.iter().enumerate() {
+            let extensao = if *tipo == "image/svg+xml" { "svg" } else { "jpg" };
+            let arquivo = format!("imagem{}.{}", i + 1, extensao);
+            fs::write(pasta.join(&arquivo), dados).with_context(|| format!("Falha ao salvar {}", pasta.join(&arquivo).display()))?;
+            enderecos.insert(caminho.clone(), format!("{}/{}", nome_pasta, arquivo));
+        }
+    }
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    let eventos = Parser::new_ext(markdown, options).map(|evento| match evento {
+        Event::Start(Tag::Image(tipo, destino, titulo)) => {
+            let destino = enderecos.get(destino.as_ref()).map(|e| CowStr::from(e.clone())).unwrap_or(destino);
+            Event::Start(Tag::Image(tipo, destino, titulo))
+        }
+        outro => outro,
+    });
+    let mut corpo = String::new();
+    html::push_html(&mut corpo, eventos);
+
+    let titulo = markdown
+        .lines()
+        .find_map(|linha| linha.trim_start().strip_prefix("# "))
+        .map(str::trim)
+        .unwrap_or("Relatório de Inspeção de Fissuras");
+    let documento = format!(
+        concat!(
+            "<!DOCTYPE html>\n<html lang=\"pt-BR\">\n<head>\n<meta charset=\"utf-8\">\n",
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+            "<title>{titulo}</title>\n<style>\n{css}\n{css_exportacao}</style>\n</head>\n<body>\n",
+            "<main><div class=\"text-viewer\"><div class=\"text-content\">\n{corpo}\n</div></div></main>\n",
+            "<footer class=\"rodape-exportacao\">Exportado em {data}</footer>\n</body>\n</html>\n",
+        ),
+        titulo = escapar(titulo),
+        css = include_str!("Template/report_page.css"),
+        css_exportacao = CSS_EXPORTACAO,
+        corpo = corpo,
+        data = Local::now().format("%d/%m/%Y %H:%M"),
+    );
+    fs::write(destino, documento).with_context(|| format!("Falha ao salvar {}", destino.display()))
+}
@@ -5,44 +5,77 @@ use std::io::BufReader;
 use walkdir::WalkDir;
 use anyhow::{Result, Context, anyhow};
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, BTreeMap};
 use std::process::Command;
 use exif::{Tag, In, Reader, Value};
 use crate::create_project::PROJECT_NAME;
+use crate::project_index::{ProjectIndex, nome_padrao};
+use crate::duplicatas;
+use crate::qualidade::{self, AnaliseQualidade};
+use crate::anotacoes::AnotacaoReferencia;
 use dioxus::prelude::Readable;
+use serde::{Serialize, Deserialize};
 
 // Representa uma localização geográfica
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub struct Location {
     pub latitude: f64,
     pub longitude: f64,
 }
 
 // Metadados extraídos de uma imagem
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageMetadata {
+    // Caminho atual do arquivo; no índice do projeto é recalculado a partir de prédio/fachada/file_name
+    #[serde(skip)]
     pub path: PathBuf,
     pub file_name: String,
     pub location: Option<Location>,
     pub gps_img_direction: Option<f64>, // Em graus, 0-359.99, Norte verdadeiro
+    #[serde(default)]
+    pub phash: Option<u64>, // Hash perceptual (dHash) usado para achar duplicatas
+    #[serde(default)]
+    pub duplicata_de: Option<String>, // file_name da imagem mantida no grupo, se esta for duplicata
+    #[serde(default)]
+    pub qualidade: Option<AnaliseQualidade>, // Nitidez, exposição e resolução medidas na ingestão
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anotacoes: Vec<AnotacaoReferencia>, // Caixas de referência importadas (labelImg, YOLO, COCO)
 }
 
 // Representa uma fachada de um prédio
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fachada {
-    pub _nome: String, // "Norte", "Sul", "Leste", "Oeste", "Indefinida"
+    pub nome: String, // "Norte", "Sul", "Leste", "Oeste", "Indefinida"
     pub imagens: Vec<ImageMetadata>,
 }
 
 // Representa um prédio
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Predio {
-    pub id: String, // Ex: "Predio-1"
-    pub centroide: Location,
-    pub fachadas: HashMap<String, Fachada>,
+    pub id: String, // Ex: "Predio-1", estável e usado como nome da pasta
+    pub nome: String, // Nome de exibição, editável pelo usuário
+    #[serde(default)]
+    pub centroide: Option<Location>, // Ausente em prédios sem imagens com GPS
+    #[serde(default)]
+    pub endereco: Option<String>,
+    #[serde(default)]
+    pub fachadas: BTreeMap<String, Fachada>, // Chave: nome da pasta da fachada (ex: "fachada-Norte")
+    #[serde(skip)]
     pub todas_imagens_no_predio: Vec<ImageMetadata>,
 }
 
+impl Predio {
+    pub fn imagens(&self) -> impl Iterator<Item = &ImageMetadata> {
+        self.fachadas.values().flat_map(|f| f.imagens.iter())
+    }
+
+    // Recalcula o centroide a partir das imagens com GPS (mantém o atual se nenhuma tiver)
+    pub fn recalcular_centroide(&mut self) {
+        let locs: Vec<Location> = self.imagens().filter_map(|im| im.location).collect();
+        self.centroide = calculate_centroid(&locs).or(self.centroide);
+    }
+}
+
 // Estatísticas do processamento de imagens (mantida para compatibilidade e informação)
 #[derive(Debug, Clone, Default)]
 pub struct ProcessingStats {
@@ -51,6 +84,9 @@ pub struct ProcessingStats {
     pub images_without_gps: usize,
     pub images_with_direction: usize,
     pub predio_groups: usize, // Renomeado de location_groups
+    pub duplicate_images: usize, // Marcadas como duplicata de outra foto da mesma fachada
+    pub low_quality_images: usize, // Reprovadas na verificação de nitidez/exposição/resolução
+    pub quality_warnings: Vec<String>,
     pub errors: Vec<String>,
 }
 
@@ -73,7 +109,7 @@ fn nome_para_tag() -> HashMap<&'static str, Tag> {
 }
 
 // Calcula a distância Haversine entre duas localizações em metros
-fn haversine_distance(loc1: &Location, loc2: &Location) -> f64 {
+pub(crate) fn haversine_distance(loc1: &Location, loc2: &Location) -> f64 {
     const R: f64 = 6371000.0; // Raio da Terra em metros
     let d_lat = (loc2.latitude - loc1.latitude).to_radians();
     let d_lon = (loc2.longitude - loc1.longitude).to_radians();
@@ -86,7 +122,7 @@ fn haversine_distance(loc1: &Location, loc2: &Location) -> f64 {
 }
 
 // Calcula o centroide de uma lista de localizações
-fn calculate_centroid(locations: &[Location]) -> Option<Location> {
+pub(crate) fn calculate_centroid(locations: &[Location]) -> Option<Location> {
     if locations.is_empty() {
         return None;
     }
@@ -183,6 +219,10 @@ fn extract_image_metadata_exiftool(path: &Path) -> Result<ImageMetadata> {
         file_name,
         location: None,
         gps_img_direction: None,
+        phash: None,
+        duplicata_de: None,
+        qualidade: None,
+        anotacoes: Vec::new(),
     };
 
     // Executa exiftool para obter metadados completos
@@ -297,6 +337,10 @@ fn extract_image_metadata_lib(path: &Path, tag_map: &HashMap<&str, Tag>) -> Resu
         file_name,
         location: None,
         gps_img_direction: None,
+        phash: None,
+        duplicata_de: None,
+        qualidade: None,
+        anotacoes: Vec::new(),
     };
 
     let file = fs::File::open(path)
@@ -420,8 +464,33 @@ fn extract_gps_string(exif: &exif::Exif, tag_map: &HashMap<&str, Tag>) -> Option
     Some(Location { latitude: lat, longitude: lon })
 }
 
+// Lê os metadados de uma imagem já organizada no projeto (usado ao adotar arquivos que não estão no índice)
+pub(crate) fn ler_metadados(path: &Path) -> ImageMetadata {
+    let tag_map = nome_para_tag();
+    let mut image_meta = extract_image_metadata_lib(path, &tag_map).unwrap_or_else(|_| ImageMetadata {
+        path: path.to_path_buf(),
+        file_name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        location: None,
+        gps_img_direction: None,
+        phash: None,
+        duplicata_de: None,
+        qualidade: None,
+        anotacoes: Vec::new(),
+    });
+    (image_meta.phash, image_meta.qualidade) = analisar_pixels(path);
+    image_meta
+}
+
+// Decodifica a imagem uma única vez para calcular o hash perceptual e a análise de qualidade
+pub(crate) fn analisar_pixels(path: &Path) -> (Option<u64>, Option<AnaliseQualidade>) {
+    match image::open(path) {
+        Ok(imagem) => (Some(duplicatas::hash_imagem(&imagem)), Some(qualidade::analisar_imagem(&imagem))),
+        Err(_) => (None, None),
+    }
+}
+
 // Função para sanitizar nomes de arquivos/diretórios
-fn sanitize_filename(name: &str) -> String {
+pub(crate) fn sanitize_filename(name: &str) -> String {
     let forbidden_chars: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
     name.replace(' ', "_")
         .chars()
@@ -443,6 +512,9 @@ pub fn process_folder(folder_path_str: &str, distance_threshold_meters: f64) ->
     let base_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     let images_base_path = base_dir.join("Projects").join(&project_name).join("images");
 
+    let project_dir = base_dir.join("Projects").join(&project_name);
+    let mut index = ProjectIndex::abrir(&project_dir)?;
+
     let input_folder_path = Path::new(folder_path_str); // Path for input images
     let tag_map = nome_para_tag();
     
@@ -463,7 +535,7 @@ pub fn process_folder(folder_path_str: &str, distance_threshold_meters: f64) ->
         .filter(|e| {
             e.path().extension()
                 .and_then(|ext| ext.to_str())
-                .map_or(false, |ext| matches!(ext.to_lowercase().as_str(), "jpg" | "jpeg"))
+                .is_some_and(|ext| matches!(ext.to_lowercase().as_str(), "jpg" | "jpeg"))
         })
     {
         stats.total_images += 1;
@@ -498,6 +570,8 @@ pub fn process_folder(folder_path_str: &str, distance_threshold_meters: f64) ->
     }
 
     let mut predios: Vec<Predio> = Vec::new();
+    // Os ids continuam a numeração dos prédios já existentes no projeto
+    let primeiro_numero = index.proximo_numero_predio();
     
     // Agrupamento de imagens em prédios
     let mut images_to_assign = images_with_location.clone();
@@ -505,20 +579,24 @@ pub fn process_folder(folder_path_str: &str, distance_threshold_meters: f64) ->
     for image_meta in images_to_assign.drain(..) {
         let mut assigned_to_existing = false;
         for predio in predios.iter_mut() {
-            if haversine_distance(&image_meta.location.unwrap(), &predio.centroide) <= distance_threshold_meters {
+            let within_threshold = predio.centroide
+                .is_some_and(|c| haversine_distance(&image_meta.location.unwrap(), &c) <= distance_threshold_meters);
+            if within_threshold {
                 predio.todas_imagens_no_predio.push(image_meta.clone());
                 let locs: Vec<Location> = predio.todas_imagens_no_predio.iter().map(|im| im.location.unwrap()).collect();
-                predio.centroide = calculate_centroid(&locs).unwrap_or(predio.centroide);
+                predio.centroide = calculate_centroid(&locs).or(predio.centroide);
                 assigned_to_existing = true;
                 break;
             }
         }
         if !assigned_to_existing {
-            let new_predio_id = format!("Predio-{}", predios.len() + 1);
+            let new_predio_id = format!("Predio-{}", primeiro_numero + predios.len());
             predios.push(Predio {
+                nome: nome_padrao(&new_predio_id),
                 id: new_predio_id,
-                centroide: image_meta.location.unwrap(),
-                fachadas: HashMap::new(),
+                centroide: image_meta.location,
+                endereco: None,
+                fachadas: BTreeMap::new(),
                 todas_imagens_no_predio: vec![image_meta.clone()],
             });
         }
@@ -527,6 +605,7 @@ pub fn process_folder(folder_path_str: &str, distance_threshold_meters: f64) ->
     // Classificação de Fachadas e Criação de Pastas
     for predio in predios.iter_mut() {
         let sanitized_predio_id = sanitize_filename(&predio.id); // Sanitizar ID do prédio
+        predio.id = sanitized_predio_id.clone(); // O id do índice é o nome da pasta
         let predio_target_dir = images_base_path.join(&sanitized_predio_id); // Usar ID sanitizado
         if let Err(e) = fs::create_dir_all(&predio_target_dir) {
             stats.errors.push(format!("Erro ao criar pasta do prédio {}: {}", sanitized_predio_id, e));
@@ -537,12 +616,6 @@ pub fn process_folder(folder_path_str: &str, distance_threshold_meters: f64) ->
             let fachada_nome_str = determinar_fachada_nome(image_data.gps_img_direction);
             let base_fachada_dir_name = format!("fachada-{}", fachada_nome_str);
             let sanitized_fachada_dir_name = sanitize_filename(&base_fachada_dir_name); // Sanitizar nome da fachada
-            
-            let fachada_entry = predio.fachadas.entry(fachada_nome_str.clone()).or_insert_with(|| Fachada {
-                _nome: fachada_nome_str.clone(),
-                imagens: Vec::new(),
-            });
-            fachada_entry.imagens.push(image_data.clone());
 
             // Criar pasta da fachada e copiar imagem
             let fachada_target_dir = predio_target_dir.join(&sanitized_fachada_dir_name); // Usar nome sanitizado
@@ -565,6 +638,28 @@ pub fn process_folder(folder_path_str: &str, distance_threshold_meters: f64) ->
                             e_remove
                         ));
                     }
+
+                    // A imagem entra no modelo já com o caminho e o nome que tem dentro do projeto
+                    let fachada_entry = predio.fachadas.entry(sanitized_fachada_dir_name.clone()).or_insert_with(|| Fachada {
+                        nome: fachada_nome_str.clone(),
+                        imagens: Vec::new(),
+                    });
+                    let (phash, analise) = analisar_pixels(&target_image_path);
+                    match &analise {
+                        Some(analise) if !analise.aprovada() => {
+                            stats.low_quality_images += 1;
+                            stats.quality_warnings.push(format!("{}: {}", sanitized_image_filename, analise.resumo()));
+                        }
+                        Some(_) => {}
+                        None => stats.errors.push(format!("Não foi possível analisar a qualidade de {}", target_image_path.display())),
+                    }
+                    fachada_entry.imagens.push(ImageMetadata {
+                        path: target_image_path.clone(),
+                        file_name: sanitized_image_filename.clone(),
+                        phash,
+                        qualidade: analise,
+                        ..image_data.clone()
+                    });
                 }
                 Err(e_copy) => {
                     stats.errors.push(format!(
@@ -578,6 +673,19 @@ pub fn process_folder(folder_path_str: &str, distance_threshold_meters: f64) ->
         }
     }
 
+    // Fotos repetidas da mesma fachada (várias tomadas da mesma fissura, sobreposição do drone)
+    for predio in predios.iter_mut() {
+        for fachada in predio.fachadas.values_mut() {
+            stats.duplicate_images += duplicatas::marcar_duplicatas(fachada);
+        }
+    }
+
+    // Salva o modelo no índice do projeto; as pastas criadas acima são a projeção dele em disco
+    index.predios.extend(predios.iter().filter(|p| !p.fachadas.is_empty()).cloned());
+    if let Err(e) = index.save(&project_dir) {
+        stats.errors.push(format!("Erro ao salvar índice do projeto: {}", e));
+    }
+
     stats.predio_groups = predios.len();
     stats.images_without_gps = stats.total_images - stats.images_with_gps;
 
@@ -19,6 +19,39 @@ use create_project::NewProject;
 use ui::Home;
 mod graph;
 use graph::GraphView;
+mod grouping_review;
+use grouping_review::GroupingReview;
+mod project_index;
+mod gazetteer;
+mod duplicatas;
+mod duplicate_review;
+use duplicate_review::DuplicateReview;
+mod qualidade;
+mod quality_review;
+use quality_review::QualityReview;
+mod project_settings;
+mod deteccoes;
+mod revisao;
+mod detection_review;
+use detection_review::DetectionReview;
+mod exportacao_dataset;
+mod anotacoes;
+mod avaliacao;
+mod modelos;
+mod model_registry;
+use model_registry::ModelRegistry;
+mod taxonomia;
+mod classificacao;
+mod geometria;
+mod manifesto;
+mod versoes_relatorio;
+mod edicao_relatorio;
+mod documento_relatorio;
+mod pdf_relatorio;
+mod docx_relatorio;
+mod html_relatorio;
+mod graficos;
+mod templates_relatorio;
 
 #[component]
 fn Process() -> Element {
@@ -29,7 +62,12 @@ fn Process() -> Element {
 
 fn main() {
     dioxus::LaunchBuilder::desktop()
-        .with_cfg(Config::new().with_window(WindowBuilder::new().with_resizable(true)))
+        .with_cfg(
+            Config::new()
+                .with_window(WindowBuilder::new().with_resizable(true))
+                // Necessário para o arrastar-e-soltar HTML5 da tela de revisão funcionar no webview
+                .with_disable_drag_drop_handler(true)
+        )
         .launch(App);
 }
 
@@ -57,6 +95,21 @@ pub enum Route {
     #[route("/graph/:project_name")]
     GraphView { project_name: String },
     
+    #[route("/review/:project_name")]
+    GroupingReview { project_name: String },
+
+    #[route("/duplicates/:project_name")]
+    DuplicateReview { project_name: String },
+
+    #[route("/quality/:project_name")]
+    QualityReview { project_name: String },
+
+    #[route("/detection-review/:project_name")]
+    DetectionReview { project_name: String },
+
+    #[route("/models/:project_name")]
+    ModelRegistry { project_name: String },
+
     #[route("/report/:project_name/:building_name")]
     ReportView { project_name: String, building_name: String },
 
@@ -0,0 +1,48 @@
+// manifesto.rs
+// Dados do projeto informados na tela "Criar Novo Projeto", salvos em Projects/<projeto>/project.json.
+// É de onde vêm o responsável, a empresa, o tipo de estrutura e as observações do cabeçalho dos relatórios.
+use std::path::Path;
+use std::fs;
+use anyhow::{Result, Context};
+use serde::{Serialize, Deserialize};
+
+pub const MANIFEST_FILE_NAME: &str = "project.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ManifestoProjeto {
+    pub nome: String,
+    #[serde(default)]
+    pub descricao: String,
+    #[serde(default)]
+    pub responsavel: String,
+    #[serde(default)]
+    pub empresa: String, // Empresa responsável pela inspeção (cabeçalho e assinatura dos relatórios)
+    #[serde(default)]
+    pub tipo_estrutura: String,
+    #[serde(default)]
+    pub ano_construcao: Option<u32>,
+    #[serde(default)]
+    pub observacoes: String,
+    #[serde(default)]
+    pub criado_em: String, // "YYYY-MM-DD"
+}
+
+impl ManifestoProjeto {
+    // Projeto criado antes do manifesto devolve um manifesto vazio
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Falha ao ler {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Falha ao parsear {}", path.display()))
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(MANIFEST_FILE_NAME);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Falha ao salvar {}", path.display()))
+    }
+}
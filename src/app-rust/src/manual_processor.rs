@@ -5,23 +5,46 @@ use std::path::{PathBuf, Path};
 use std::fs;
 use dioxus::prelude::Readable;
 use std::process::{Command, Stdio};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use crate::project_index::ProjectIndex;
+use crate::project_settings::ProjectSettings;
+use crate::revisao::InfoRevisao;
+use crate::modelos::RegistroModelos;
+use crate::classificacao;
+use crate::geometria::GeometriaFissura;
+use crate::deteccoes;
 
 #[derive(Props, Clone, PartialEq)]
 pub struct ManualProcessorProps {
     pub project_name: String,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct FissuraData {
     pub name: String,
     pub confidence: f64,
+    // [x1, y1, x2, y2] em pixels da imagem original; ausente em resultados antigos
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<[f64; 4]>,
+    // Preenchido ao carregar os resultados com a revisão humana (nunca vem do script)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revisao: Option<InfoRevisao>,
+    // Medidas da fissura dentro da caixa (ver geometria.rs), também preenchidas só ao carregar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometria: Option<GeometriaFissura>,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ImageAnalysisResult {
     pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
     pub fissura: Vec<FissuraData>,
+    // Id (registro de modelos) do modelo que gerou o resultado; ausente em resultados antigos
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modelo: Option<String>,
 }
 
 #[derive(Clone, PartialEq)]
@@ -150,6 +173,10 @@ pub fn ManualProcessor(props: ManualProcessorProps) -> Element {
             }
             
             if folder_organization_successful {
+                // Registra no índice do projeto os prédios/fachadas recém-criados
+                if let Err(e) = ProjectIndex::abrir(&base_dir.join("Projects").join(&project_name_for_path)) {
+                    eprintln!("Erro ao atualizar índice do projeto: {}", e);
+                }
                 status_writer.set("Pastas organizadas com sucesso! Iniciando análise de imagens...".to_string());
 
                 match run_yolo_script_and_parse_results(&project_name_clone, status_writer, &base_dir).await {
@@ -413,24 +440,57 @@ pub async fn run_yolo_script_and_parse_results(
     status.set("Preparando para executar script de análise de imagens...".to_string());
 
     let script_path = app_rust_dir.join("..").join("Yolo").join("YOLO-Det-Py").join("rodar_modelo_prod.py");
-    let model_path = app_rust_dir.join("..").join("Yolo").join("YOLO-Det-Py").join("best.pt");
 
     if !script_path.exists() {
         return Err(format!("Script Python não encontrado em: {}", script_path.display()));
     }
+
+    let script_project_argument = format!("../app-rust/Projects/{}", project_name);
+
+    // Imagens que o projeto pediu para ficar fora da detecção (ex: duplicatas)
+    let project_dir = app_rust_dir.join("Projects").join(project_name);
+    let exclusoes_path = ProjectIndex::abrir(&project_dir)
+        .and_then(|index| index.salvar_exclusoes(&project_dir))
+        .map_err(|e| format!("Falha ao preparar lista de imagens excluídas: {}", e))?;
+    let settings = ProjectSettings::load(&project_dir)
+        .map_err(|e| format!("Falha ao ler configurações do projeto: {}", e))?;
+
+    // Modelo ativo do projeto, conferido contra o checksum registrado
+    let registro = RegistroModelos::abrir()
+        .map_err(|e| format!("Falha ao ler o registro de modelos: {}", e))?;
+    let modelo = registro.resolver(settings.modelo_ativo.as_deref())
+        .map_err(|e| e.to_string())?;
+    let model_path = modelo.caminho();
     if !model_path.exists() {
         return Err(format!("Modelo YOLO não encontrado em: {}", model_path.display()));
     }
+    registro.verificar(modelo).map_err(|e| e.to_string())?;
 
-    status.set("Executando script de análise de imagens... (Isso pode levar um tempo)".to_string());
-
-    let script_project_argument = format!("../app-rust/Projects/{}", project_name);
+    let modo = if settings.ladrilhamento.ativo {
+        format!(" em modo ladrilhado (tiles de {} px)", settings.ladrilhamento.tamanho)
+    } else {
+        String::new()
+    };
+    status.set(format!(
+        "Executando script de análise de imagens com {}{}... (Isso pode levar um tempo)",
+        modelo.descricao(),
+        modo
+    ));
 
     let output = Command::new("python3")
         .current_dir(app_rust_dir)
         .arg(&script_path)
         .arg(script_project_argument)
         .arg(&model_path)
+        .arg("--excluir")
+        .arg(&exclusoes_path)
+        .arg("--modelo-id")
+        .arg(&modelo.id)
+        .arg("--imgsz")
+        .arg(modelo.tamanho_entrada.to_string())
+        .arg("--classes")
+        .arg(modelo.taxonomia.argumento_script())
+        .args(settings.ladrilhamento.argumentos_script())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
@@ -447,6 +507,22 @@ pub async fn run_yolo_script_and_parse_results(
     
     status.set("Script executado. Processando resultados...".to_string());
 
-    serde_json::from_str::<Vec<ImageAnalysisResult>>(&stdout_str)
-        .map_err(|e| format!("Falha ao parsear JSON da saída do script: {}\nSaída: {}", e, stdout_str))
+    let resultados = serde_json::from_str::<Vec<ImageAnalysisResult>>(&stdout_str)
+        .map_err(|e| format!("Falha ao parsear JSON da saída do script: {}\nSaída: {}", e, stdout_str))?;
+
+    // Segunda etapa: classificador por imagem (ou por recorte), se o projeto pediu
+    if settings.classificacao.ativa {
+        status.set("Detecção concluída. Executando o modelo de classificação... (Isso pode levar um tempo)".to_string());
+        let classificacoes = classificacao::executar(app_rust_dir, &project_dir, &settings.classificacao, &exclusoes_path)
+            .map_err(|e| format!("{:#}", e))?;
+        status.set(format!("Classificação concluída: {} imagem(ns) classificada(s).", classificacoes.len()));
+    }
+
+    // Mede as fissuras agora para os gráficos e relatórios não precisarem segmentar ao abrir
+    status.set("Medindo fissuras...".to_string());
+    let medidas = deteccoes::medir_pendentes(&project_dir, &resultados)
+        .map_err(|e| format!("{:#}", e))?;
+    status.set(format!("Processamento concluído: {} fissura(s) medida(s).", medidas));
+
+    Ok(resultados)
 }
\ No newline at end of file
@@ -0,0 +1,358 @@
+// model_registry.rs
+// Tela do registro de modelos: importa novos modelos de detecção (.pt) com nome, versão, classes
+// e tamanho de entrada, edita a taxonomia de classes de cada modelo e escolhe qual deles o projeto
+// usa na próxima detecção.
+use dioxus::prelude::*;
+use dioxus_router::prelude::*;
+use std::path::{Path, PathBuf};
+use rfd::AsyncFileDialog;
+use crate::modelos::RegistroModelos;
+use crate::project_index;
+use crate::project_settings::ProjectSettings;
+use crate::taxonomia::Taxonomia;
+use crate::Route;
+
+// Relê as configurações do projeto e altera só o modelo ativo
+fn salvar_modelo_ativo(project_dir: &Path, id: &str) -> anyhow::Result<()> {
+    let mut settings = ProjectSettings::load(project_dir)?;
+    settings.modelo_ativo = Some(id.to_string());
+    settings.save(project_dir)
+}
+
+#[derive(Props, PartialEq, Clone)]
+pub struct ModelRegistryProps {
+    pub project_name: String,
+}
+
+#[component]
+pub fn ModelRegistry(props: ModelRegistryProps) -> Element {
+    let project_dir: PathBuf = project_index::project_dir(&props.project_name);
+    let navigator = use_navigator();
+    let mut status = use_signal(String::new);
+    let mut registro = use_signal(RegistroModelos::default);
+    // Só libera a importação depois de ler o registro, para não sobrescrevê-lo com uma lista vazia
+    let mut registro_carregado = use_signal(|| false);
+    let mut modelo_ativo = use_signal(|| None::<String>);
+
+    // Registro e configurações carregados num efeito, para os erros irem para o status
+    use_effect({
+        let project_dir = project_dir.clone();
+        move || {
+            let mut erros = Vec::new();
+            match RegistroModelos::abrir() {
+                Ok(lido) => {
+                    registro.set(lido);
+                    registro_carregado.set(true);
+                }
+                Err(e) => erros.push(format!("Erro ao ler o registro de modelos: {:#}", e)),
+            }
+            match ProjectSettings::load(&project_dir) {
+                Ok(settings) => modelo_ativo.set(settings.modelo_ativo),
+                Err(e) => erros.push(format!("Erro ao ler configurações do projeto: {:#}", e)),
+            }
+            if !erros.is_empty() {
+                status.set(erros.join(" "));
+            }
+        }
+    });
+
+    // Formulário de importação
+    let mut arquivo = use_signal(|| None::<PathBuf>);
+    let mut nome = use_signal(String::new);
+    let mut versao = use_signal(String::new);
+    let mut classes = use_signal(|| "retracao, termica".to_string());
+    let mut tamanho = use_signal(|| 640u32);
+
+    // Taxonomia em edição: id do modelo e cópia das classes
+    let mut editando = use_signal(|| None::<(String, Taxonomia)>);
+
+    let ativo = registro.read().resolver(modelo_ativo.read().as_deref()).ok().map(|m| m.id.clone());
+
+    rsx! {
+        document::Stylesheet { href: asset!("/assets/tailwind.css") }
+
+        div { class: "min-h-screen bg-gray-100 text-gray-900 font-sans",
+            document::Link {
+                href: "https://fonts.googleapis.com/icon?family=Material+Icons",
+                rel: "stylesheet"
+            }
+
+            header { class: "flex items-center justify-between bg-blue-600 text-black p-4 shadow",
+                div { class: "flex items-center gap-4",
+                    i { class: "material-icons", "memory" }
+                    h1 { class: "text-xl font-bold", "Modelos de Detecção: {props.project_name}" }
+                }
+                button {
+                    class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                    onclick: move |_| { navigator.push(Route::Process {}); },
+                    i { class: "material-icons", "arrow_back" }
+                    "Voltar"
+                }
+            }
+
+            div { class: "container mx-auto px-4 py-8 max-w-5xl space-y-6",
+                if !status.read().is_empty() {
+                    p { class: "text-center text-gray-700 py-2 px-4 border border-gray-300 bg-white rounded-md", "{status}" }
+                }
+
+                div { class: "bg-white rounded-lg shadow-md p-6",
+                    h2 { class: "text-lg font-semibold mb-4", "Modelos registrados" }
+                    if registro.read().modelos.is_empty() {
+                        p { class: "text-gray-500", "Nenhum modelo registrado." }
+                    }
+                    table { class: "w-full text-sm",
+                        tr { class: "text-left text-gray-600 border-b",
+                            th { class: "py-2", "Nome" }
+                            th { "Versão" }
+                            th { "Id" }
+                            th { "Classes" }
+                            th { "Entrada" }
+                            th { "Importado em" }
+                            th {}
+                        }
+                        for modelo in registro.read().modelos.iter().cloned() {
+                            tr { key: "{modelo.id}", class: "border-b",
+                                td { class: "py-2 font-semibold", "{modelo.nome}" }
+                                td { "{modelo.versao}" }
+                                td { class: "font-mono text-xs", title: "SHA-256: {modelo.sha256}", "{modelo.id}" }
+                                td {
+                                    for classe in modelo.taxonomia.classes.iter() {
+                                        span {
+                                            key: "{classe.chave}",
+                                            class: "mr-3 whitespace-nowrap",
+                                            title: "{classe.chave} (índice {classe.indice}, peso {classe.peso_severidade})",
+                                            span { style: "color: {classe.cor};", "⬤ " }
+                                            "{classe.rotulo}"
+                                        }
+                                    }
+                                }
+                                td { "{modelo.tamanho_entrada} px" }
+                                td { "{modelo.importado_em}" }
+                                td { class: "space-x-2 whitespace-nowrap",
+                                    button {
+                                        class: "px-3 py-1 bg-gray-200 rounded-md hover:bg-gray-300",
+                                        onclick: {
+                                            let modelo = modelo.clone();
+                                            move |_| editando.set(Some((modelo.id.clone(), modelo.taxonomia.clone())))
+                                        },
+                                        "Editar classes"
+                                    }
+                                    if ativo.as_deref() == Some(modelo.id.as_str()) {
+                                        span { class: "px-2 py-1 text-xs bg-green-100 text-green-700 rounded", "Ativo neste projeto" }
+                                    } else {
+                                        button {
+                                            class: "px-3 py-1 bg-blue-600 text-white rounded-md hover:bg-blue-700",
+                                            onclick: {
+                                                let project_dir = project_dir.clone();
+                                                let modelo = modelo.clone();
+                                                move |_| {
+                                                    match salvar_modelo_ativo(&project_dir, &modelo.id) {
+                                                        Ok(()) => {
+                                                            modelo_ativo.set(Some(modelo.id.clone()));
+                                                            status.set(format!(
+                                                                "{} será usado na próxima detecção. Os resultados atuais continuam marcados com o modelo anterior.",
+                                                                modelo.descricao()
+                                                            ));
+                                                        }
+                                                        Err(e) => status.set(format!("Erro ao salvar configurações do projeto: {}", e)),
+                                                    }
+                                                }
+                                            },
+                                            "Usar neste projeto"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if let Some((id, taxonomia)) = editando() {
+                    div { class: "bg-white rounded-lg shadow-md p-6 space-y-4",
+                        h2 { class: "text-lg font-semibold", "Classes do modelo {id}" }
+                        p { class: "text-sm text-gray-600",
+                            "Índices e chaves vêm do treino do modelo. Rótulo, cor, peso de severidade e descrição são usados nos gráficos, na revisão e nos relatórios."
+                        }
+                        for (posicao, classe) in taxonomia.classes.iter().cloned().enumerate() {
+                            div { key: "{classe.chave}", class: "grid grid-cols-12 gap-3 items-start border-b pb-3",
+                                div { class: "col-span-2 pt-2",
+                                    p { class: "font-mono text-sm", "{classe.indice} = {classe.chave}" }
+                                }
+                                label { class: "col-span-3 text-gray-700 text-sm",
+                                    "Rótulo"
+                                    input {
+                                        class: "w-full px-3 py-2 border border-gray-300 rounded-md",
+                                        r#type: "text",
+                                        value: "{classe.rotulo}",
+                                        oninput: move |e| {
+                                            if let Some((_, t)) = editando.write().as_mut() {
+                                                t.classes[posicao].rotulo = e.value();
+                                            }
+                                        }
+                                    }
+                                }
+                                label { class: "col-span-1 text-gray-700 text-sm",
+                                    "Cor"
+                                    input {
+                                        class: "w-full h-10 border border-gray-300 rounded-md",
+                                        r#type: "color",
+                                        value: "{classe.cor}",
+                                        oninput: move |e| {
+                                            if let Some((_, t)) = editando.write().as_mut() {
+                                                t.classes[posicao].cor = e.value();
+                                            }
+                                        }
+                                    }
+                                }
+                                label { class: "col-span-2 text-gray-700 text-sm",
+                                    "Peso de severidade"
+                                    input {
+                                        class: "w-full px-3 py-2 border border-gray-300 rounded-md",
+                                        r#type: "number",
+                                        min: "0",
+                                        step: "0.5",
+                                        value: "{classe.peso_severidade}",
+                                        onchange: move |e| {
+                                            if let (Ok(valor), Some((_, t))) = (e.value().parse::<f64>(), editando.write().as_mut()) {
+                                                t.classes[posicao].peso_severidade = valor;
+                                            }
+                                        }
+                                    }
+                                }
+                                label { class: "col-span-4 text-gray-700 text-sm",
+                                    "Descrição para os relatórios"
+                                    textarea {
+                                        class: "w-full px-3 py-2 border border-gray-300 rounded-md",
+                                        rows: "2",
+                                        value: "{classe.descricao}",
+                                        oninput: move |e| {
+                                            if let Some((_, t)) = editando.write().as_mut() {
+                                                t.classes[posicao].descricao = e.value();
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        div { class: "flex gap-4",
+                            button {
+                                class: "px-6 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700",
+                                onclick: move |_| {
+                                    let Some((id, taxonomia)) = editando() else { return };
+                                    let resultado = registro.write().atualizar_taxonomia(&id, taxonomia);
+                                    match resultado {
+                                        Ok(()) => {
+                                            status.set("Classes do modelo atualizadas.".to_string());
+                                            editando.set(None);
+                                        }
+                                        Err(e) => status.set(format!("Erro ao salvar classes: {:#}", e)),
+                                    }
+                                },
+                                "Salvar classes"
+                            }
+                            button {
+                                class: "px-6 py-2 bg-gray-200 rounded-md hover:bg-gray-300",
+                                onclick: move |_| editando.set(None),
+                                "Cancelar"
+                            }
+                        }
+                    }
+                }
+
+                div { class: "bg-white rounded-lg shadow-md p-6 space-y-4",
+                    h2 { class: "text-lg font-semibold", "Importar modelo" }
+                    div { class: "flex gap-4 items-center",
+                        input {
+                            class: "flex-1 px-4 py-2 border border-gray-300 rounded-md",
+                            r#type: "text",
+                            readonly: true,
+                            value: arquivo().map(|p| p.display().to_string()).unwrap_or_default(),
+                            placeholder: "Arquivo do modelo (.pt)"
+                        }
+                        button {
+                            class: "px-4 py-2 bg-gray-200 rounded-md hover:bg-gray-300 flex items-center gap-2",
+                            onclick: move |_| {
+                                spawn(async move {
+                                    if let Some(file) = AsyncFileDialog::new()
+                                        .add_filter("Modelo YOLO", &["pt"])
+                                        .pick_file()
+                                        .await
+                                    {
+                                        arquivo.set(Some(file.path().to_path_buf()));
+                                    }
+                                });
+                            },
+                            i { class: "material-icons", "upload_file" }
+                            "Selecionar arquivo"
+                        }
+                    }
+                    div { class: "grid grid-cols-2 gap-4",
+                        label { class: "text-gray-700",
+                            "Nome"
+                            input {
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md",
+                                r#type: "text",
+                                value: "{nome}",
+                                oninput: move |e| nome.set(e.value()),
+                            }
+                        }
+                        label { class: "text-gray-700",
+                            "Versão"
+                            input {
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md",
+                                r#type: "text",
+                                value: "{versao}",
+                                oninput: move |e| versao.set(e.value()),
+                            }
+                        }
+                        label { class: "text-gray-700",
+                            "Classes (na ordem dos índices, separadas por vírgula)"
+                            input {
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md",
+                                r#type: "text",
+                                value: "{classes}",
+                                oninput: move |e| classes.set(e.value()),
+                            }
+                        }
+                        label { class: "text-gray-700",
+                            "Tamanho de entrada (px)"
+                            input {
+                                class: "w-full px-3 py-2 border border-gray-300 rounded-md",
+                                r#type: "number",
+                                min: "32",
+                                step: "32",
+                                value: "{tamanho}",
+                                onchange: move |e| {
+                                    if let Ok(valor) = e.value().parse::<u32>() {
+                                        tamanho.set(valor);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    button {
+                        class: "px-6 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 disabled:opacity-50 flex items-center gap-2",
+                        disabled: arquivo().is_none() || !registro_carregado(),
+                        onclick: move |_| {
+                            let Some(origem) = arquivo() else { return };
+                            let lista_classes: Vec<String> = classes().split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect();
+                            status.set("Calculando checksum e copiando modelo...".to_string());
+                            let resultado = registro.write().importar(&origem, &nome(), &versao(), lista_classes, tamanho());
+                            match resultado {
+                                Ok(modelo) => {
+                                    status.set(format!("Modelo {} registrado.", modelo.descricao()));
+                                    arquivo.set(None);
+                                    nome.set(String::new());
+                                    versao.set(String::new());
+                                }
+                                Err(e) => status.set(format!("Erro ao importar modelo: {:#}", e)),
+                            }
+                        },
+                        i { class: "material-icons", "add" }
+                        "Importar"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// modelos.rs
+// Registro dos modelos de detecção, compartilhado entre projetos: Models/registry.json e os
+// arquivos em Models/<id>/. O id é derivado do SHA-256 do arquivo, então o mesmo best.pt tem
+// sempre o mesmo id e duas versões diferentes nunca se confundem. Cada projeto escolhe o modelo
+// ativo em project_settings.json e cada resultado de detecção guarda o id do modelo que o gerou.
+// Cada modelo traz a sua taxonomia de classes (ver taxonomia.rs).
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::io::{BufReader, Read};
+use anyhow::{Result, Context, bail, anyhow};
+use chrono::Local;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::image_processor::sanitize_filename;
+use crate::taxonomia::Taxonomia;
+
+pub const REGISTRY_FILE_NAME: &str = "registry.json";
+
+pub fn models_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Models")
+}
+
+// Modelo que acompanha o repositório; é registrado automaticamente se o registro estiver vazio
+fn modelo_legado() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join("Yolo").join("YOLO-Det-Py").join("best.pt")
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ModeloRegistrado {
+    pub id: String,
+    pub nome: String,
+    pub versao: String,
+    pub arquivo: String, // Nome do arquivo dentro de Models/<id>/
+    #[serde(default)]
+    pub taxonomia: Taxonomia,
+    // Registros antigos guardavam só os nomes das classes; convertidos em taxonomia ao carregar
+    #[serde(default, rename = "classes", skip_serializing)]
+    classes_legado: Vec<String>,
+    pub tamanho_entrada: u32, // imgsz usado na inferência
+    pub sha256: String,
+    pub importado_em: String,
+}
+
+impl ModeloRegistrado {
+    pub fn caminho(&self) -> PathBuf {
+        models_dir().join(&self.id).join(&self.arquivo)
+    }
+
+    // Ex: "Fissuras v2 (3fa9c01b22de)"
+    pub fn descricao(&self) -> String {
+        format!("{} v{} ({})", self.nome, self.versao, self.id)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RegistroModelos {
+    #[serde(default)]
+    pub modelos: Vec<ModeloRegistrado>,
+}
+
+pub fn checksum(path: &Path) -> Result<String> {
+    let arquivo = fs::File::open(path)
+        .with_context(|| format!("Falha ao abrir {}", path.display()))?;
+    let mut leitor = BufReader::new(arquivo);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let lidos = leitor.read(&mut buffer)
+            .with_context(|| format!("Falha ao ler {}", path.display()))?;
+        if lidos == 0 {
+            break;
+        }
+        hasher.update(&buffer[..lidos]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+impl RegistroModelos {
+    pub fn load() -> Result<Self> {
+        let path = models_dir().join(REGISTRY_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Falha ao ler {}", path.display()))?;
+        let mut registro: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Falha ao parsear {}", path.display()))?;
+        for modelo in registro.modelos.iter_mut().filter(|m| m.taxonomia.classes.is_empty()) {
+            modelo.taxonomia = Taxonomia::sugerida(&modelo.classes_legado);
+        }
+        Ok(registro)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = models_dir();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Falha ao criar {}", dir.display()))?;
+        let path = dir.join(REGISTRY_FILE_NAME);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Falha ao salvar {}", path.display()))
+    }
+
+    // Carrega o registro; na primeira vez, registra o best.pt que acompanha o repositório
+    // com as classes e o imgsz com que ele foi treinado
+    pub fn abrir() -> Result<Self> {
+        let mut registro = Self::load()?;
+        let legado = modelo_legado();
+        if registro.modelos.is_empty() && legado.exists() {
+            registro.importar(&legado, "Modelo padrão", "1", vec!["retracao".to_string(), "termica".to_string()], 224)?;
+        }
+        Ok(registro)
+    }
+
+    pub fn modelo(&self, id: &str) -> Option<&ModeloRegistrado> {
+        self.modelos.iter().find(|m| m.id == id)
+    }
+
+    // Modelo a usar num projeto: o escolhido nas configurações ou, sem escolha, o primeiro registrado
+    pub fn resolver(&self, modelo_ativo: Option<&str>) -> Result<&ModeloRegistrado> {
+        match modelo_ativo {
+            Some(id) => self.modelo(id)
+                .ok_or_else(|| anyhow!("O modelo ativo do projeto ({}) não está no registro de modelos", id)),
+            None => self.modelos.first()
+                .ok_or_else(|| anyhow!("Nenhum modelo de detecção registrado. Importe um modelo (.pt) antes de rodar a detecção.")),
+        }
+    }
+
+    // Copia o arquivo para Models/<id>/ e registra. O mesmo arquivo não é registrado duas vezes.
+    pub fn importar(&mut self, origem: &Path, nome: &str, versao: &str, classes: Vec<String>, tamanho_entrada: u32) -> Result<ModeloRegistrado> {
+        if nome.trim().is_empty() || versao.trim().is_empty() {
+            bail!("Informe nome e versão do modelo");
+        }
+        if classes.is_empty() {
+            bail!("Informe as classes do modelo, na ordem dos índices");
+        }
+        let sha256 = checksum(origem)?;
+        if let Some(existente) = self.modelos.iter().find(|m| m.sha256 == sha256) {
+            bail!("Este arquivo já está registrado como {}", existente.descricao());
+        }
+
+        let id = sha256[..12].to_string();
+        let arquivo = sanitize_filename(&origem.file_name().unwrap_or_default().to_string_lossy());
+        let destino_dir = models_dir().join(&id);
+        fs::create_dir_all(&destino_dir)
+            .with_context(|| format!("Falha ao criar {}", destino_dir.display()))?;
+        fs::copy(origem, destino_dir.join(&arquivo))
+            .with_context(|| format!("Falha ao copiar {} para {}", origem.display(), destino_dir.display()))?;
+
+        let modelo = ModeloRegistrado {
+            id,
+            nome: nome.trim().to_string(),
+            versao: versao.trim().to_string(),
+            arquivo,
+            taxonomia: Taxonomia::sugerida(&classes.iter().map(|c| c.trim()).filter(|c| !c.is_empty()).collect::<Vec<_>>()),
+            classes_legado: Vec::new(),
+            tamanho_entrada: tamanho_entrada.max(32),
+            sha256,
+            importado_em: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        };
+        self.modelos.push(modelo.clone());
+        self.save()?;
+        Ok(modelo)
+    }
+
+    // Atualiza rótulos, cores, pesos e descrições das classes. Índices e chaves vêm do modelo
+    // treinado e não podem mudar aqui.
+    pub fn atualizar_taxonomia(&mut self, id: &str, taxonomia: Taxonomia) -> Result<()> {
+        let modelo = self.modelos.iter_mut().find(|m| m.id == id)
+            .ok_or_else(|| anyhow!("Modelo {} não está no registro", id))?;
+        let identificacao = |t: &Taxonomia| t.classes.iter().map(|c| (c.indice, c.chave.clone())).collect::<Vec<_>>();
+        if identificacao(&taxonomia) != identificacao(&modelo.taxonomia) {
+            bail!("As classes do modelo {} não podem ser adicionadas, removidas ou renomeadas", modelo.descricao());
+        }
+        if let Some(classe) = taxonomia.classes.iter().find(|c| c.rotulo.trim().is_empty()) {
+            bail!("Informe o rótulo da classe {}", classe.chave);
+        }
+        if let Some(classe) = taxonomia.classes.iter().find(|c| !(c.peso_severidade.is_finite() && c.peso_severidade >= 0.0)) {
+            bail!("O peso de severidade da classe {} deve ser um número maior ou igual a zero", classe.chave);
+        }
+        modelo.taxonomia = taxonomia;
+        self.save()
+    }
+
+    // Confere se o arquivo registrado não foi trocado depois da importação
+    pub fn verificar(&self, modelo: &ModeloRegistrado) -> Result<()> {
+        let atual = checksum(&modelo.caminho())?;
+        if atual != modelo.sha256 {
+            bail!("O arquivo do modelo {} foi alterado depois de registrado (checksum diferente)", modelo.descricao());
+        }
+        Ok(())
+    }
+}
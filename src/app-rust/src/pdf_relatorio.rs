@@ -0,0 +1,383 @@
+// pdf_relatorio.rs
+// Exportação do relatório para PDF sem programas externos (pandoc/LaTeX). Diagrama o documento de
+// documento_relatorio.rs em páginas A4 com as fontes embutidas do PDF (Helvetica, codificação
+// Windows-1252, que cobre os acentos do português): títulos, parágrafos, listas, tabelas com
+// bordas e imagens anotadas com as caixas detectadas. Cada página tem cabeçalho com empresa e
+// projeto e rodapé com a numeração "Página N de M".
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use anyhow::{Result, Context, anyhow};
+use image::codecs::jpeg::JpegEncoder;
+use printpdf::{
+    BuiltinFont, Color, ColorBits, ColorSpace, Image, ImageFilter, ImageXObject, IndirectFontRef, Line, Mm,
+    PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Px, Rgb,
+};
+use crate::documento_relatorio::{self, Alinhamento, Bloco, CaixaAnotada, DocumentoRelatorio, Trecho};
+
+const LARGURA_PAGINA: f64 = 210.0;
+const ALTURA_PAGINA: f64 = 297.0;
+const MARGEM_LATERAL: f64 = 20.0;
+const MARGEM_SUPERIOR: f64 = 25.0;
+const MARGEM_INFERIOR: f64 = 20.0;
+const LARGURA_UTIL: f64 = LARGURA_PAGINA - 2.0 * MARGEM_LATERAL;
+const ALTURA_MAXIMA_IMAGEM: f64 = 110.0;
+const QUALIDADE_JPEG: u8 = 85;
+const TAMANHO_TEXTO: f64 = 10.0;
+const TAMANHO_TABELA: f64 = 8.5;
+const TAMANHO_MARGENS: f64 = 8.0;
+const MM_POR_PT: f64 = 25.4 / 72.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Estilo {
+    Normal,
+    Negrito,
+    Italico,
+}
+
+// Largura aproximada de um caractere da Helvetica em frações do tamanho da fonte. As fontes
+// embutidas não trazem métricas no printpdf; a aproximação só precisa não estourar a margem.
+fn largura_caractere(c: char) -> f64 {
+    match c {
+        'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '\'' | '|' | '!' | 'í' | 'ì' => 0.24,
+        ' ' | 'f' | 't' | 'r' | 'I' | '(' | ')' | '[' | ']' | '/' | '-' => 0.33,
+        'm' | 'w' | 'M' | 'W' | '@' | '%' => 0.85,
+        c if c.is_uppercase() => 0.68,
+        c if c.is_ascii_digit() => 0.56,
+        _ => 0.53,
+    }
+}
+
+fn largura_texto(texto: &str, tamanho: f64, estilo: Estilo) -> f64 {
+    let fator = if estilo == Estilo::Negrito { 1.06 } else { 1.0 };
+    texto.chars().map(largura_caractere).sum::<f64>() * tamanho * MM_POR_PT * fator
+}
+
+fn altura_linha(tamanho: f64) -> f64 {
+    tamanho * MM_POR_PT * 1.35
+}
+
+// Quebra texto simples em linhas que cabem na largura
+fn quebrar(texto: &str, largura: f64, tamanho: f64, estilo: Estilo) -> Vec<String> {
+    let mut linhas = Vec::new();
+    for paragrafo in texto.split('\n') {
+        let mut linha = String::new();
+        for palavra in paragrafo.split_whitespace() {
+            let candidata = if linha.is_empty() { palavra.to_string() } else { format!("{} {}", linha, palavra) };
+            if !linha.is_empty() && largura_texto(&candidata, tamanho, estilo) > largura {
+                linhas.push(std::mem::replace(&mut linha, palavra.to_string()));
+            } else {
+                linha = candidata;
+            }
+        }
+        linhas.push(linha);
+    }
+    linhas
+}
+
+struct Fontes {
+    normal: IndirectFontRef,
+    negrito: IndirectFontRef,
+    italico: IndirectFontRef,
+}
+
+impl Fontes {
+    fn de(&self, estilo: Estilo) -> &IndirectFontRef {
+        match estilo {
+            Estilo::Normal => &self.normal,
+            Estilo::Negrito => &self.negrito,
+            Estilo::Italico => &self.italico,
+        }
+    }
+}
+
+struct Paginador<'a> {
+    documento: &'a DocumentoRelatorio,
+    pdf: PdfDocumentReference,
+    fontes: Fontes,
+    camadas: Vec<PdfLayerReference>,
+    y: f64, // Posição atual, em mm a partir da base da página
+}
+
+impl<'a> Paginador<'a> {
+    fn novo(documento: &'a DocumentoRelatorio) -> Result<Self> {
+        let (pdf, pagina, camada) = PdfDocument::new(documento.titulo.as_str(), Mm(LARGURA_PAGINA), Mm(ALTURA_PAGINA), "Relatório");
+        let fonte = |f| pdf.add_builtin_font(f).map_err(|e| anyhow!("Falha ao carregar fonte do PDF: {}", e));
+        let fontes = Fontes {
+            normal: fonte(BuiltinFont::Helvetica)?,
+            negrito: fonte(BuiltinFont::HelveticaBold)?,
+            italico: fonte(BuiltinFont::HelveticaOblique)?,
+        };
+        let primeira = pdf.get_page(pagina).get_layer(camada);
+        let mut paginador = Self { documento, pdf, fontes, camadas: Vec::new(), y: 0.0 };
+        paginador.iniciar_pagina(primeira);
+        Ok(paginador)
+    }
+
+    fn camada(&self) -> &PdfLayerReference {
+        self.camadas.last().expect("paginador sempre tem uma página")
+    }
+
+    fn iniciar_pagina(&mut self, camada: PdfLayerReference) {
+        camada.set_fill_color(cinza(0.4));
+        camada.use_text(self.documento.cabecalho(), TAMANHO_MARGENS, Mm(MARGEM_LATERAL), Mm(ALTURA_PAGINA - 12.0), &self.fontes.normal);
+        camada.set_outline_color(cinza(0.7));
+        camada.set_outline_thickness(0.5);
+        camada.add_shape(segmento(MARGEM_LATERAL, ALTURA_PAGINA - 14.0, LARGURA_PAGINA - MARGEM_LATERAL, ALTURA_PAGINA - 14.0));
+        camada.set_fill_color(cinza(0.0));
+        self.camadas.push(camada);
+        self.y = ALTURA_PAGINA - MARGEM_SUPERIOR;
+    }
+
+    fn nova_pagina(&mut self) {
+        let (pagina, camada) = self.pdf.add_page(Mm(LARGURA_PAGINA), Mm(ALTURA_PAGINA), "Relatório");
+        let camada = self.pdf.get_page(pagina).get_layer(camada);
+        self.iniciar_pagina(camada);
+    }
+
+    // Quebra a página se o próximo elemento não couber
+    fn garantir(&mut self, altura: f64) {
+        if self.y - altura < MARGEM_INFERIOR {
+            self.nova_pagina();
+        }
+    }
+
+    fn texto(&self, texto: &str, tamanho: f64, x: f64, y: f64, estilo: Estilo) {
+        self.camada().use_text(texto, tamanho, Mm(x), Mm(y), self.fontes.de(estilo));
+    }
+
+    fn titulo(&mut self, nivel: u8, texto: &str) {
+        let tamanho = match nivel {
+            1 => 18.0,
+            2 => 14.0,
+            3 => 12.0,
+            _ => 11.0,
+        };
+        let linhas = quebrar(texto, LARGURA_UTIL, tamanho, Estilo::Negrito);
+        // Título não fica sozinho no fim da página
+        self.garantir(altura_linha(tamanho) * linhas.len() as f64 + 3.0 + altura_linha(TAMANHO_TEXTO) * 2.0);
+        self.y -= 3.0;
+        for linha in linhas {
+            self.y -= altura_linha(tamanho);
+            self.texto(&linha, tamanho, MARGEM_LATERAL, self.y, Estilo::Negrito);
+        }
+        self.y -= 1.5;
+    }
+
+    // Parágrafo ou item de lista com trechos em negrito/itálico, quebrado palavra a palavra
+    fn trechos(&mut self, trechos: &[Trecho], recuo: f64, marcador: Option<&str>) {
+        let largura = LARGURA_UTIL - recuo;
+        let espaco = largura_texto(" ", TAMANHO_TEXTO, Estilo::Normal);
+        let mut linhas: Vec<Vec<(String, Estilo, f64)>> = vec![Vec::new()];
+        let mut ocupado = 0.0;
+        for trecho in trechos {
+            let estilo = if trecho.negrito { Estilo::Negrito } else if trecho.italico { Estilo::Italico } else { Estilo::Normal };
+            for (i, parte) in trecho.texto.split('\n').enumerate() {
+                if i > 0 {
+                    linhas.push(Vec::new());
+                    ocupado = 0.0;
+                }
+                for palavra in parte.split_whitespace() {
+                    let tamanho = largura_texto(palavra, TAMANHO_TEXTO, estilo);
+                    let linha_atual = linhas.last_mut().expect("há sempre uma linha");
+                    if !linha_atual.is_empty() && ocupado + espaco + tamanho > largura {
+                        linhas.push(Vec::new());
+                        ocupado = 0.0;
+                    }
+                    let linha_atual = linhas.last_mut().expect("há sempre uma linha");
+                    let x = if linha_atual.is_empty() { 0.0 } else { ocupado + espaco };
+                    linha_atual.push((palavra.to_string(), estilo, x));
+                    ocupado = x + tamanho;
+                }
+            }
+        }
+        linhas.retain(|linha| !linha.is_empty());
+        for (i, linha) in linhas.iter().enumerate() {
+            self.garantir(altura_linha(TAMANHO_TEXTO));
+            self.y -= altura_linha(TAMANHO_TEXTO);
+            if let (0, Some(marcador)) = (i, marcador) {
+                self.texto(marcador, TAMANHO_TEXTO, MARGEM_LATERAL + recuo - 4.0, self.y, Estilo::Normal);
+            }
+            for (palavra, estilo, x) in linha {
+                self.texto(palavra, TAMANHO_TEXTO, MARGEM_LATERAL + recuo + x, self.y, *estilo);
+            }
+        }
+        self.y -= 1.5;
+    }
+
+    fn tabela(&mut self, cabecalho: &[String], linhas: &[Vec<String>], alinhamentos: &[Alinhamento]) {
+        let colunas = cabecalho.len().max(1);
+        // Largura das colunas proporcional ao maior conteúdo, com um mínimo para não esmagar nenhuma
+        let conteudo: Vec<f64> = (0..colunas)
+            .map(|c| {
+                std::iter::once(cabecalho)
+                    .chain(linhas.iter().map(|l| l.as_slice()))
+                    .filter_map(|l| l.get(c))
+                    .map(|t| largura_texto(t, TAMANHO_TABELA, Estilo::Normal).min(LARGURA_UTIL / 2.0))
+                    .fold(10.0, f64::max)
+            })
+            .collect();
+        let total: f64 = conteudo.iter().sum();
+        let larguras: Vec<f64> = conteudo.iter().map(|c| c / total * LARGURA_UTIL).collect();
+        let respiro = 1.5;
+
+        self.y -= 2.0;
+        self.linha_tabela(cabecalho, &larguras, alinhamentos, respiro, true);
+        for linha in linhas {
+            self.linha_tabela(linha, &larguras, alinhamentos, respiro, false);
+        }
+        self.y -= 3.0;
+    }
+
+    fn linha_tabela(&mut self, celulas: &[String], larguras: &[f64], alinhamentos: &[Alinhamento], respiro: f64, cabecalho: bool) {
+        let estilo = if cabecalho { Estilo::Negrito } else { Estilo::Normal };
+        let quebradas: Vec<Vec<String>> = larguras
+            .iter()
+            .enumerate()
+            .map(|(c, largura)| quebrar(celulas.get(c).map_or("", |t| t.as_str()), largura - 2.0 * respiro, TAMANHO_TABELA, estilo))
+            .collect();
+        let maximo = quebradas.iter().map(|l| l.len()).max().unwrap_or(1).max(1);
+        let altura = maximo as f64 * altura_linha(TAMANHO_TABELA) + 2.0 * respiro;
+        self.garantir(altura);
+
+        let topo = self.y;
+        let mut x = MARGEM_LATERAL;
+        let camada = self.camada().clone();
+        if cabecalho {
+            camada.set_fill_color(cinza(0.9));
+            camada.add_shape(retangulo(MARGEM_LATERAL, topo - altura, LARGURA_UTIL, altura, true));
+            camada.set_fill_color(cinza(0.0));
+        }
+        camada.set_outline_color(cinza(0.6));
+        camada.set_outline_thickness(0.4);
+        for (c, (largura, linhas)) in larguras.iter().zip(&quebradas).enumerate() {
+            camada.add_shape(retangulo(x, topo - altura, *largura, altura, false));
+            let alinhamento = alinhamentos.get(c).copied().unwrap_or_default();
+            for (i, linha) in linhas.iter().enumerate() {
+                let y = topo - respiro - altura_linha(TAMANHO_TABELA) * (i as f64 + 1.0) + 0.8;
+                let inicio = match alinhamento {
+                    Alinhamento::Esquerda => x + respiro,
+                    Alinhamento::Direita => x + largura - respiro - largura_texto(linha, TAMANHO_TABELA, estilo),
+                };
+                self.texto(linha, TAMANHO_TABELA, inicio, y, estilo);
+            }
+            x += largura;
+        }
+        self.y -= altura;
+    }
+
+    fn imagem(&mut self, caminho: &str, legenda: &str, caixas: &[CaixaAnotada]) {
+        let imagem = match documento_relatorio::imagem_anotada(caminho, caixas) {
+            Ok(imagem) => imagem,
+            Err(e) => {
+                let aviso = Trecho { texto: format!("[Imagem indisponível: {:#}]", e), negrito: false, italico: true };
+                self.trechos(&[aviso], 0.0, None);
+                return;
+            }
+        };
+        let (largura_px, altura_px) = imagem.dimensions();
+        // Embutida como JPEG (filtro DCT do PDF): os pixels crus deixariam o arquivo enorme
+        let mut jpeg = Vec::new();
+        if let Err(e) = JpegEncoder::new_with_quality(&mut jpeg, QUALIDADE_JPEG).encode_image(&imagem) {
+            let aviso = Trecho { texto: format!("[Falha ao comprimir a imagem {}: {}]", caminho, e), negrito: false, italico: true };
+            self.trechos(&[aviso], 0.0, None);
+            return;
+        }
+        let largura = LARGURA_UTIL.min(ALTURA_MAXIMA_IMAGEM * largura_px as f64 / altura_px as f64);
+        let altura = largura * altura_px as f64 / largura_px as f64;
+        self.garantir(altura + 4.0 + altura_linha(TAMANHO_TABELA) * 2.0);
+
+        self.y -= altura + 2.0;
+        let x = MARGEM_LATERAL + (LARGURA_UTIL - largura) / 2.0;
+        let objeto = ImageXObject::new(
+            Px(largura_px as usize),
+            Px(altura_px as usize),
+            ColorSpace::Rgb,
+            ColorBits::Bit8,
+            true,
+            Some(ImageFilter::DCT),
+            None,
+            jpeg,
+        );
+        // O DPI define o tamanho impresso: escolhido para a imagem ocupar exatamente a largura calculada
+        let dpi = largura_px as f64 * 25.4 / largura;
+        Image::from(objeto).add_to_layer(self.camada().clone(), Some(Mm(x)), Some(Mm(self.y)), None, None, None, Some(dpi));
+
+        for linha in quebrar(legenda, LARGURA_UTIL, TAMANHO_TABELA, Estilo::Italico) {
+            self.y -= altura_linha(TAMANHO_TABELA);
+            let x = MARGEM_LATERAL + (LARGURA_UTIL - largura_texto(&linha, TAMANHO_TABELA, Estilo::Italico)).max(0.0) / 2.0;
+            self.texto(&linha, TAMANHO_TABELA, x, self.y, Estilo::Italico);
+        }
+        self.y -= 3.0;
+    }
+
+    fn separador(&mut self) {
+        self.garantir(6.0);
+        self.y -= 3.0;
+        let camada = self.camada().clone();
+        camada.set_outline_color(cinza(0.8));
+        camada.set_outline_thickness(0.5);
+        camada.add_shape(segmento(MARGEM_LATERAL, self.y, LARGURA_PAGINA - MARGEM_LATERAL, self.y));
+        self.y -= 3.0;
+    }
+
+    // Rodapé escrito no fim, quando o total de páginas é conhecido
+    fn concluir(self) -> PdfDocumentReference {
+        let total = self.camadas.len();
+        for (i, camada) in self.camadas.iter().enumerate() {
+            let texto = format!("Página {} de {}", i + 1, total);
+            let x = LARGURA_PAGINA - MARGEM_LATERAL - largura_texto(&texto, TAMANHO_MARGENS, Estilo::Normal);
+            camada.set_fill_color(cinza(0.4));
+            camada.use_text(self.documento.titulo.as_str(), TAMANHO_MARGENS, Mm(MARGEM_LATERAL), Mm(10.0), &self.fontes.normal);
+            camada.use_text(texto, TAMANHO_MARGENS, Mm(x), Mm(10.0), &self.fontes.normal);
+        }
+        self.pdf
+    }
+}
+
+fn cinza(nivel: f64) -> Color {
+    Color::Rgb(Rgb::new(nivel, nivel, nivel, None))
+}
+
+fn segmento(x1: f64, y1: f64, x2: f64, y2: f64) -> Line {
+    Line {
+        points: vec![(Point::new(Mm(x1), Mm(y1)), false), (Point::new(Mm(x2), Mm(y2)), false)],
+        is_closed: false,
+        has_fill: false,
+        has_stroke: true,
+        is_clipping_path: false,
+    }
+}
+
+fn retangulo(x: f64, y: f64, largura: f64, altura: f64, preenchido: bool) -> Line {
+    Line {
+        points: vec![
+            (Point::new(Mm(x), Mm(y)), false),
+            (Point::new(Mm(x + largura), Mm(y)), false),
+            (Point::new(Mm(x + largura), Mm(y + altura)), false),
+            (Point::new(Mm(x), Mm(y + altura)), false),
+        ],
+        is_closed: true,
+        has_fill: preenchido,
+        has_stroke: !preenchido,
+        is_clipping_path: false,
+    }
+}
+
+pub fn gerar(documento: &DocumentoRelatorio, destino: &Path) -> Result<()> {
+    let mut paginador = Paginador::novo(documento)?;
+    for bloco in &documento.blocos {
+        match bloco {
+            Bloco::Titulo { nivel, texto } => paginador.titulo(*nivel, texto),
+            Bloco::Paragrafo(trechos) => paginador.trechos(trechos, 0.0, None),
+            Bloco::Item(trechos) => paginador.trechos(trechos, 6.0, Some("-")),
+            Bloco::Tabela { cabecalho, linhas, alinhamentos } => paginador.tabela(cabecalho, linhas, alinhamentos),
+            Bloco::Imagem { caminho, legenda, caixas } => paginador.imagem(caminho, legenda, caixas),
+            Bloco::Separador => paginador.separador(),
+        }
+    }
+    let pdf = paginador.concluir();
+    let arquivo = File::create(destino).with_context(|| format!("Falha ao criar {}", destino.display()))?;
+    pdf.save(&mut BufWriter::new(arquivo))
+        .map_err(|e| anyhow!("Falha ao salvar o PDF em {}: {}", destino.display(), e))
+}
@@ -0,0 +1,373 @@
+// project_index.rs
+// Índice do projeto salvo em Projects/<projeto>/project_index.json.
+// Guarda o modelo prédio/fachada/imagem produzido na ingestão; a estrutura de pastas em images/
+// (images/<id do prédio>/<pasta da fachada>/<arquivo>) é uma projeção deste índice.
+use std::path::{Path, PathBuf};
+use std::fs;
+use anyhow::{Result, Context, anyhow};
+use serde::{Serialize, Deserialize};
+use walkdir::WalkDir;
+use crate::image_processor::{Predio, Fachada, ImageMetadata, ler_metadados, analisar_pixels};
+use crate::manual_processor::ImageAnalysisResult;
+use crate::duplicatas;
+use crate::revisao::RevisaoProjeto;
+use crate::classificacao;
+use crate::geometria;
+
+pub const INDEX_FILE_NAME: &str = "project_index.json";
+// Lista (um caminho por linha) das imagens que o script de detecção deve ignorar
+pub const EXCLUSOES_FILE_NAME: &str = "detection_exclusions.txt";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ProjectIndex {
+    pub predios: Vec<Predio>,
+    // Quando ativo, fotos marcadas como duplicata ficam fora da detecção e das estatísticas
+    #[serde(default)]
+    pub excluir_duplicatas: bool,
+    // Quando ativo, fotos reprovadas na verificação de qualidade ficam fora da detecção e dos relatórios
+    #[serde(default)]
+    pub excluir_baixa_qualidade: bool,
+}
+
+pub fn project_dir(project_name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Projects").join(project_name)
+}
+
+// Nome de exibição inicial a partir do id (ex: "Predio-3" -> "Prédio 3")
+pub fn nome_padrao(id: &str) -> String {
+    match id.strip_prefix("Predio-") {
+        Some(numero) => format!("Prédio {}", numero),
+        None => id.replace('_', " "),
+    }
+}
+
+// Nome de exibição inicial de uma fachada a partir da pasta (ex: "fachada-Norte" -> "Norte")
+pub fn nome_fachada_padrao(pasta: &str) -> String {
+    pasta.strip_prefix("fachada-").unwrap_or(pasta).replace('_', " ")
+}
+
+// Parte do caminho depois da pasta "images" (ex: "Predio-1/fachada-Norte/foto.jpg")
+pub fn caminho_relativo_imagens(path: &Path) -> Option<PathBuf> {
+    let componentes: Vec<_> = path.components().collect();
+    let idx = componentes.iter().rposition(|c| c.as_os_str() == "images")?;
+    Some(componentes[idx + 1..].iter().collect())
+}
+
+fn is_image_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_lowercase().as_str(), "jpg" | "jpeg" | "png"))
+}
+
+// Onde uma imagem do índice deve estar em disco
+pub fn caminho_projetado(images_dir: &Path, predio_id: &str, pasta_fachada: &str, file_name: &str) -> PathBuf {
+    images_dir.join(predio_id).join(pasta_fachada).join(file_name)
+}
+
+impl ProjectIndex {
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(INDEX_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Falha ao ler {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Falha ao parsear {}", path.display()))
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(INDEX_FILE_NAME);
+        fs::create_dir_all(project_dir)
+            .with_context(|| format!("Falha ao criar {}", project_dir.display()))?;
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Falha ao salvar {}", path.display()))
+    }
+
+    // Carrega o índice ao abrir um projeto: recalcula os caminhos das imagens e adota arquivos
+    // que existem nas pastas mas não estão no índice (projetos antigos ou organizados manualmente)
+    pub fn abrir(project_dir: &Path) -> Result<Self> {
+        let mut index = Self::load(project_dir)?;
+        let images_dir = project_dir.join("images");
+        index.projetar_caminhos(&images_dir);
+        if index.adotar_pastas(&images_dir) {
+            index.save(project_dir)?;
+        }
+        Ok(index)
+    }
+
+    pub fn predio(&self, id: &str) -> Option<&Predio> {
+        self.predios.iter().find(|p| p.id == id)
+    }
+
+    pub fn predio_mut(&mut self, id: &str) -> Option<&mut Predio> {
+        self.predios.iter_mut().find(|p| p.id == id)
+    }
+
+    // Nome de exibição do prédio, ou o nome padrão derivado do id
+    pub fn nome_exibicao(&self, id: &str) -> String {
+        self.predio(id).map(|p| p.nome.clone()).unwrap_or_else(|| nome_padrao(id))
+    }
+
+    // Procura um prédio pelo id ou pelo nome de exibição
+    pub fn buscar(&self, id_ou_nome: &str) -> Option<&Predio> {
+        self.predio(id_ou_nome).or_else(|| self.predios.iter().find(|p| p.nome == id_ou_nome))
+    }
+
+    // Próximo número livre para ids no formato "Predio-N"
+    pub fn proximo_numero_predio(&self) -> usize {
+        self.predios
+            .iter()
+            .filter_map(|p| p.id.strip_prefix("Predio-").and_then(|n| n.parse::<usize>().ok()))
+            .max()
+            .map_or(1, |n| n + 1)
+    }
+
+    // Encontra o prédio e a fachada (pasta) de uma imagem a partir do seu caminho
+    pub fn localizar(&self, image_path: &Path) -> Option<(&Predio, &str, &Fachada)> {
+        let rel = caminho_relativo_imagens(image_path)?;
+        let partes: Vec<String> = rel.iter().map(|c| c.to_string_lossy().into_owned()).collect();
+        let [predio_id, pasta, file_name] = partes.as_slice() else { return None };
+        let predio = self.predio(predio_id)?;
+        let (pasta, fachada) = predio.fachadas.get_key_value(pasta.as_str())?;
+        fachada.imagens.iter().find(|im| im.file_name == *file_name)?;
+        Some((predio, pasta.as_str(), fachada))
+    }
+
+    pub fn imagem(&self, image_path: &Path) -> Option<&ImageMetadata> {
+        let file_name = image_path.file_name()?.to_str()?;
+        let (_, _, fachada) = self.localizar(image_path)?;
+        fachada.imagens.iter().find(|im| im.file_name == file_name)
+    }
+
+    // Imagem que não deve entrar na detecção nem nas estatísticas
+    pub fn excluida(&self, image_path: &Path) -> bool {
+        self.imagem(image_path).is_some_and(|im| {
+            (self.excluir_duplicatas && im.duplicata_de.is_some())
+                || (self.excluir_baixa_qualidade && im.qualidade.as_ref().is_some_and(|q| !q.aprovada()))
+        })
+    }
+
+    pub fn caminhos_excluidos(&self) -> Vec<PathBuf> {
+        self.predios
+            .iter()
+            .flat_map(|p| p.imagens())
+            .filter(|im| self.excluida(&im.path))
+            .map(|im| im.path.clone())
+            .collect()
+    }
+
+    pub fn salvar_exclusoes(&self, project_dir: &Path) -> Result<PathBuf> {
+        let path = project_dir.join(EXCLUSOES_FILE_NAME);
+        let linhas: Vec<String> = self.caminhos_excluidos().iter().map(|p| p.display().to_string()).collect();
+        fs::write(&path, linhas.join("\n"))
+            .with_context(|| format!("Falha ao salvar {}", path.display()))?;
+        Ok(path)
+    }
+
+    // Refaz a marcação de duplicatas em todas as fachadas, calculando os hashes que faltam
+    pub fn marcar_duplicatas(&mut self) -> usize {
+        let mut marcadas = 0;
+        for fachada in self.predios.iter_mut().flat_map(|p| p.fachadas.values_mut()) {
+            for imagem in fachada.imagens.iter_mut().filter(|im| im.phash.is_none()) {
+                imagem.phash = duplicatas::calcular_hash(&imagem.path).ok();
+            }
+            marcadas += duplicatas::marcar_duplicatas(fachada);
+        }
+        marcadas
+    }
+
+    // Refaz a análise de qualidade das imagens que ainda não têm uma. Retorna quantas estão reprovadas.
+    pub fn analisar_qualidade(&mut self) -> usize {
+        for imagem in self.predios.iter_mut().flat_map(|p| p.fachadas.values_mut()).flat_map(|f| f.imagens.iter_mut()) {
+            if imagem.qualidade.is_none() {
+                let (phash, analise) = analisar_pixels(&imagem.path);
+                imagem.phash = imagem.phash.or(phash);
+                imagem.qualidade = analise;
+            }
+        }
+        self.predios
+            .iter()
+            .flat_map(|p| p.imagens())
+            .filter(|im| im.qualidade.as_ref().is_some_and(|q| !q.aprovada()))
+            .count()
+    }
+
+    pub fn normalizar_duplicatas(&mut self) {
+        for fachada in self.predios.iter_mut().flat_map(|p| p.fachadas.values_mut()) {
+            duplicatas::normalizar(fachada);
+        }
+    }
+
+    // Atualiza `path` de cada imagem para onde ela deve estar de acordo com o índice
+    pub fn projetar_caminhos(&mut self, images_dir: &Path) {
+        for predio in self.predios.iter_mut() {
+            for (pasta, fachada) in predio.fachadas.iter_mut() {
+                for imagem in fachada.imagens.iter_mut() {
+                    imagem.path = caminho_projetado(images_dir, &predio.id, pasta, &imagem.file_name);
+                }
+            }
+        }
+    }
+
+    // Sincroniza o índice com os arquivos em disco. Retorna true se algo mudou.
+    fn adotar_pastas(&mut self, images_dir: &Path) -> bool {
+        let mut mudou = false;
+
+        // Remove do índice imagens cujo arquivo não existe mais
+        for predio in self.predios.iter_mut() {
+            for fachada in predio.fachadas.values_mut() {
+                let antes = fachada.imagens.len();
+                fachada.imagens.retain(|im| im.path.exists());
+                mudou |= fachada.imagens.len() != antes;
+            }
+        }
+
+        // Adota arquivos em images/<prédio>/<fachada>/ que ainda não estão no índice
+        for entry in WalkDir::new(images_dir).min_depth(3).max_depth(3).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.is_file() || !is_image_file(path) || self.localizar(path).is_some() {
+                continue;
+            }
+            let Some(rel) = caminho_relativo_imagens(path) else { continue };
+            let partes: Vec<String> = rel.iter().map(|c| c.to_string_lossy().into_owned()).collect();
+            let [predio_id, pasta, _] = partes.as_slice() else { continue };
+
+            if self.predio(predio_id).is_none() {
+                self.predios.push(Predio {
+                    id: predio_id.clone(),
+                    nome: nome_padrao(predio_id),
+                    centroide: None,
+                    endereco: None,
+                    fachadas: Default::default(),
+                    todas_imagens_no_predio: Vec::new(),
+                });
+            }
+            let predio = self.predio_mut(predio_id).expect("prédio acabou de ser inserido");
+            predio.fachadas
+                .entry(pasta.clone())
+                .or_insert_with(|| Fachada { nome: nome_fachada_padrao(pasta), imagens: Vec::new() })
+                .imagens
+                .push(ler_metadados(path));
+            predio.recalcular_centroide();
+            mudou = true;
+        }
+
+        mudou |= self.remover_vazios();
+        mudou
+    }
+
+    // Remove fachadas e prédios sem imagens. Retorna true se algo foi removido.
+    fn remover_vazios(&mut self) -> bool {
+        let antes: usize = self.predios.iter().map(|p| p.fachadas.len()).sum::<usize>() + self.predios.len();
+        for predio in self.predios.iter_mut() {
+            predio.fachadas.retain(|_, f| !f.imagens.is_empty());
+        }
+        self.predios.retain(|p| !p.fachadas.is_empty());
+        let depois: usize = self.predios.iter().map(|p| p.fachadas.len()).sum::<usize>() + self.predios.len();
+        antes != depois
+    }
+
+    // Nome de arquivo livre dentro de uma fachada, adicionando um sufixo se já existir
+    pub fn nome_livre(fachada: Option<&Fachada>, file_name: &str) -> String {
+        let existe = |nome: &str| fachada.is_some_and(|f| f.imagens.iter().any(|im| im.file_name == nome));
+        if !existe(file_name) {
+            return file_name.to_string();
+        }
+        let original = Path::new(file_name);
+        let stem = original.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let ext = original.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+        (1..)
+            .map(|i| format!("{}-{}{}", stem, i, ext))
+            .find(|candidato| !existe(candidato))
+            .expect("sempre existe um nome livre")
+    }
+
+    // Move os arquivos para onde o índice diz que devem estar, remove pastas vazias e
+    // reescreve os caminhos nos arquivos do projeto que guardam imagens por caminho (detecções,
+    // revisão, classificação e medidas). Retorna os movimentos realizados.
+    pub fn aplicar_layout(&mut self, project_dir: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let images_dir = project_dir.join("images");
+        let mut realizados: Vec<(PathBuf, PathBuf)> = Vec::new();
+        let mut erro: Option<anyhow::Error> = None;
+
+        'predios: for predio in self.predios.iter_mut() {
+            for (pasta, fachada) in predio.fachadas.iter_mut() {
+                for imagem in fachada.imagens.iter_mut() {
+                    let destino = caminho_projetado(&images_dir, &predio.id, pasta, &imagem.file_name);
+                    if imagem.path == destino {
+                        continue;
+                    }
+                    if let Some(parent) = destino.parent() {
+                        if let Err(e) = fs::create_dir_all(parent) {
+                            erro = Some(anyhow!("Erro ao criar pasta {}: {}", parent.display(), e));
+                            break 'predios;
+                        }
+                    }
+                    if let Err(e) = fs::rename(&imagem.path, &destino) {
+                        erro = Some(anyhow!("Erro ao mover {} para {}: {}", imagem.path.display(), destino.display(), e));
+                        break 'predios;
+                    }
+                    realizados.push((imagem.path.clone(), destino.clone()));
+                    imagem.path = destino;
+                }
+            }
+        }
+
+        // Mesmo em caso de erro, o que já foi movido precisa refletir no detection_results.json, na
+        // revisão e na classificação
+        atualizar_deteccoes(project_dir, &realizados)?;
+        let mut revisao = RevisaoProjeto::load(project_dir)?;
+        if revisao.atualizar_caminhos(&realizados) {
+            revisao.save(project_dir)?;
+        }
+        classificacao::atualizar_caminhos(project_dir, &realizados)?;
+        geometria::atualizar_caminhos(project_dir, &realizados)?;
+        remover_pastas_vazias(&images_dir);
+        self.remover_vazios();
+        self.normalizar_duplicatas();
+
+        match erro {
+            Some(e) => Err(e),
+            None => Ok(realizados),
+        }
+    }
+}
+
+// Remove pastas de prédio/fachada que ficaram vazias
+fn remover_pastas_vazias(images_dir: &Path) {
+    for entry in WalkDir::new(images_dir).min_depth(1).max_depth(2).contents_first(true).into_iter().filter_map(|e| e.ok()) {
+        if entry.file_type().is_dir() {
+            let _ = fs::remove_dir(entry.path()); // só remove se estiver vazia
+        }
+    }
+}
+
+// Reescreve os caminhos do detection_results.json de acordo com as imagens movidas
+fn atualizar_deteccoes(project_dir: &Path, movimentos: &[(PathBuf, PathBuf)]) -> Result<()> {
+    let json_path = project_dir.join("detection_results.json");
+    if !json_path.exists() || movimentos.is_empty() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&json_path)
+        .with_context(|| format!("Falha ao ler {}", json_path.display()))?;
+    let mut resultados: Vec<ImageAnalysisResult> = serde_json::from_str(&content)
+        .with_context(|| format!("Falha ao parsear {}", json_path.display()))?;
+
+    let relativos: Vec<(PathBuf, &PathBuf)> = movimentos
+        .iter()
+        .filter_map(|(origem, destino)| caminho_relativo_imagens(origem).map(|rel| (rel, destino)))
+        .collect();
+
+    for resultado in resultados.iter_mut() {
+        let Some(rel) = caminho_relativo_imagens(Path::new(&resultado.path)) else { continue };
+        if let Some((_, destino)) = relativos.iter().find(|(origem_rel, _)| *origem_rel == rel) {
+            resultado.path = destino.display().to_string();
+        }
+    }
+
+    fs::write(&json_path, serde_json::to_string_pretty(&resultados)?)
+        .with_context(|| format!("Falha ao salvar {}", json_path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,218 @@
+// project_settings.rs
+// Configurações do projeto salvas em Projects/<projeto>/project_settings.json
+// (parâmetros do pipeline de detecção e filtros aplicados aos resultados).
+use std::path::Path;
+use std::fs;
+use std::collections::BTreeMap;
+use anyhow::{Result, Context};
+use serde::{Serialize, Deserialize};
+
+pub const SETTINGS_FILE_NAME: &str = "project_settings.json";
+
+// Detecção por tiles sobrepostos para imagens de alta resolução (drone)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConfiguracaoLadrilhamento {
+    pub ativo: bool,
+    pub tamanho: u32, // Lado do tile em pixels
+    pub sobreposicao: f64, // Fração do tile compartilhada com o vizinho (0.0 - 0.5)
+}
+
+impl Default for ConfiguracaoLadrilhamento {
+    fn default() -> Self {
+        Self { ativo: false, tamanho: 640, sobreposicao: 0.2 }
+    }
+}
+
+impl ConfiguracaoLadrilhamento {
+    // Argumentos extras para o script de detecção
+    pub fn argumentos_script(&self) -> Vec<String> {
+        if !self.ativo {
+            return Vec::new();
+        }
+        vec![
+            "--tile-size".to_string(),
+            self.tamanho.max(32).to_string(),
+            "--tile-overlap".to_string(),
+            self.sobreposicao.clamp(0.0, 0.5).to_string(),
+        ]
+    }
+}
+
+// Segunda etapa de análise: o modelo de classificação (YOLO-Class-Py) roda depois do detector,
+// na imagem inteira ou em cada caixa detectada, e grava probabilidades por imagem
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ModoClassificacao {
+    #[default]
+    Imagem,
+    Recortes,
+}
+
+impl ModoClassificacao {
+    pub fn argumento_script(&self) -> &'static str {
+        match self {
+            ModoClassificacao::Imagem => "imagem",
+            ModoClassificacao::Recortes => "recortes",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConfiguracaoClassificacao {
+    pub ativa: bool,
+    #[serde(default)]
+    pub modo: ModoClassificacao,
+    pub confianca_minima: f64, // Probabilidade mínima para a imagem contar como "tem a classe"
+    pub tamanho_entrada: u32,
+    // Caminho do modelo .pt; ausente = o que acompanha o repositório (YOLO-Class-Py/best.pt)
+    #[serde(default)]
+    pub modelo: Option<String>,
+}
+
+impl Default for ConfiguracaoClassificacao {
+    fn default() -> Self {
+        Self { ativa: false, modo: ModoClassificacao::Imagem, confianca_minima: 0.5, tamanho_entrada: 224, modelo: None }
+    }
+}
+
+// Escala para converter as medidas das fissuras (ver geometria.rs) de pixels para milímetros
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MetodoEscala {
+    #[default]
+    Nenhuma, // Medidas só em pixels
+    Marcador, // Objeto de tamanho conhecido fotografado junto (ex: régua, alvo de 100 mm)
+    Camera, // Distância até a fachada e dados da câmera
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConfiguracaoEscala {
+    #[serde(default)]
+    pub metodo: MetodoEscala,
+    pub marcador_mm: f64, // Tamanho real do marcador
+    pub marcador_px: f64, // Tamanho do marcador medido na imagem
+    pub distancia_mm: f64, // Distância da câmera até a fachada
+    pub focal_mm: f64, // Distância focal real da lente (não a equivalente 35 mm)
+    pub largura_sensor_mm: f64,
+}
+
+impl Default for ConfiguracaoEscala {
+    fn default() -> Self {
+        Self {
+            metodo: MetodoEscala::Nenhuma,
+            marcador_mm: 100.0,
+            marcador_px: 0.0,
+            distancia_mm: 5000.0,
+            focal_mm: 4.5,
+            largura_sensor_mm: 6.3,
+        }
+    }
+}
+
+impl ConfiguracaoEscala {
+    // Milímetros por pixel numa imagem com a largura dada; None sem escala válida
+    pub fn mm_por_pixel(&self, largura_imagem_px: u32) -> Option<f64> {
+        let escala = match self.metodo {
+            MetodoEscala::Nenhuma => return None,
+            MetodoEscala::Marcador => self.marcador_mm / self.marcador_px,
+            // Modelo pinhole: largura coberta na fachada = distância * sensor / focal
+            MetodoEscala::Camera => self.distancia_mm * self.largura_sensor_mm / (self.focal_mm * largura_imagem_px as f64),
+        };
+        (escala.is_finite() && escala > 0.0).then_some(escala)
+    }
+
+    // Texto para a metodologia do relatório
+    pub fn descricao(&self) -> String {
+        match self.metodo {
+            MetodoEscala::Nenhuma => "sem escala (medidas em pixels)".to_string(),
+            MetodoEscala::Marcador => format!("marcador de referência de {} mm medindo {} px na imagem", self.marcador_mm, self.marcador_px),
+            MetodoEscala::Camera => format!(
+                "câmera a {:.2} m da fachada, focal de {} mm e sensor de {} mm de largura",
+                self.distancia_mm / 1000.0, self.focal_mm, self.largura_sensor_mm
+            ),
+        }
+    }
+}
+
+// Política aplicada às detecções ao carregar os resultados (gráficos, relatórios, exportações).
+// Os padrões reproduzem os do próprio YOLO, então um projeto sem configuração não muda nada.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PoliticaDeteccao {
+    pub confianca_minima_padrao: f64, // 0.0 - 1.0
+    #[serde(default)]
+    pub confianca_minima: BTreeMap<String, f64>, // Por classe; sobrepõe o padrão
+    pub iou_nms: f64, // Caixas da mesma classe com IoU acima disso são suprimidas
+    #[serde(default)]
+    pub classes_ignoradas: Vec<String>,
+}
+
+impl Default for PoliticaDeteccao {
+    fn default() -> Self {
+        Self {
+            confianca_minima_padrao: 0.25,
+            confianca_minima: BTreeMap::new(),
+            iou_nms: 0.7,
+            classes_ignoradas: Vec::new(),
+        }
+    }
+}
+
+impl PoliticaDeteccao {
+    pub fn confianca_minima_para(&self, classe: &str) -> f64 {
+        self.confianca_minima.get(&classe.to_lowercase()).copied().unwrap_or(self.confianca_minima_padrao)
+    }
+
+    pub fn ignora(&self, classe: &str) -> bool {
+        self.classes_ignoradas.iter().any(|c| c.eq_ignore_ascii_case(classe))
+    }
+
+    pub fn definir_ignorada(&mut self, classe: &str, ignorada: bool) {
+        self.classes_ignoradas.retain(|c| !c.eq_ignore_ascii_case(classe));
+        if ignorada {
+            self.classes_ignoradas.push(classe.to_lowercase());
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ProjectSettings {
+    #[serde(default)]
+    pub ladrilhamento: ConfiguracaoLadrilhamento,
+    #[serde(default)]
+    pub politica: PoliticaDeteccao,
+    // Id do modelo de detecção no registro (ver modelos.rs); ausente = primeiro modelo registrado
+    #[serde(default)]
+    pub modelo_ativo: Option<String>,
+    #[serde(default)]
+    pub classificacao: ConfiguracaoClassificacao,
+    #[serde(default)]
+    pub escala: ConfiguracaoEscala,
+    // Nome do template dos relatórios (ver templates_relatorio.rs); ausente = o padrão embutido
+    #[serde(default)]
+    pub template_relatorio: Option<String>,
+    // Modo estrito: campo do template que não existe nos dados impede a geração do relatório em vez
+    // de sair em branco
+    #[serde(default)]
+    pub template_estrito: bool,
+}
+
+impl ProjectSettings {
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(SETTINGS_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Falha ao ler {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Falha ao parsear {}", path.display()))
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(SETTINGS_FILE_NAME);
+        fs::create_dir_all(project_dir)
+            .with_context(|| format!("Falha ao criar {}", project_dir.display()))?;
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Falha ao salvar {}", path.display()))
+    }
+}
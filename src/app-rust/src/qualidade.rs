@@ -0,0 +1,128 @@
+// qualidade.rs
+// Verificação de qualidade das imagens antes da detecção: nitidez (variância do Laplaciano),
+// exposição (histograma de luminância) e resolução. Imagens reprovadas podem ser excluídas da
+// detecção e dos relatórios pelo índice do projeto.
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use image::DynamicImage;
+use image::imageops::FilterType;
+
+// A nitidez é medida numa versão reduzida para o limiar não depender da resolução da câmera
+const LADO_MAXIMO_ANALISE: u32 = 1024;
+
+pub const NITIDEZ_MINIMA: f64 = 100.0;
+// Fração máxima de pixels estourados (>= 250) ou sem informação (<= 5)
+pub const FRACAO_MAXIMA_SUPEREXPOSTA: f64 = 0.25;
+pub const FRACAO_MAXIMA_SUBEXPOSTA: f64 = 0.25;
+pub const BRILHO_MINIMO: f64 = 40.0;
+pub const BRILHO_MAXIMO: f64 = 215.0;
+pub const LARGURA_MINIMA: u32 = 1280;
+pub const ALTURA_MINIMA: u32 = 720;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnaliseQualidade {
+    pub nitidez: f64, // Variância do Laplaciano
+    pub brilho_medio: f64, // 0-255
+    pub fracao_superexposta: f64,
+    pub fracao_subexposta: f64,
+    pub largura: u32,
+    pub altura: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProblemaQualidade {
+    Desfocada,
+    Superexposta,
+    Subexposta,
+    BaixaResolucao,
+}
+
+impl fmt::Display for ProblemaQualidade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemaQualidade::Desfocada => write!(f, "desfocada"),
+            ProblemaQualidade::Superexposta => write!(f, "superexposta"),
+            ProblemaQualidade::Subexposta => write!(f, "subexposta"),
+            ProblemaQualidade::BaixaResolucao => write!(f, "baixa resolução"),
+        }
+    }
+}
+
+impl AnaliseQualidade {
+    pub fn problemas(&self) -> Vec<ProblemaQualidade> {
+        let mut problemas = Vec::new();
+        if self.nitidez < NITIDEZ_MINIMA {
+            problemas.push(ProblemaQualidade::Desfocada);
+        }
+        if self.fracao_superexposta > FRACAO_MAXIMA_SUPEREXPOSTA || self.brilho_medio > BRILHO_MAXIMO {
+            problemas.push(ProblemaQualidade::Superexposta);
+        }
+        if self.fracao_subexposta > FRACAO_MAXIMA_SUBEXPOSTA || self.brilho_medio < BRILHO_MINIMO {
+            problemas.push(ProblemaQualidade::Subexposta);
+        }
+        let (maior, menor) = (self.largura.max(self.altura), self.largura.min(self.altura));
+        if maior < LARGURA_MINIMA || menor < ALTURA_MINIMA {
+            problemas.push(ProblemaQualidade::BaixaResolucao);
+        }
+        problemas
+    }
+
+    pub fn aprovada(&self) -> bool {
+        self.problemas().is_empty()
+    }
+
+    // Ex: "desfocada, subexposta"
+    pub fn resumo(&self) -> String {
+        self.problemas().iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+pub fn analisar_imagem(imagem: &DynamicImage) -> AnaliseQualidade {
+    let (largura, altura) = (imagem.width(), imagem.height());
+    let reduzida = if largura.max(altura) > LADO_MAXIMO_ANALISE {
+        imagem.resize(LADO_MAXIMO_ANALISE, LADO_MAXIMO_ANALISE, FilterType::Triangle)
+    } else {
+        imagem.clone()
+    };
+    let cinza = reduzida.to_luma8();
+    let (w, h) = cinza.dimensions();
+
+    // Histograma de luminância
+    let total = (w as f64) * (h as f64);
+    let mut soma = 0.0;
+    let mut estourados = 0usize;
+    let mut escuros = 0usize;
+    for pixel in cinza.pixels() {
+        let v = pixel[0];
+        soma += v as f64;
+        if v >= 250 {
+            estourados += 1;
+        } else if v <= 5 {
+            escuros += 1;
+        }
+    }
+
+    // Laplaciano 3x3 (4-vizinhança) e sua variância
+    let mut valores = Vec::with_capacity((w.saturating_sub(2) * h.saturating_sub(2)) as usize);
+    for y in 1..h.saturating_sub(1) {
+        for x in 1..w.saturating_sub(1) {
+            let p = |dx: i32, dy: i32| cinza.get_pixel((x as i32 + dx) as u32, (y as i32 + dy) as u32)[0] as f64;
+            valores.push(p(0, -1) + p(-1, 0) + p(1, 0) + p(0, 1) - 4.0 * p(0, 0));
+        }
+    }
+    let nitidez = if valores.is_empty() {
+        0.0
+    } else {
+        let media = valores.iter().sum::<f64>() / valores.len() as f64;
+        valores.iter().map(|v| (v - media).powi(2)).sum::<f64>() / valores.len() as f64
+    };
+
+    AnaliseQualidade {
+        nitidez,
+        brilho_medio: if total > 0.0 { soma / total } else { 0.0 },
+        fracao_superexposta: if total > 0.0 { estourados as f64 / total } else { 0.0 },
+        fracao_subexposta: if total > 0.0 { escuros as f64 / total } else { 0.0 },
+        largura,
+        altura,
+    }
+}
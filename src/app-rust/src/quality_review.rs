@@ -0,0 +1,163 @@
+// quality_review.rs
+// Tela com as imagens reprovadas na verificação de qualidade (desfocadas, mal expostas ou de
+// baixa resolução) e a opção de deixá-las fora da detecção e dos relatórios.
+use dioxus::prelude::*;
+use dioxus_router::prelude::*;
+use std::path::PathBuf;
+use crate::grouping_review::{reorganizar, use_indice, concluir_operacao};
+use crate::project_index;
+use crate::qualidade::{AnaliseQualidade, NITIDEZ_MINIMA};
+use crate::Route;
+
+#[derive(Debug, Clone, PartialEq)]
+struct ImagemReprovada {
+    local: String, // "Prédio 1 / Norte"
+    path: PathBuf,
+    file_name: String,
+    analise: AnaliseQualidade,
+}
+
+#[derive(Props, PartialEq, Clone)]
+pub struct QualityReviewProps {
+    pub project_name: String,
+}
+
+#[component]
+pub fn QualityReview(props: QualityReviewProps) -> Element {
+    let project_dir: PathBuf = project_index::project_dir(&props.project_name);
+    let status = use_signal(String::new);
+    let indice = use_indice(&project_dir, status);
+    let navigator = use_navigator();
+
+    let excluir_baixa_qualidade = indice.read().excluir_baixa_qualidade;
+    let mut total_analisadas = 0usize;
+    let mut reprovadas: Vec<ImagemReprovada> = Vec::new();
+    for predio in &indice.read().predios {
+        for fachada in predio.fachadas.values() {
+            for imagem in &fachada.imagens {
+                let Some(analise) = &imagem.qualidade else { continue };
+                total_analisadas += 1;
+                if !analise.aprovada() {
+                    reprovadas.push(ImagemReprovada {
+                        local: format!("{} / {}", predio.nome, fachada.nome),
+                        path: imagem.path.clone(),
+                        file_name: imagem.file_name.clone(),
+                        analise: analise.clone(),
+                    });
+                }
+            }
+        }
+    }
+    let total_reprovadas = reprovadas.len();
+
+    rsx! {
+        document::Stylesheet { href: asset!("/assets/tailwind.css") }
+
+        div { class: "min-h-screen bg-gray-100 text-gray-900 font-sans",
+            document::Link {
+                href: "https://fonts.googleapis.com/icon?family=Material+Icons",
+                rel: "stylesheet"
+            }
+
+            header { class: "flex items-center justify-between bg-blue-600 text-black p-4 shadow",
+                div { class: "flex items-center gap-4",
+                    i { class: "material-icons", "high_quality" }
+                    h1 { class: "text-xl font-bold", "Qualidade das Imagens: {props.project_name}" }
+                }
+                div { class: "flex items-center gap-2",
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        onclick: {
+                            let project_name = props.project_name.clone();
+                            move |_| { navigator.push(Route::GroupingReview { project_name: project_name.clone() }); }
+                        },
+                        i { class: "material-icons", "photo_library" }
+                        "Agrupamento"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-white text-blue-600 rounded-md shadow flex items-center gap-2",
+                        onclick: {
+                            let project_name = props.project_name.clone();
+                            move |_| { navigator.push(Route::GraphView { project_name: project_name.clone() }); }
+                        },
+                        i { class: "material-icons", "bar_chart" }
+                        "Ver Gráficos"
+                    }
+                }
+            }
+
+            div { class: "container mx-auto px-4 py-8 max-w-6xl space-y-6",
+                p { class: "text-gray-600",
+                    "{total_reprovadas} de {total_analisadas} imagem(ns) analisada(s) foram reprovadas. Fotos desfocadas ou mal expostas geram detecções falsas ou perdidas."
+                }
+
+                div { class: "bg-white rounded-lg shadow-md p-6 flex flex-wrap items-center gap-4",
+                    label { class: "flex items-center gap-2 text-gray-700 flex-1",
+                        input {
+                            r#type: "checkbox",
+                            checked: excluir_baixa_qualidade,
+                            onchange: {
+                                let project_dir = project_dir.clone();
+                                move |e: FormEvent| {
+                                    let excluir = e.checked();
+                                    let resultado = reorganizar(&project_dir, |index| {
+                                        index.excluir_baixa_qualidade = excluir;
+                                        Ok(())
+                                    })
+                                    .map(|_| if excluir {
+                                        "Imagens reprovadas serão ignoradas na detecção e nos relatórios.".to_string()
+                                    } else {
+                                        "Imagens reprovadas voltam a ser consideradas na detecção e nos relatórios.".to_string()
+                                    });
+                                    concluir_operacao(resultado, &project_dir, indice, status);
+                                }
+                            }
+                        }
+                        "Excluir imagens reprovadas da detecção e dos relatórios"
+                    }
+                    button {
+                        class: "px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 flex items-center gap-2",
+                        onclick: {
+                            let project_dir = project_dir.clone();
+                            move |_| {
+                                let resultado = reorganizar(&project_dir, |index| Ok(index.analisar_qualidade()))
+                                    .map(|n| format!("Análise concluída: {} imagem(ns) reprovada(s).", n));
+                                concluir_operacao(resultado, &project_dir, indice, status);
+                            }
+                        },
+                        i { class: "material-icons", "refresh" }
+                        "Analisar imagens pendentes"
+                    }
+                }
+
+                if !status.read().is_empty() {
+                    p { class: "text-center text-gray-700 py-2 px-4 border border-gray-300 bg-white rounded-md", "{status}" }
+                }
+
+                if reprovadas.is_empty() {
+                    p { class: "text-center text-gray-500", "Nenhuma imagem reprovada." }
+                }
+
+                div { class: "grid grid-cols-1 sm:grid-cols-2 md:grid-cols-3 gap-4",
+                    for imagem in reprovadas.into_iter() {
+                        div { key: "{imagem.path.display()}", class: "bg-white rounded-lg shadow-md p-4",
+                            div { class: "aspect-w-4 aspect-h-3 bg-gray-200 rounded-lg overflow-hidden",
+                                img {
+                                    src: "file://{imagem.path.display()}",
+                                    class: "w-full h-full object-cover",
+                                    alt: "{imagem.file_name}"
+                                }
+                            }
+                            p { class: "mt-2 text-sm font-semibold text-gray-700 truncate", "{imagem.file_name}" }
+                            p { class: "text-xs text-gray-500", "{imagem.local}" }
+                            p { class: "text-sm text-red-600", "{imagem.analise.resumo()}" }
+                            p { class: "text-xs text-gray-500",
+                                "Nitidez: {imagem.analise.nitidez:.0} (mín. {NITIDEZ_MINIMA:.0}) · Brilho: {imagem.analise.brilho_medio:.0} · {imagem.analise.largura}x{imagem.analise.altura}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
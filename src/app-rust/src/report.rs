@@ -1,40 +1,41 @@
 use dioxus::prelude::*;
 use dioxus_router::prelude::*;
-use serde::Deserialize;
-use tempfile::NamedTempFile;
 use pulldown_cmark::{Parser, Options, html};
 use std::{
     io::{
         Read,
         Write,
-        BufWriter,
-        BufReader
     },
-    process::Command,
     fs::File,
     path::{Path, PathBuf},
     env
 };
+use anyhow::Context as _;
 use chrono::Local;
-use rand::Rng;
+use rfd::AsyncFileDialog;
 use crate::Route;
+use crate::project_index::{self, ProjectIndex};
+use crate::image_processor::Predio;
+use crate::project_settings::{ProjectSettings, ModoClassificacao};
+use crate::deteccoes;
+use crate::modelos::RegistroModelos;
+use crate::taxonomia::Taxonomia;
+use crate::classificacao;
+use crate::manifesto::ManifestoProjeto;
+use crate::versoes_relatorio::{self, HistoricoRelatorio, LinhaDiff};
+use crate::edicao_relatorio;
+use crate::documento_relatorio::DocumentoRelatorio;
+use crate::pdf_relatorio;
+use crate::docx_relatorio;
+use crate::html_relatorio;
+use crate::templates_relatorio;
+use crate::report_structures::{ReportData, ContextoRelatorio, MetodologiaRelatorio, LimiarClasse, ClassificacaoRelatorio, ClassificacaoClasse};
+use std::collections::{BTreeMap, HashMap};
 
 #[path = "./report_generator.rs"]
 pub mod report_generator;
 use report_generator::generate_report;
 
-#[derive(Deserialize, Debug, Clone)]
-struct DetectionFissura {
-    name: String,
-    confidence: f64,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-struct ImageDetectionData {
-    path: String,
-    fissura: Vec<DetectionFissura>,
-}
-
 fn render_markdown(md: &str) -> String {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_TABLES);
@@ -46,138 +47,339 @@ fn render_markdown(md: &str) -> String {
     html_output
 }
 
-fn export(md_content: &str, file_type: &str) {
-    let md_content = md_content.to_string();
-    let file_type = file_type.to_string();
-
-    std::thread::spawn(move || {
-        let file_type_lower = file_type.to_lowercase();
-        let file_type_upper = file_type.to_uppercase();
-
-        if let Some(path) = rfd::FileDialog::new()
-            .set_title(&format!("Salvar arquivo {} como...", file_type_lower))
-            .add_filter(&file_type_upper, &[&file_type_lower])
-            .set_file_name(&format!("Relatorio.{}", &file_type_lower))
-            .save_file()
-        {
-            let path = PathBuf::from(path);
-            if &file_type_lower == "md" {
-                let new_file = File::create(&path).unwrap();
-                let mut writer = BufWriter::new(new_file);
-                writer.write_all(md_content.as_bytes()).unwrap();
-            } else {
-                let mut temp_md = NamedTempFile::new().unwrap();
-                write!(temp_md, "{}", md_content).unwrap();
-                temp_md.flush().unwrap();
-
-                let status = Command::new("pandoc")
-                    .arg(temp_md.path())
-                    .arg("-o")
-                    .arg(&path)
-                    .status()
-                    .expect(&format!("Falha ao gerar {} com pandoc", &file_type_upper));
-
-                if !status.success() {
-                    eprintln!("Erro ao converter Markdown para {} com pandoc", &file_type_upper);
+// MD é gravado como está; PDF, DOCX e HTML são montados pelo próprio app (pdf_relatorio.rs,
+// docx_relatorio.rs e html_relatorio.rs), sem depender do pandoc. PDF e DOCX vêm dos dados do
+// relatório com o layout do app: do Markdown só levam as seções marcadas, então o template escolhido
+// e as edições fora dessas seções ficam só no MD e no HTML. Devolve o arquivo gravado, ou None se o
+// usuário cancelou a escolha do destino.
+async fn exportar(project_name: String, building_name: String, md_content: String, file_type: &str) -> anyhow::Result<Option<PathBuf>> {
+    let file_type_lower = file_type.to_lowercase();
+    let Some(arquivo) = AsyncFileDialog::new()
+        .set_title(format!("Salvar arquivo {} como...", file_type_lower))
+        .add_filter(file_type.to_uppercase(), &[&file_type_lower])
+        .set_file_name(format!("Relatorio.{}", &file_type_lower))
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    let path = arquivo.path().to_path_buf();
+    // A montagem das imagens anotadas é pesada; fica fora da thread da interface
+    tokio::task::spawn_blocking(move || -> anyhow::Result<Option<PathBuf>> {
+        let project_dir = project_index::project_dir(&project_name);
+        match file_type_lower.as_str() {
+            "md" => std::fs::write(&path, md_content.as_bytes()).with_context(|| format!("Falha ao gravar {}", path.display()))?,
+            "html" => html_relatorio::gerar(&project_dir, &md_content, &path)?,
+            formato => {
+                let modelo = modelo_relatorio(&project_name, &building_name).map_err(|e| anyhow::anyhow!("{}", e))?;
+                let documento = DocumentoRelatorio::montar(&modelo.dados, &modelo.contexto, &modelo.taxonomia, &md_content);
+                match formato {
+                    "pdf" => pdf_relatorio::gerar(&documento, &path)?,
+                    _ => docx_relatorio::gerar(&documento, &project_dir, &path)?,
                 }
             }
         }
-    });
+        println!("[RUST report.rs] {} gerado em: {:?}", file_type_lower.to_uppercase(), path);
+        Ok(Some(path))
+    })
+    .await?
 }
 
-fn get_report(project_name_prop: &str, building_name_prop: &str) -> Result<String, handlebars::RenderError> {
-    let template: &str = include_str!("Template/report_template.md");
+// PDF e DOCX não usam o Markdown inteiro (ver exportar): diz se o Markdown salvo foi editado fora das
+// seções marcadas desde a última versão gerada, comparando-o com essa versão e as seções dele
+fn editado_fora_das_secoes(project_name: &str, building_name: &str, md_content: &str) -> bool {
+    let report_dir = pasta_relatorios(project_name);
+    let Ok(historico) = HistoricoRelatorio::load(&report_dir, &nome_base_relatorio(project_name, building_name)) else { return false };
+    let Some(gerada) = historico.versoes.iter().rev().find(|versao| !versao.editada) else { return false };
+    let Ok(gerado) = historico.ler(&report_dir, gerada.numero) else { return false };
+    let esperado = edicao_relatorio::aplicar_secoes(&gerado, &edicao_relatorio::extrair_secoes(md_content));
+    esperado.trim_end() != md_content.trim_end()
+}
 
-    let cwd_string = match env::current_dir() {
-        Ok(cwd) => {
-            let s = cwd.display().to_string();
-            println!("[RUST report.rs] Current Working Directory: {}", s);
-            s
-        }
+// Exporta o Markdown salvo do relatório no formato dado e mostra o resultado (ou o erro) em `status`
+fn iniciar_exportacao(project_name: String, building_name: String, md_path: PathBuf, file_type: &'static str, mut status: Signal<String>) {
+    let md_content = match std::fs::read_to_string(&md_path) {
+        Ok(content) => content,
         Err(e) => {
-            let err_msg = format!("Failed to get CWD for report: {}", e);
-            eprintln!("[RUST report.rs] {}", err_msg);
-            return Err(handlebars::RenderError::from(handlebars::RenderErrorReason::Other(err_msg)));
+            status.set(format!("Erro ao ler o relatório {} para exportação: {}", md_path.display(), e));
+            return;
         }
     };
+    let aviso = if matches!(file_type, "PDF" | "DOCX") && editado_fora_das_secoes(&project_name, &building_name, &md_content) {
+        ". Atenção: o relatório tem edições fora das seções marcadas, que não entram neste formato (use MD ou HTML para levá-las)"
+    } else {
+        ""
+    };
+    status.set(format!("Exportando em {}...", file_type));
+    spawn(async move {
+        match exportar(project_name, building_name, md_content, file_type).await {
+            Ok(Some(path)) => status.set(format!("{} salvo em {}{}", file_type, path.display(), aviso)),
+            Ok(None) => status.set(String::new()),
+            Err(e) => status.set(format!("Erro ao exportar em {}: {:#}", file_type, e)),
+        }
+    });
+}
 
-    let detection_json_path_str = format!("../Projects/{}/detection_results.json", project_name_prop);
-    let absolute_detection_json_path = Path::new(&cwd_string).join(&detection_json_path_str);
+// Seção de metodologia: registra com quais filtros as detecções do relatório foram obtidas. As
+// contagens de revisão são preenchidas em report_generator::contexto_relatorio.
+fn metodologia_do_projeto(settings: &ProjectSettings, modelo: String) -> MetodologiaRelatorio {
+    let politica = &settings.politica;
+    let limiares = politica.confianca_minima
+        .iter()
+        .map(|(classe, confianca)| LimiarClasse { classe: classe.clone(), confianca: (confianca * 100.0).round() as u32 })
+        .collect();
+    let classes_ignoradas = if politica.classes_ignoradas.is_empty() {
+        "nenhuma".to_string()
+    } else {
+        politica.classes_ignoradas.join(", ")
+    };
+    let ladrilhamento = if settings.ladrilhamento.ativo {
+        format!("tiles de {} px com {:.0}% de sobreposição", settings.ladrilhamento.tamanho, settings.ladrilhamento.sobreposicao * 100.0)
+    } else {
+        "imagem inteira".to_string()
+    };
+    MetodologiaRelatorio {
+        modelo,
+        ladrilhamento,
+        confianca_minima_padrao: (politica.confianca_minima_padrao * 100.0).round() as u32,
+        limiares,
+        iou_nms: politica.iou_nms,
+        classes_ignoradas,
+        escala: settings.escala.descricao(),
+        fissuras_revisadas: 0,
+        fissuras_total: 0,
+    }
+}
 
-    println!("[RUST report.rs] Attempting to read detection_results.json from (absolute constructed): {:?}", absolute_detection_json_path);
+// Valor de building_name na rota do relatório que pede o resumo do projeto (todos os prédios)
+pub const RESUMO_PROJETO: &str = "resumo-projeto";
 
-    if !absolute_detection_json_path.exists() {
-        let err_msg = format!("Arquivo detection_results.json não existe em: {:?}. Verifique o CWD e o caminho relativo.", absolute_detection_json_path);
-        eprintln!("[RUST report.rs] {}", err_msg);
-        return Err(handlebars::RenderError::from(handlebars::RenderErrorReason::Other(err_msg)));
-    }
+// Modelo do relatório: os dados de cada prédio coberto (os mesmos exportados em JSON) e o contexto
+// do template derivado deles. É a base do Markdown e das exportações em PDF e DOCX.
+struct ModeloRelatorio {
+    predios: Vec<String>, // Ids dos prédios, na ordem de `dados`
+    dados: Vec<ReportData>,
+    contexto: ContextoRelatorio,
+    taxonomia: Taxonomia,
+    estrito: bool, // Modo estrito do template nas configurações do projeto
+}
 
-    let file = File::open(&absolute_detection_json_path)
-        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao abrir detection_results.json (path: {:?}): {}", absolute_detection_json_path, e))))?;
-    
-    let detection_data_vec: Vec<ImageDetectionData> = serde_json::from_reader(BufReader::new(file))
-        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao ler/parsear detection_results.json (path: {:?}): {}", absolute_detection_json_path, e))))?;
+fn modelo_relatorio(project_name_prop: &str, building_name_prop: &str) -> Result<ModeloRelatorio, handlebars::RenderError> {
+    let project_dir = project_index::project_dir(project_name_prop);
+    let settings = ProjectSettings::load(&project_dir)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao ler configurações do projeto: {}", e))))?;
+    // Exclusões do índice e política de detecção já aplicadas
+    let detection_data_vec = deteccoes::carregar(&project_dir)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao carregar resultados de detecção: {:#}", e))))?;
+
+    // Modelo que gerou os resultados (o carregamento já recusa resultados misturados) e a
+    // taxonomia dele, que dá rótulos, descrições e pesos das classes
+    let registro = RegistroModelos::load().unwrap_or_default();
+    let modelo_resultados = detection_data_vec.first()
+        .and_then(|r| r.modelo.as_deref())
+        .map(|id| (id.to_string(), registro.modelo(id)));
+    let modelo = match &modelo_resultados {
+        Some((_, Some(registrado))) => registrado.descricao(),
+        Some((id, None)) => id.clone(),
+        None => "não identificado".to_string(),
+    };
+    let taxonomia = Taxonomia::dos_resultados(&project_dir, &detection_data_vec);
+
+    let index = ProjectIndex::abrir(&project_dir)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao abrir índice do projeto: {:#}", e))))?;
+
+    // Prédios cobertos: um só, ou todos no resumo do projeto. O relatório de um prédio só considera
+    // as imagens das fachadas dele.
+    let resumo_projeto = building_name_prop == RESUMO_PROJETO;
+    let predios_relatorio: Vec<&Predio> = if resumo_projeto {
+        index.predios.iter().collect()
+    } else {
+        let predio = index.buscar(building_name_prop)
+            .ok_or_else(|| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Prédio '{}' não encontrado no índice do projeto", building_name_prop))))?;
+        vec![predio]
+    };
+    let do_relatorio = |path: &str| {
+        resumo_projeto || index.localizar(Path::new(path)).is_some_and(|(predio, _, _)| predio.id == predios_relatorio[0].id)
+    };
+    let detection_data_vec: Vec<_> = detection_data_vec.into_iter().filter(|r| do_relatorio(&r.path)).collect();
 
-    let mut fissuras_flat_for_template = Vec::new();
-    let mut rng = rand::thread_rng();
+    // Classificação por imagem (segunda etapa), quando o projeto a rodou
+    let classificacoes: Vec<_> = classificacao::carregar(&project_dir)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao carregar resultados da classificação: {:#}", e))))?
+        .into_iter()
+        .filter(|c| do_relatorio(&c.path))
+        .collect();
+    let confianca_classificacao = settings.classificacao.confianca_minima;
+    let resumo_classificacao: Vec<ClassificacaoClasse> = classificacao::resumir(&classificacoes, &detection_data_vec, &index, confianca_classificacao)
+        .into_iter()
+        .map(|r| ClassificacaoClasse { classe: taxonomia.rotulo(&r.classe), imagens: r.imagens, caixas: r.caixas })
+        .collect();
 
-    for image_data in detection_data_vec {
-        let path_obj = Path::new(&image_data.path);
-        let facade_name = path_obj.parent().and_then(|p| p.file_name()).and_then(|os| os.to_str()).unwrap_or("N/A").to_string();
+    // Gráficos da seção de estatísticas, em Report/<projeto>/graficos/; sem eles o relatório sai assim mesmo
+    let graficos = report_generator::graficos_relatorio(
+        &predios_relatorio,
+        &index,
+        &detection_data_vec,
+        &taxonomia,
+        &pasta_relatorios(project_name_prop).join("graficos"),
+        &nome_base_relatorio(project_name_prop, building_name_prop),
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("[RUST report.rs] Falha ao gerar os gráficos do relatório: {:#}", e);
+        Vec::new()
+    });
+
+    // Dados de cada prédio (os mesmos exportados em JSON) e o contexto do template derivado deles
+    let manifesto = ManifestoProjeto::load(&project_dir)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao ler dados do projeto: {:#}", e))))?;
+    let data_analise = report_generator::data_analise(&project_dir);
+    let dados_predios: Vec<ReportData> = predios_relatorio
+        .iter()
+        .map(|predio| report_generator::dados_relatorio(project_name_prop, &manifesto, predio, &index, &detection_data_vec, &taxonomia, data_analise.clone()))
+        .collect();
+    let classificacao_imagens: HashMap<String, String> = classificacoes
+        .iter()
+        .filter_map(|c| {
+            let (classe, p) = c.classe_prevista()?;
+            Some((c.path.clone(), format!("{} ({:.0}%)", taxonomia.rotulo(classe), p * 100.0)))
+        })
+        .collect();
+    let resumo_classificacao = (!classificacoes.is_empty()).then(|| ClassificacaoRelatorio {
+        imagens: classificacoes.len(),
+        modo: match settings.classificacao.modo {
+            ModoClassificacao::Imagem => "imagem inteira",
+            ModoClassificacao::Recortes => "cada caixa detectada",
+        }
+        .to_string(),
+        confianca_minima: (confianca_classificacao * 100.0).round() as u32,
+        resumo: resumo_classificacao,
+    });
+    let contexto = report_generator::contexto_relatorio(
+        &dados_predios,
+        resumo_projeto,
+        &taxonomia,
+        metodologia_do_projeto(&settings, modelo),
+        resumo_classificacao,
+        &classificacao_imagens,
+        graficos,
+    )
+    .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("{:#}", e))))?;
+    Ok(ModeloRelatorio {
+        predios: predios_relatorio.iter().map(|predio| predio.id.clone()).collect(),
+        dados: dados_predios,
+        contexto,
+        taxonomia,
+        estrito: settings.template_estrito,
+    })
+}
 
-        for fissura_item in image_data.fissura {
-            let mut fissura_obj_for_template = serde_json::Map::new();
-            fissura_obj_for_template.insert("caminho_imagem".to_string(), serde_json::Value::String(image_data.path.clone()));
-            fissura_obj_for_template.insert("classificacao".to_string(), serde_json::Value::String(fissura_item.name.clone()));
-            
-            let confidence_number = serde_json::Number::from_f64(fissura_item.confidence)
-                                      .unwrap_or_else(|| serde_json::Number::from(0));
-            fissura_obj_for_template.insert("confianca".to_string(), serde_json::Value::Number(confidence_number));
-            
-            fissura_obj_for_template.insert("faceta_id".to_string(), serde_json::Value::String(facade_name.clone()));
-            fissura_obj_for_template.insert("orientacao".to_string(), serde_json::Value::String("N/A".to_string()));
-            fissura_obj_for_template.insert("observacoes".to_string(), serde_json::Value::String("N/A".to_string()));
-            fissura_obj_for_template.insert("id_fissura".to_string(), serde_json::Value::String(format!("f_{}", rng.gen::<u32>())));
+// Markdown do relatório a partir dos dados atuais do projeto, com as variáveis que o template usa
+// e os dados não têm (saem em branco, ou impedem a geração no modo estrito)
+fn gerar_markdown(project_name_prop: &str, building_name_prop: &str, template: &str) -> Result<(String, Vec<String>), handlebars::RenderError> {
+    let project_dir = project_index::project_dir(project_name_prop);
+    let modelo = modelo_relatorio(project_name_prop, building_name_prop)?;
+    let final_json_for_template = serde_json::to_value(&modelo.contexto)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao montar os dados do relatório: {}", e))))?;
 
-            fissuras_flat_for_template.push(serde_json::Value::Object(fissura_obj_for_template));
+    let ausentes = templates_relatorio::variaveis_ausentes(template, &final_json_for_template)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro no template do relatório: {}", e))))?;
+    // No modo estrito o relatório não é gerado com campos em branco
+    if modelo.estrito && !ausentes.is_empty() {
+        return Err(handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!(
+            "O template usa campos que não existem nos dados do relatório (modo estrito): {}",
+            ausentes.join(", ")
+        ))));
+    }
+    if !ausentes.is_empty() {
+        eprintln!("[RUST report.rs] Variáveis do template sem valor nos dados: {}", ausentes.join(", "));
+    }
+    let report_markdown_content = generate_report(template, &final_json_for_template, &modelo.taxonomia, modelo.estrito)?;
+    // Exportação dos mesmos dados em JSON (Dados-<projeto>-<prédio>.json na pasta do projeto)
+    for (predio, dados) in modelo.predios.iter().zip(&modelo.dados) {
+        if let Err(e) = report_generator::salvar_dados_json(&project_dir, project_name_prop, predio, dados) {
+            eprintln!("[RUST report.rs] Falha ao exportar dados do relatório: {:#}", e);
         }
     }
+    Ok((report_markdown_content, ausentes))
+}
 
-    let mut template_data = serde_json::Map::new();
-    template_data.insert("nome_projeto".to_string(), serde_json::Value::String(project_name_prop.to_string()));
-    template_data.insert("nome_predio".to_string(), serde_json::Value::String(building_name_prop.to_string()));
-    template_data.insert("fissuras".to_string(), serde_json::Value::Array(fissuras_flat_for_template));
-    
-    let now = Local::now();
-    template_data.insert("data_geracao".to_string(), serde_json::Value::String(now.format("%Y-%m-%d %H:%M:%S").to_string()));
+// Aviso no topo do relatório na tela (não vai para o Markdown nem para as exportações)
+fn aviso_variaveis_ausentes(ausentes: &[String]) -> String {
+    if ausentes.is_empty() {
+        return String::new();
+    }
+    let lista = ausentes.join(", ").replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    format!("<div class=\"avisos-template\"><strong>O template usa campos sem valor nos dados do projeto, que saíram em branco:</strong> {}</div>", lista)
+}
+
+// Nome dos arquivos do relatório (sem extensão) em Report/<projeto>/
+fn nome_base_relatorio(project_name: &str, building_name: &str) -> String {
+    format!("Relatorio-{}-{}", project_name.replace(' ', "_"), building_name.replace(' ', "_"))
+}
 
-    let final_json_for_template = serde_json::Value::Object(template_data);
+fn pasta_relatorios(project_name: &str) -> PathBuf {
+    ["Report", project_name].iter().collect()
+}
 
-    let report_output_dir: PathBuf = ["Report", project_name_prop].iter().collect();
-    let report_md_filename: String = format!("Relatorio-{}-{}.md", project_name_prop.replace(' ', "_"), building_name_prop.replace(' ', "_"));
-    let report_md_filepath: PathBuf = report_output_dir.join(&report_md_filename);
+// Relatório em HTML. O Markdown salvo é reaproveitado enquanto as entradas forem as mesmas da
+// última versão; quando alguma muda (ou com `forcar`) ele é gerado de novo e vira uma nova versão.
+fn get_report(project_name_prop: &str, building_name_prop: &str, forcar: bool) -> Result<String, handlebars::RenderError> {
+    let project_dir = project_index::project_dir(project_name_prop);
+    // Template escolhido no projeto (ver templates_relatorio.rs); trocar de template ou editá-lo muda
+    // o hash da entrada "template" e gera uma nova versão
+    let template_escolhido = templates_relatorio::selecionado(&project_dir);
+    let template = template_escolhido.conteudo.as_str();
+    let report_output_dir = pasta_relatorios(project_name_prop);
+    let nome_base = nome_base_relatorio(project_name_prop, building_name_prop);
+    let report_md_filepath: PathBuf = report_output_dir.join(format!("{}.md", nome_base));
+    std::fs::create_dir_all(&report_output_dir).map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao criar pasta Report '{:?}': {}", report_output_dir, e))))?;
 
-    let report_markdown_content: String;
+    // Entradas do relatório recortadas no prédio; o resumo do projeto usa os arquivos inteiros
+    let predio_entradas = (building_name_prop != RESUMO_PROJETO).then_some(building_name_prop);
+    let mut historico = HistoricoRelatorio::load(&report_output_dir, &nome_base)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao ler histórico do relatório: {:#}", e))))?;
+    // Relatório gerado antes do histórico: entra como primeira versão, sem entradas registradas
+    if historico.versoes.is_empty() && report_md_filepath.exists() {
+        let anterior = std::fs::read_to_string(&report_md_filepath)
+            .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao ler arquivo MD existente '{:?}': {}", report_md_filepath, e))))?;
+        historico.registrar(&report_output_dir, &nome_base, &anterior, BTreeMap::new(), false)
+            .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao salvar histórico do relatório: {:#}", e))))?;
+    }
 
-    if !report_md_filepath.exists() {
-        std::fs::create_dir_all(&report_output_dir).map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao criar pasta Report '{:?}': {}", report_output_dir, e))))?;
-        report_markdown_content = generate_report(template, &final_json_for_template)?;
-        let mut file = File::create(&report_md_filepath)
-            .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao criar arquivo MD '{:?}': {}", report_md_filepath, e))))?;
-        file.write_all(report_markdown_content.as_bytes())
-            .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao escrever no arquivo MD '{:?}': {}", report_md_filepath, e))))?;
-        println!("[RUST report.rs] Novo relatório MD gerado em: {:?}", report_md_filepath);
-    } else {
+    let atualizado = historico.atual()
+        .is_some_and(|versao| versao.entradas == versoes_relatorio::hashes_entradas(&project_dir, predio_entradas, template));
+    if atualizado && !forcar && report_md_filepath.exists() {
         let mut file = File::open(&report_md_filepath)
             .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao abrir arquivo MD existente '{:?}': {}", report_md_filepath, e))))?;
         let mut md_content = String::new();
         file.read_to_string(&mut md_content)
             .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao ler arquivo MD existente '{:?}': {}", report_md_filepath, e))))?;
-        report_markdown_content = md_content;
         println!("[RUST report.rs] Relatório MD existente carregado de: {:?}", report_md_filepath);
+        return Ok(render_markdown(&md_content));
     }
 
-    Ok(render_markdown(&report_markdown_content))
+    let (mut report_markdown_content, ausentes) = gerar_markdown(project_name_prop, building_name_prop, template)?;
+    if let Ok(atual) = std::fs::read_to_string(&report_md_filepath) {
+        // Edição feita no app depois da última versão entra no histórico antes de ser substituída
+        if let Some(ultima) = historico.atual().cloned() {
+            if historico.ler(&report_output_dir, ultima.numero).ok().as_deref() != Some(atual.as_str()) {
+                historico.registrar(&report_output_dir, &nome_base, &atual, ultima.entradas, true)
+                    .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao salvar histórico do relatório: {:#}", e))))?;
+            }
+        }
+        // Conclusões, recomendações e demais seções marcadas passam para a nova versão
+        report_markdown_content = edicao_relatorio::aplicar_secoes(&report_markdown_content, &edicao_relatorio::extrair_secoes(&atual));
+    }
+    let entradas = versoes_relatorio::hashes_entradas(&project_dir, predio_entradas, template);
+    let versao = historico.registrar(&report_output_dir, &nome_base, &report_markdown_content, entradas, false)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao salvar histórico do relatório: {:#}", e))))?;
+    let mut file = File::create(&report_md_filepath)
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao criar arquivo MD '{:?}': {}", report_md_filepath, e))))?;
+    file.write_all(report_markdown_content.as_bytes())
+        .map_err(|e| handlebars::RenderError::from(handlebars::RenderErrorReason::Other(format!("Erro ao escrever no arquivo MD '{:?}': {}", report_md_filepath, e))))?;
+    println!("[RUST report.rs] Relatório MD gerado em: {:?} (versão {})", report_md_filepath, versao.numero);
+
+    Ok(format!("{}{}", aviso_variaveis_ausentes(&ausentes), render_markdown(&report_markdown_content)))
 }
 
 #[derive(Props, PartialEq, Clone)]
@@ -188,14 +390,105 @@ pub struct ReportViewProps {
 
 #[allow(non_snake_case)]
 pub fn ReportView(props: ReportViewProps) -> Element {
-    let report_md_filename: String = format!("Relatorio-{}-{}.md", &props.project_name.replace(' ', "_"), &props.building_name.replace(' ', "_"));
-    let report_md_filepath: PathBuf = ["Report", &props.project_name, &report_md_filename].iter().collect();
+    let report_output_dir = pasta_relatorios(&props.project_name);
+    let nome_base = nome_base_relatorio(&props.project_name, &props.building_name);
+    let report_md_filepath: PathBuf = report_output_dir.join(format!("{}.md", nome_base));
     
     if let Ok(cwd) = env::current_dir() {
         println!("[RUST ReportView Render] CWD: {:?}", cwd);
     }
     println!("[RUST ReportView Render] Tentando usar MD de: {:?}", report_md_filepath);
 
+    let erro_html = |e: handlebars::RenderError| format!("<h1>Erro ao gerar relatório</h1><p>Detalhes: {}</p><p>Verifique o console para mais informações sobre caminhos de arquivos.</p>", e);
+    let mut conteudo = use_signal({
+        let (project_name, building_name) = (props.project_name.clone(), props.building_name.clone());
+        move || get_report(&project_name, &building_name, false).unwrap_or_else(erro_html)
+    });
+    let mut historico = use_signal({
+        let (report_output_dir, nome_base) = (report_output_dir.clone(), nome_base.clone());
+        move || HistoricoRelatorio::load(&report_output_dir, &nome_base).unwrap_or_default()
+    });
+    let mut versao_a = use_signal(|| None::<u32>);
+    let mut versao_b = use_signal(|| None::<u32>);
+    // Editor: Markdown em edição, contador de alterações (para o salvamento automático só gravar
+    // depois de uma pausa na digitação) e situação do salvamento
+    let mut editando = use_signal(|| false);
+    let mut texto = use_signal(String::new);
+    let mut edicoes = use_signal(|| 0u64);
+    let mut status_edicao = use_signal(String::new);
+    let project_dir = project_index::project_dir(&props.project_name);
+    let personalizacao_docx = docx_relatorio::descricao_personalizacao(&project_dir);
+    // Templates disponíveis (embutido, globais e do projeto) e o escolhido no projeto
+    let templates = templates_relatorio::listar(&project_dir);
+    let mut template_escolhido = use_signal({
+        let project_dir = project_dir.clone();
+        move || templates_relatorio::nome_selecionado(&project_dir)
+    });
+    let mut template_estrito = use_signal({
+        let project_dir = project_dir.clone();
+        move || ProjectSettings::load(&project_dir).map(|s| s.template_estrito).unwrap_or_default()
+    });
+    let mut status_template = use_signal(String::new);
+    let status_exportacao = use_signal(String::new);
+    let secoes_preservadas = edicao_relatorio::extrair_secoes(&texto())
+        .into_keys()
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    // Uma linha por versão, da mais recente para a mais antiga, com as entradas que mudaram
+    let linhas_historico: Vec<(u32, String, String)> = {
+        let historico = historico.read();
+        historico.versoes
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, versao)| {
+                let alteracoes = match i.checked_sub(1).map(|anterior| &historico.versoes[anterior]) {
+                    _ if versao.editada => "edição manual no app".to_string(),
+                    None if versao.entradas.is_empty() => "relatório anterior ao histórico".to_string(),
+                    None => "primeira versão".to_string(),
+                    Some(anterior) if anterior.entradas.is_empty() => "—".to_string(),
+                    Some(anterior) => {
+                        let alteradas = versoes_relatorio::entradas_alteradas(&anterior.entradas, &versao.entradas);
+                        if alteradas.is_empty() {
+                            "regenerado sem alterações nas entradas".to_string()
+                        } else {
+                            alteradas.iter().map(|e| versoes_relatorio::rotulo_entrada(e)).collect::<Vec<_>>().join(", ")
+                        }
+                    }
+                };
+                (versao.numero, versao.gerada_em.clone(), alteracoes)
+            })
+            .collect()
+    };
+    // Comparação entre duas versões escolhidas: (classe CSS, prefixo, linha)
+    let diff: Option<Result<Vec<(&str, &str, String)>, String>> = match (versao_a(), versao_b()) {
+        (Some(a), Some(b)) => {
+            let historico = historico.read();
+            Some(
+                historico.ler(&report_output_dir, a)
+                    .and_then(|antigo| Ok((antigo, historico.ler(&report_output_dir, b)?)))
+                    .map(|(antigo, novo)| {
+                        versoes_relatorio::diff_linhas(&antigo, &novo)
+                            .into_iter()
+                            .map(|linha| match linha {
+                                LinhaDiff::Igual(texto) => ("igual", " ", texto),
+                                LinhaDiff::Removida(texto) => ("removida", "-", texto),
+                                LinhaDiff::Adicionada(texto) => ("adicionada", "+", texto),
+                            })
+                            .collect()
+                    })
+                    .map_err(|e| format!("{:#}", e)),
+            )
+        }
+        _ => None,
+    };
+    let (linhas_diff, erro_diff) = match diff {
+        Some(Ok(linhas)) => (Some(linhas), None),
+        Some(Err(erro)) => (None, Some(erro)),
+        None => (None, None),
+    };
+
     rsx! {
         document::Link {
             rel: "stylesheet",
@@ -219,59 +512,264 @@ pub fn ReportView(props: ReportViewProps) -> Element {
                     }
                     button {
                         onclick: {
-                            let path_clone = report_md_filepath.clone();
+                            let (project_name, building_name) = (props.project_name.clone(), props.building_name.clone());
+                            let path = report_md_filepath.clone();
+                            move |_| iniciar_exportacao(project_name.clone(), building_name.clone(), path.clone(), "MD", status_exportacao)
+                        },
+                        "Exportar em MD"
+                    }
+                    button {
+                        onclick: {
+                            let (project_name, building_name) = (props.project_name.clone(), props.building_name.clone());
+                            let path = report_md_filepath.clone();
+                            move |_| iniciar_exportacao(project_name.clone(), building_name.clone(), path.clone(), "PDF", status_exportacao)
+                        },
+                        "Exportar em PDF"
+                    },
+                    button {
+                        onclick: {
+                            let (project_name, building_name) = (props.project_name.clone(), props.building_name.clone());
+                            let path = report_md_filepath.clone();
+                            move |_| iniciar_exportacao(project_name.clone(), building_name.clone(), path.clone(), "DOCX", status_exportacao)
+                        },
+                        title: "Aparência do DOCX: {personalizacao_docx} (reference.docx e logo.png na pasta do projeto ou em src/Template)",
+                        "Exportar em DOCX"
+                    }
+                    button {
+                        onclick: {
+                            let (project_name, building_name) = (props.project_name.clone(), props.building_name.clone());
+                            let path = report_md_filepath.clone();
+                            move |_| iniciar_exportacao(project_name.clone(), building_name.clone(), path.clone(), "HTML", status_exportacao)
+                        },
+                        "Exportar em HTML"
+                    }
+                    button {
+                        onclick: {
+                            let path = report_md_filepath.clone();
                             move |_| {
-                                if path_clone.exists() {
-                                    match std::fs::read_to_string(&path_clone) {
-                                        Ok(content) => export(&content, "MD"),
-                                        Err(e) => eprintln!("Erro ao ler arquivo MD para exportação: {}", e),
+                                if editando() {
+                                    if let Err(e) = std::fs::write(&path, texto()) {
+                                        status_edicao.set(format!("Erro ao salvar: {}", e));
+                                        return;
                                     }
+                                    conteudo.set(render_markdown(&texto()));
+                                    editando.set(false);
                                 } else {
-                                     eprintln!("Arquivo de relatório MD não encontrado para exportação: {:?}", path_clone);
+                                    texto.set(std::fs::read_to_string(&path).unwrap_or_default());
+                                    status_edicao.set(String::new());
+                                    editando.set(true);
                                 }
                             }
                         },
-                        "Exportar em MD"
+                        if editando() { "Concluir edição" } else { "Editar relatório" }
                     }
                     button {
                         onclick: {
-                            let path_clone = report_md_filepath.clone();
+                            let (project_name, building_name) = (props.project_name.clone(), props.building_name.clone());
+                            let (report_output_dir, nome_base) = (report_output_dir.clone(), nome_base.clone());
+                            let path = report_md_filepath.clone();
                             move |_| {
-                                if path_clone.exists() {
-                                    match std::fs::read_to_string(&path_clone) {
-                                        Ok(content) => export(&content, "PDF"),
-                                        Err(e) => eprintln!("Erro ao ler arquivo MD para exportação: {}", e),
+                                // Grava o que está no editor para as seções marcadas passarem para a nova versão
+                                if editando() {
+                                    if let Err(e) = std::fs::write(&path, texto()) {
+                                        status_edicao.set(format!("Erro ao salvar: {}", e));
+                                        return;
                                     }
-                                } else {
-                                     eprintln!("Arquivo de relatório MD não encontrado para exportação: {:?}", path_clone);
+                                }
+                                conteudo.set(get_report(&project_name, &building_name, true).unwrap_or_else(erro_html));
+                                historico.set(HistoricoRelatorio::load(&report_output_dir, &nome_base).unwrap_or_default());
+                                if editando() {
+                                    texto.set(std::fs::read_to_string(&path).unwrap_or_default());
+                                    status_edicao.set("Relatório regenerado; seções preservadas mantidas.".to_string());
                                 }
                             }
                         },
-                        "Exportar em PDF"
-                    },
+                        "Regenerar relatório"
+                    }
+                    span {
+                        class: "editor-status",
+                        "PDF e DOCX usam o layout do app e, do Markdown, só as seções marcadas; o template escolhido e as demais edições valem para MD e HTML."
+                    }
+                    span { class: "editor-status", "{status_exportacao}" }
+                }
+                div {
+                    class: "templates",
+                    "Template do relatório "
+                    select {
+                        value: "{template_escolhido}",
+                        onchange: {
+                            let (project_name, building_name) = (props.project_name.clone(), props.building_name.clone());
+                            let (report_output_dir, nome_base) = (report_output_dir.clone(), nome_base.clone());
+                            let project_dir = project_dir.clone();
+                            move |e: FormEvent| {
+                                let nome = e.value();
+                                if let Err(e) = templates_relatorio::escolher(&project_dir, &nome) {
+                                    status_template.set(format!("Erro ao salvar o template escolhido: {:#}", e));
+                                    return;
+                                }
+                                template_escolhido.set(nome);
+                                status_template.set(String::new());
+                                conteudo.set(get_report(&project_name, &building_name, false).unwrap_or_else(erro_html));
+                                historico.set(HistoricoRelatorio::load(&report_output_dir, &nome_base).unwrap_or_default());
+                            }
+                        },
+                        for template in templates.iter() {
+                            option {
+                                key: "{template.nome}",
+                                value: "{template.nome}",
+                                "{template.nome} ({template.origem.descricao()})"
+                            }
+                        }
+                    }
                     button {
                         onclick: {
-                            let path_clone = report_md_filepath.clone();
+                            let project_dir = project_dir.clone();
                             move |_| {
-                                if path_clone.exists() {
-                                    match std::fs::read_to_string(&path_clone) {
-                                        Ok(content) => export(&content, "DOCX"),
-                                        Err(e) => eprintln!("Erro ao ler arquivo MD para exportação: {}", e),
-                                    }
-                                } else {
-                                     eprintln!("Arquivo de relatório MD não encontrado para exportação: {:?}", path_clone);
+                                let nome = template_escolhido();
+                                let copia = templates_relatorio::listar(&project_dir)
+                                    .into_iter()
+                                    .find(|t| t.nome == nome)
+                                    .ok_or_else(|| anyhow::anyhow!("Template '{}' não encontrado", nome))
+                                    .and_then(|template| templates_relatorio::copiar_para_projeto(&project_dir, &template));
+                                match copia {
+                                    Ok(caminho) => match open::that(&caminho) {
+                                        Ok(()) => status_template.set(format!("Editando {}; salve o arquivo e regenere o relatório.", caminho.display())),
+                                        Err(e) => status_template.set(format!("Template copiado para {} (não foi possível abrir: {})", caminho.display(), e)),
+                                    },
+                                    Err(e) => status_template.set(format!("Erro ao copiar o template: {:#}", e)),
                                 }
                             }
                         },
-                        "Exportar em DOCX"
+                        title: "Copia o template para a pasta templates/ do projeto e abre no editor padrão",
+                        "Personalizar template"
+                    }
+                    label {
+                        title: "Campo do template sem valor nos dados impede a geração do relatório, em vez de sair em branco",
+                        input {
+                            r#type: "checkbox",
+                            checked: template_estrito(),
+                            onchange: {
+                                let (project_name, building_name) = (props.project_name.clone(), props.building_name.clone());
+                                let (report_output_dir, nome_base) = (report_output_dir.clone(), nome_base.clone());
+                                let project_dir = project_dir.clone();
+                                move |e: FormEvent| {
+                                    let estrito = e.checked();
+                                    let salvo = ProjectSettings::load(&project_dir).and_then(|mut settings| {
+                                        settings.template_estrito = estrito;
+                                        settings.save(&project_dir)
+                                    });
+                                    if let Err(e) = salvo {
+                                        status_template.set(format!("Erro ao salvar o modo estrito: {:#}", e));
+                                        return;
+                                    }
+                                    template_estrito.set(estrito);
+                                    status_template.set(String::new());
+                                    // As configurações são entrada do relatório: a mudança gera uma nova versão
+                                    conteudo.set(get_report(&project_name, &building_name, false).unwrap_or_else(erro_html));
+                                    historico.set(HistoricoRelatorio::load(&report_output_dir, &nome_base).unwrap_or_default());
+                                }
+                            }
+                        }
+                        " Modo estrito"
+                    }
+                    span { class: "editor-status", "{status_template}" }
+                }
+                if editando() {
+                    div {
+                        class: "editor",
+                        textarea {
+                            class: "editor-texto",
+                            value: "{texto}",
+                            oninput: {
+                                let path = report_md_filepath.clone();
+                                move |e: FormEvent| {
+                                    texto.set(e.value());
+                                    edicoes += 1;
+                                    status_edicao.set("Alterações não salvas".to_string());
+                                    let (path, edicao) = (path.clone(), edicoes());
+                                    spawn(async move {
+                                        tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+                                        // Só a última alteração de uma sequência grava
+                                        if edicoes() != edicao {
+                                            return;
+                                        }
+                                        match std::fs::write(&path, texto()) {
+                                            Ok(()) => {
+                                                conteudo.set(render_markdown(&texto()));
+                                                status_edicao.set(format!("Salvo automaticamente às {}", Local::now().format("%H:%M:%S")));
+                                            }
+                                            Err(e) => status_edicao.set(format!("Erro ao salvar: {}", e)),
+                                        }
+                                    });
+                                }
+                            }
+                        }
+                        div {
+                            class: "text-content editor-preview",
+                            dangerous_inner_html: render_markdown(&texto())
+                        }
+                    }
+                    p {
+                        class: "editor-status",
+                        "{status_edicao} · Trechos preservados ao regenerar: {secoes_preservadas}. As demais alterações ficam no histórico e são substituídas na próxima geração."
+                    }
+                } else {
+                    div {
+                        class: "text-viewer",
+                        div {
+                            class: "text-content",
+                            dangerous_inner_html: conteudo()
+                        }
                     }
                 }
                 div {
-                    class: "text-viewer",
+                    class: "historico",
+                    h2 { "Versões do relatório" }
+                    table {
+                        tr {
+                            th { "Versão" }
+                            th { "Gerada em" }
+                            th { "Entradas alteradas" }
+                        }
+                        for (numero, gerada_em, alteracoes) in linhas_historico.iter() {
+                            tr { key: "{numero}",
+                                td { "v{numero}" }
+                                td { "{gerada_em}" }
+                                td { "{alteracoes}" }
+                            }
+                        }
+                    }
                     div {
-                        class: "text-content",
-                        dangerous_inner_html: get_report(&props.project_name, &props.building_name)
-                            .unwrap_or_else(|e| format!("<h1>Erro ao gerar relatório</h1><p>Detalhes: {}</p><p>Verifique o console para mais informações sobre caminhos de arquivos.</p>", e))
+                        class: "comparacao",
+                        "Comparar "
+                        select {
+                            value: versao_a().map(|v| v.to_string()).unwrap_or_default(),
+                            onchange: move |e| versao_a.set(e.value().parse().ok()),
+                            option { value: "", "—" }
+                            for (numero, gerada_em, _) in linhas_historico.iter() {
+                                option { key: "{numero}", value: "{numero}", "v{numero} ({gerada_em})" }
+                            }
+                        }
+                        " com "
+                        select {
+                            value: versao_b().map(|v| v.to_string()).unwrap_or_default(),
+                            onchange: move |e| versao_b.set(e.value().parse().ok()),
+                            option { value: "", "—" }
+                            for (numero, gerada_em, _) in linhas_historico.iter() {
+                                option { key: "{numero}", value: "{numero}", "v{numero} ({gerada_em})" }
+                            }
+                        }
+                    }
+                    if let Some(linhas) = linhas_diff {
+                        pre {
+                            class: "diff",
+                            for (classe, prefixo, texto) in linhas {
+                                div { class: "{classe}", "{prefixo} {texto}" }
+                            }
+                        }
+                    }
+                    if let Some(erro) = erro_diff {
+                        p { class: "erro", "{erro}" }
                     }
                 }
             }
@@ -1,129 +1,384 @@
 use handlebars::Handlebars;
 use serde_json::Value;
-use crate::report_structures::{ReportData, Faceta, Fissura};
-use std::path::{Path, PathBuf};
+use crate::report_structures::{
+    ReportData, Faceta, Fissura, GraficoRelatorio, ContextoRelatorio, MetodologiaRelatorio, ResumoClasse,
+    ResumoPredio, QuantidadeClasse, ClassificacaoRelatorio, FissuraRelatorio,
+};
+use crate::project_index::{ProjectIndex, caminho_relativo_imagens};
+use crate::image_processor::Predio;
+use crate::manual_processor::ImageAnalysisResult;
+use crate::manifesto::ManifestoProjeto;
+use crate::taxonomia::Taxonomia;
+use crate::deteccoes::{self, DETECTION_FILE_NAME};
+use crate::anotacoes::normalizar_classe;
+use crate::revisao::StatusRevisao;
+use crate::graficos::{self, SerieGrafico};
+use crate::templates_relatorio;
+use std::collections::HashMap;
+use std::path::Path;
 use std::fs::{self, File};
 use std::io::Write;
-use chrono::Local;
+use chrono::{DateTime, Local};
+use anyhow::Context;
 
-// This function simulates calling the C++ model and processing images
-// In a real scenario, this would involve FFI or inter-process communication
-fn simulate_cpp_model_and_image_processing(
-    images_dir: &Path,
-    project_name: &str,
-    building_name: &str,
-) -> Result<(Vec<Faceta>, Vec<Fissura>), Box<dyn std::error::Error>> {
+// Facetas (fachadas do índice) e fissuras de um prédio a partir dos resultados já filtrados por
+// deteccoes::carregar (exclusões, política, revisão humana e medidas)
+pub fn facetas_e_fissuras(
+    predio: &Predio,
+    index: &ProjectIndex,
+    resultados: &[ImageAnalysisResult],
+    taxonomia: &Taxonomia,
+) -> (Vec<Faceta>, Vec<Fissura>) {
+    let mut facetas: Vec<Faceta> = predio
+        .fachadas
+        .iter()
+        .map(|(pasta, fachada)| Faceta {
+            id: pasta.clone(),
+            orientacao: fachada.nome.clone(),
+            qtd_rachaduras: 0,
+            qtd_imagens: fachada.imagens.iter().filter(|im| !index.excluida(&im.path)).count() as u32,
+            observacoes: String::new(),
+        })
+        .collect();
+    let mut imagens_com_fissura = vec![0u32; facetas.len()];
     let mut fissuras = Vec::new();
-    let mut facetas = Vec::new();
-    let mut faceta_id_counter = 1;
-    let mut fissura_counter = 1;
-
-    // Mock facetas - let's create two for now
-    let faceta1_id = format!("F{:02}", faceta_id_counter);
-    facetas.push(Faceta {
-        id: faceta1_id.clone(),
-        orientacao: "Norte".to_string(),
-        qtd_rachaduras: 0, // Will be updated based on found fissuras
-        observacoes: format!("Observações da faceta {} do {}", faceta1_id, building_name),
-    });
-    faceta_id_counter += 1;
-
-    let faceta2_id = format!("F{:02}", faceta_id_counter);
-    facetas.push(Faceta {
-        id: faceta2_id.clone(),
-        orientacao: "Leste".to_string(),
-        qtd_rachaduras: 0, // Will be updated based on found fissuras
-        observacoes: format!("Observações da faceta {} do {}", faceta2_id, building_name),
-    });
-
-    if images_dir.exists() && images_dir.is_dir() {
-        for entry in fs::read_dir(images_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                // Simulate data for each image found
-                let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
-                let image_path_in_report = format!("images/{}", file_name);
-
-                // Assign to a faceta (e.g., alternating or based on some logic)
-                let target_faceta_id = if fissura_counter % 2 == 0 { &faceta2_id } else { &faceta1_id };
-
-                fissuras.push(Fissura {
-                    faceta_id: target_faceta_id.clone(),
-                    localizacao: format!("Localização simulada {} ({})", fissura_counter, file_name),
-                    classificacao: if fissura_counter % 3 == 0 {
-                        "Estrutural".to_string()
-                    } else if fissura_counter % 3 == 1 {
-                        "Térmica".to_string()
-                    } else {
-                        "Retração plástica".to_string()
-                    },
-                    descricao: format!("Descrição simulada da fissura {} para a imagem {}.", fissura_counter, file_name),
-                    caminho_imagem: image_path_in_report,
-                });
-
-                // Update qtd_rachaduras for the faceta
-                if let Some(faceta) = facetas.iter_mut().find(|f| f.id == *target_faceta_id) {
-                    faceta.qtd_rachaduras += 1;
-                }
-                fissura_counter += 1;
-            }
+
+    for resultado in resultados {
+        let path = Path::new(&resultado.path);
+        let Some((predio_imagem, pasta, fachada)) = index.localizar(path) else { continue };
+        if predio_imagem.id != predio.id || resultado.fissura.is_empty() {
+            continue;
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let caminho_imagem = caminho_relativo_imagens(path)
+            .map(|rel| format!("images/{}", rel.to_string_lossy().replace('\\', "/")))
+            .unwrap_or_else(|| resultado.path.clone());
+
+        for fissura in &resultado.fissura {
+            fissuras.push(Fissura {
+                id: format!("FIS-{:03}", fissuras.len() + 1),
+                faceta_id: pasta.to_string(),
+                localizacao: format!("{} / {} / {}", predio.nome, fachada.nome, file_name),
+                classificacao: taxonomia.rotulo(&fissura.name),
+                classe: fissura.name.clone(),
+                confianca: fissura.confidence,
+                bbox: fissura.bbox,
+                orientacao: fissura.geometria.as_ref().map(|g| g.orientacao_descricao()),
+                geometria: fissura.geometria.clone(),
+                status_revisao: fissura.revisao.as_ref().map(|r| r.descricao()).unwrap_or_else(|| "Não revisada".to_string()),
+                revisada: fissura.revisao.as_ref().is_some_and(|r| r.status != StatusRevisao::Pendente),
+                nota_revisor: fissura.revisao.as_ref().and_then(|r| r.nota.clone()),
+                classificacao_original: fissura.revisao.as_ref().and_then(|r| r.classe_original.as_deref()).map(|c| taxonomia.rotulo(c)),
+                descricao: taxonomia.classe(&fissura.name).map(|c| c.descricao.clone()).unwrap_or_default(),
+                fachada: fachada.nome.clone(),
+                caminho_imagem: caminho_imagem.clone(),
+                caminho_resultado: resultado.path.clone(),
+            });
+        }
+        if let Some(i) = facetas.iter().position(|f| f.id == pasta) {
+            facetas[i].qtd_rachaduras += resultado.fissura.len() as u32;
+            imagens_com_fissura[i] += 1;
         }
     }
 
-    Ok((facetas, fissuras))
+    for (faceta, com_fissura) in facetas.iter_mut().zip(imagens_com_fissura) {
+        faceta.observacoes = format!("{} de {} imagem(ns) com fissura", com_fissura, faceta.qtd_imagens);
+    }
+    (facetas, fissuras)
 }
 
-pub fn generate_json_report(
-    base_projects_dir_str: &str, // e.g., "src/app-rust/Projects"
-    project_name: &str,          // e.g., "Galpão_Logístico_XPTO"
-    building_name: &str,         // e.g., "Galpão_3"
-) -> Result<ReportData, Box<dyn std::error::Error>> {
-    let project_root = Path::new(base_projects_dir_str).join(project_name);
-    let images_dir = project_root.join("images");
-
-    println!("Checking for images in: {:?}", images_dir);
-
-    let (facetas, fissuras) = simulate_cpp_model_and_image_processing(&images_dir, project_name, building_name)?;
-
-    let current_date = Local::now().format("%Y-%m-%d").to_string();
-
-    let report_data = ReportData {
-        nome_projeto: project_name.to_string(),
-        data_analise: current_date,
-        nome_responsavel: "Eng. Responsável Simulado".to_string(),
-        nome_predio: building_name.to_string(),
-        endereco_predio: "Endereço Simulado, 123".to_string(),
-        numero_andares: 1, // Mock data
-        ano_construcao: 2000, // Mock data
-        tipo_estrutura: "Tipo de Estrutura Simulada".to_string(),
-        observacoes_gerais: format!("Observações gerais simuladas para o {} - {}.", project_name, building_name),
-        facetas,
-        fissuras,
-        conclusao_geral: "Conclusão geral simulada. Análise requer especialista.".to_string(),
-        recomendacoes: "Recomendações simuladas. Verificar todas as fissuras.".to_string(),
-        funcao_responsavel: "Engenheiro de Simulação".to_string(),
-        nome_empresa: "Simulações & Análises Ltda.".to_string(),
+// Gráficos da seção de estatísticas: distribuição por classe, fissuras por prédio (quando o relatório
+// cobre mais de um) e por fachada de cada prédio. Cada um é salvo em `pasta` como SVG estático e PNG,
+// com nomes começando por `nome_base`. Sem fissuras consideradas não há gráficos.
+pub fn graficos_relatorio(
+    predios: &[&Predio],
+    index: &ProjectIndex,
+    resultados: &[ImageAnalysisResult],
+    taxonomia: &Taxonomia,
+    pasta: &Path,
+    nome_base: &str,
+) -> anyhow::Result<Vec<GraficoRelatorio>> {
+    // Só as classes com fissuras no relatório, na ordem da taxonomia
+    let presentes: Vec<String> = deteccoes::classes_presentes(resultados).iter().map(|c| normalizar_classe(c)).collect();
+    let chaves: Vec<String> = taxonomia.com_extras(presentes.clone()).into_iter().filter(|c| presentes.contains(c)).collect();
+    if chaves.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut total = SerieGrafico::nova(String::new(), chaves.len());
+    let mut por_predio: Vec<SerieGrafico> = predios.iter().map(|p| SerieGrafico::nova(p.nome.clone(), chaves.len())).collect();
+    let mut por_fachada: Vec<Vec<(&str, SerieGrafico)>> = predios
+        .iter()
+        .map(|p| p.fachadas.iter().map(|(pasta, f)| (pasta.as_str(), SerieGrafico::nova(f.nome.clone(), chaves.len()))).collect())
+        .collect();
+    for resultado in resultados {
+        total.somar(&chaves, resultado);
+        let Some((predio, pasta_fachada, _)) = index.localizar(Path::new(&resultado.path)) else { continue };
+        let Some(i) = predios.iter().position(|p| p.id == predio.id) else { continue };
+        por_predio[i].somar(&chaves, resultado);
+        if let Some((_, serie)) = por_fachada[i].iter_mut().find(|(pasta, _)| *pasta == pasta_fachada) {
+            serie.somar(&chaves, resultado);
+        }
+    }
+    let mut classes = graficos::classes_grafico(taxonomia, &chaves);
+    for (classe, contagem) in classes.iter_mut().zip(&total.contagens) {
+        classe.total = *contagem;
+    }
+
+    fs::create_dir_all(pasta).with_context(|| format!("Falha ao criar {}", pasta.display()))?;
+    // Caminhos absolutos, como os das imagens das fissuras, para o relatório abrir de qualquer lugar
+    let pasta = if pasta.is_absolute() {
+        pasta.to_path_buf()
+    } else {
+        std::env::current_dir().context("Falha ao ler a pasta atual")?.join(pasta)
+    };
+    let mut gerados = Vec::new();
+    let mut salvar = |sufixo: &str, titulo: String, svg: String, png: Vec<u8>| -> anyhow::Result<()> {
+        let caminho_svg = pasta.join(format!("{}-{}.svg", nome_base, sufixo));
+        let caminho_png = caminho_svg.with_extension("png");
+        fs::write(&caminho_svg, svg).with_context(|| format!("Falha ao salvar {}", caminho_svg.display()))?;
+        fs::write(&caminho_png, png).with_context(|| format!("Falha ao salvar {}", caminho_png.display()))?;
+        gerados.push(GraficoRelatorio {
+            titulo,
+            imagem: caminho_png.to_string_lossy().replace('\\', "/"),
+            svg: caminho_svg.to_string_lossy().replace('\\', "/"),
+        });
+        Ok(())
     };
 
-    let report_file_name = format!("Dados-{}-{}.json", project_name, building_name);
-    let report_file_path = project_root.join(report_file_name.clone());
+    let abrangencia = match predios {
+        [predio] => predio.nome.clone(),
+        _ => "Todos os Prédios".to_string(),
+    };
+    salvar(
+        "distribuicao",
+        format!("Distribuição por Classe — {}", abrangencia),
+        graficos::svg_donut(&classes, graficos::Estilo::Relatorio),
+        graficos::png_donut(&classes)?,
+    )?;
+    if predios.len() > 1 {
+        salvar(
+            "predios",
+            "Fissuras por Prédio".to_string(),
+            graficos::svg_barras(&por_predio, &classes, graficos::Estilo::Relatorio),
+            graficos::png_barras(&por_predio, &classes)?,
+        )?;
+    }
+    for (predio, fachadas) in predios.iter().zip(por_fachada) {
+        let series: Vec<SerieGrafico> = fachadas.into_iter().map(|(_, serie)| serie).collect();
+        if series.iter().all(|s| s.contagens.iter().all(|c| *c == 0)) {
+            continue;
+        }
+        salvar(
+            &format!("fachadas-{}", predio.id),
+            format!("Fissuras por Fachada — {}", predio.nome),
+            graficos::svg_barras(&series, &classes, graficos::Estilo::Relatorio),
+            graficos::png_barras(&series, &classes)?,
+        )?;
+    }
+    Ok(gerados)
+}
 
-    let json_string = serde_json::to_string_pretty(&report_data)?;
+// A data da análise é a da última detecção gravada no projeto
+pub fn data_analise(project_dir: &Path) -> String {
+    fs::metadata(project_dir.join(DETECTION_FILE_NAME))
+        .and_then(|m| m.modified())
+        .map(DateTime::<Local>::from)
+        .unwrap_or_else(|_| Local::now())
+        .format("%Y-%m-%d")
+        .to_string()
+}
 
-    let mut file = File::create(&report_file_path)?;
+// Dados de um prédio para o relatório e para a exportação em JSON
+pub fn dados_relatorio(
+    project_name: &str,
+    manifesto: &ManifestoProjeto,
+    predio: &Predio,
+    index: &ProjectIndex,
+    resultados: &[ImageAnalysisResult],
+    taxonomia: &Taxonomia,
+    data_analise: String,
+) -> ReportData {
+    let (facetas, fissuras) = facetas_e_fissuras(predio, index, resultados, taxonomia);
+    ReportData {
+        nome_projeto: if manifesto.nome.is_empty() { project_name.to_string() } else { manifesto.nome.clone() },
+        data_analise,
+        nome_responsavel: manifesto.responsavel.clone(),
+        nome_predio: predio.nome.clone(),
+        endereco_predio: predio.endereco.clone(),
+        numero_andares: None, // Ainda não coletado pelo app
+        ano_construcao: manifesto.ano_construcao,
+        tipo_estrutura: manifesto.tipo_estrutura.clone(),
+        observacoes_gerais: manifesto.observacoes.clone(),
+        modelo: resultados.first().and_then(|r| r.modelo.clone()),
+        facetas,
+        fissuras,
+        conclusao_geral: String::new(),
+        recomendacoes: String::new(),
+        funcao_responsavel: String::new(),
+        nome_empresa: manifesto.empresa.clone(),
+    }
+}
+
+// Grava os dados em Dados-<projeto>-<prédio>.json na pasta do projeto
+pub fn salvar_dados_json(project_root: &Path, project_name: &str, building_name: &str, report_data: &ReportData) -> anyhow::Result<()> {
+    let report_file_path = project_root.join(format!("Dados-{}-{}.json", project_name, building_name));
+    let json_string = serde_json::to_string_pretty(report_data)?;
+    let mut file = File::create(&report_file_path).with_context(|| format!("Falha ao criar {}", report_file_path.display()))?;
     file.write_all(json_string.as_bytes())?;
+    Ok(())
+}
+
+// Texto para campos do cabeçalho que o projeto não preencheu
+fn ou_nao_informado(texto: &str) -> String {
+    if texto.trim().is_empty() { "Não informado".to_string() } else { texto.to_string() }
+}
+
+// Quantidade de fissuras por classe (chave normalizada), na ordem em que aparecem
+fn contar_classes<'a>(fissuras: impl Iterator<Item = &'a Fissura>) -> Vec<(String, usize)> {
+    let mut classes: Vec<(String, usize)> = Vec::new();
+    for fissura in fissuras {
+        let chave = normalizar_classe(&fissura.classe);
+        match classes.iter_mut().find(|(classe, _)| *classe == chave) {
+            Some((_, quantidade)) => *quantidade += 1,
+            None => classes.push((chave, 1)),
+        }
+    }
+    classes
+}
+
+fn indice_severidade(classes: &[(String, usize)], taxonomia: &Taxonomia) -> f64 {
+    classes.iter().map(|(chave, quantidade)| taxonomia.peso(chave) * *quantidade as f64).sum()
+}
+
+// Contexto do template a partir dos dados de cada prédio coberto: um só, ou todos no resumo do
+// projeto (`resumo_projeto`), quando facetas e fissuras levam o nome do prédio. `metodologia` vem com
+// os filtros do projeto e recebe aqui as contagens de revisão; `classificacao_imagens` é o texto da
+// classe prevista por caminho de imagem nos resultados.
+pub fn contexto_relatorio(
+    dados: &[ReportData],
+    resumo_projeto: bool,
+    taxonomia: &Taxonomia,
+    mut metodologia: MetodologiaRelatorio,
+    classificacao: Option<ClassificacaoRelatorio>,
+    classificacao_imagens: &HashMap<String, String>,
+    graficos: Vec<GraficoRelatorio>,
+) -> anyhow::Result<ContextoRelatorio> {
+    let primeiro = dados.first().context("Nenhum prédio no índice do projeto para o relatório")?;
 
-    println!("Report generated successfully at: {:?}", report_file_path);
-    Ok(report_data)
+    let mut facetas = Vec::new();
+    let mut fissuras = Vec::new();
+    let mut predios = Vec::new();
+    for predio in dados {
+        for faceta in &predio.facetas {
+            let mut faceta = faceta.clone();
+            if resumo_projeto {
+                faceta.id = format!("{} / {}", predio.nome_predio, faceta.id);
+            }
+            facetas.push(faceta);
+        }
+        for fissura in &predio.fissuras {
+            let geometria = fissura.geometria.as_ref();
+            fissuras.push(FissuraRelatorio {
+                id_fissura: format!("FIS-{:03}", fissuras.len() + 1),
+                faceta_id: if resumo_projeto { format!("{} / {}", predio.nome_predio, fissura.fachada) } else { fissura.fachada.clone() },
+                orientacao: fissura.orientacao.clone().unwrap_or_else(|| "Não medida".to_string()),
+                comprimento: geometria.map(|g| g.comprimento_descricao()),
+                largura: geometria.map(|g| g.largura_descricao()),
+                classe_abertura: geometria.and_then(|g| g.classe_abertura).map(|c| c.descricao().to_string()),
+                classificacao: fissura.classificacao.clone(),
+                classificacao_original: fissura.classificacao_original.clone(),
+                classe: fissura.classe.clone(),
+                confianca: fissura.confianca,
+                classificacao_imagem: classificacao_imagens.get(&fissura.caminho_resultado).cloned(),
+                status_revisao: fissura.status_revisao.clone(),
+                revisada: fissura.revisada,
+                nota_revisor: fissura.nota_revisor.clone(),
+                observacoes: predio.facetas
+                    .iter()
+                    .find(|f| f.id == fissura.faceta_id)
+                    .map(|f| f.observacoes.clone())
+                    .unwrap_or_default(),
+                caminho_imagem: fissura.caminho_resultado.clone(),
+                bbox: fissura.bbox,
+            });
+        }
+        if resumo_projeto {
+            let classes = contar_classes(predio.fissuras.iter());
+            predios.push(ResumoPredio {
+                nome: predio.nome_predio.clone(),
+                endereco: ou_nao_informado(predio.endereco_predio.as_deref().unwrap_or_default()),
+                imagens: predio.facetas.iter().map(|f| f.qtd_imagens).sum(),
+                fissuras: predio.fissuras.len(),
+                indice_severidade: format!("{:.1}", indice_severidade(&classes, taxonomia)),
+                classes: classes
+                    .iter()
+                    .map(|(chave, quantidade)| QuantidadeClasse { rotulo: taxonomia.rotulo(chave), quantidade: *quantidade })
+                    .collect(),
+            });
+        }
+    }
+
+    // Resumo por classe na ordem da taxonomia; o índice de severidade soma o peso de cada fissura
+    let contagem = contar_classes(dados.iter().flat_map(|d| d.fissuras.iter()));
+    let resumo_classes: Vec<ResumoClasse> = taxonomia
+        .com_extras(contagem.iter().map(|(classe, _)| classe.clone()))
+        .into_iter()
+        .filter_map(|chave| {
+            let (_, quantidade) = contagem.iter().find(|(classe, _)| *classe == chave)?;
+            Some(ResumoClasse {
+                rotulo: taxonomia.rotulo(&chave),
+                descricao: taxonomia.classe(&chave).map(|c| c.descricao.clone()).unwrap_or_default(),
+                quantidade: *quantidade,
+                peso: taxonomia.peso(&chave),
+            })
+        })
+        .collect();
+    metodologia.fissuras_revisadas = fissuras.iter().filter(|f| f.revisada).count();
+    metodologia.fissuras_total = fissuras.len();
+
+    let (nome_predio, endereco_predio) = if resumo_projeto {
+        (format!("Todos os prédios ({})", dados.len()), "Ver resumo por prédio".to_string())
+    } else {
+        (primeiro.nome_predio.clone(), ou_nao_informado(primeiro.endereco_predio.as_deref().unwrap_or_default()))
+    };
+    Ok(ContextoRelatorio {
+        nome_projeto: primeiro.nome_projeto.clone(),
+        data_analise: primeiro.data_analise.clone(),
+        data_geracao: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        nome_responsavel: ou_nao_informado(&primeiro.nome_responsavel),
+        funcao_responsavel: ou_nao_informado(&primeiro.funcao_responsavel),
+        nome_empresa: ou_nao_informado(&primeiro.nome_empresa),
+        nome_predio,
+        endereco_predio,
+        numero_andares: primeiro.numero_andares.map(|n| n.to_string()).unwrap_or_else(|| "Não informado".to_string()),
+        ano_construcao: primeiro.ano_construcao.map(|a| a.to_string()).unwrap_or_else(|| "Não informado".to_string()),
+        tipo_estrutura: ou_nao_informado(&primeiro.tipo_estrutura),
+        observacoes_gerais: ou_nao_informado(&primeiro.observacoes_gerais),
+        metodologia,
+        facetas,
+        indice_severidade: format!("{:.1}", indice_severidade(&contagem, taxonomia)),
+        resumo_classes,
+        predios,
+        classificacao,
+        graficos,
+        fissuras,
+        conclusao_geral: primeiro.conclusao_geral.clone(),
+        recomendacoes: primeiro.recomendacoes.clone(),
+    })
 }
 
-// The original generate_report function using Handlebars. Keep or remove based on usage.
+// Renderiza o template do relatório com os helpers de templates_relatorio.rs. No modo estrito uma
+// variável que não existe nos dados é erro de renderização em vez de texto em branco.
 pub fn generate_report(
     template_text: &str,
-    origin_data: &Value
+    origin_data: &Value,
+    taxonomia: &Taxonomia,
+    estrito: bool,
 ) -> Result<String, handlebars::RenderError> {
     let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(estrito);
+    templates_relatorio::registrar_helpers(&mut handlebars, taxonomia.clone());
 
     handlebars.register_template_string("t1", template_text)?;
 
@@ -1,21 +1,49 @@
 use serde::{Serialize, Deserialize};
+use crate::geometria::GeometriaFissura;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Faceta {
-    pub id: String,
-    pub orientacao: String,
+    pub id: String, // Pasta da fachada no índice (ex: "fachada-Norte")
+    pub orientacao: String, // Nome da fachada no índice (ex: "Norte")
     #[serde(rename = "qtd_rachaduras")]
     pub qtd_rachaduras: u32,
+    pub qtd_imagens: u32, // Imagens da fachada consideradas (sem as excluídas pelo índice)
     pub observacoes: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Fissura {
+    pub id: String,
     pub faceta_id: String,
-    pub localizacao: String,
-    pub classificacao: String, // "Estrutural", "Térmica", "Retração plástica", "Deslocamento"
+    pub localizacao: String, // "<prédio> / <fachada> / <arquivo>"
+    pub classificacao: String, // Rótulo da classe na taxonomia do modelo (ver taxonomia.rs), ex: "Térmica"
+    pub classe: String, // Chave da classe gravada nos resultados, ex: "termica"
+    pub confianca: f64,
+    pub bbox: Option<[f64; 4]>,
+    pub orientacao: Option<String>, // Da medição da fissura (ver geometria.rs), ex: "Diagonal (45°)"
+    pub geometria: Option<GeometriaFissura>,
+    pub status_revisao: String,
+    #[serde(default)]
+    pub revisada: bool, // Decisão do revisor registrada (aceita ou rejeitada)
+    #[serde(default)]
+    pub nota_revisor: Option<String>,
+    #[serde(default)]
+    pub classificacao_original: Option<String>, // Rótulo dado pelo modelo quando o revisor corrigiu a classe
     pub descricao: String,
-    pub caminho_imagem: String,
+    #[serde(default)]
+    pub fachada: String, // Nome da fachada no índice (ex: "Norte")
+    pub caminho_imagem: String, // Relativo à pasta do projeto (ex: "images/Predio-1/fachada-Norte/foto.jpg")
+    // Caminho gravado nos resultados de detecção, o mesmo das caixas e da classificação da imagem
+    #[serde(skip)]
+    pub caminho_resultado: String,
+}
+
+// Gráfico da seção de estatísticas; o template usa o PNG, que todos os formatos exportados aceitam
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GraficoRelatorio {
+    pub titulo: String,
+    pub imagem: String, // Caminho absoluto do PNG
+    pub svg: String, // Caminho absoluto do SVG estático
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -24,15 +52,128 @@ pub struct ReportData {
     pub data_analise: String, // e.g., "YYYY-MM-DD"
     pub nome_responsavel: String,
     pub nome_predio: String,
-    pub endereco_predio: String,
-    pub numero_andares: u32,
-    pub ano_construcao: u32,
+    pub endereco_predio: Option<String>,
+    pub numero_andares: Option<u32>,
+    pub ano_construcao: Option<u32>,
     pub tipo_estrutura: String,
     pub observacoes_gerais: String,
+    pub modelo: Option<String>, // Id do modelo que gerou as detecções
     pub facetas: Vec<Faceta>,
     pub fissuras: Vec<Fissura>,
     pub conclusao_geral: String,
     pub recomendacoes: String,
     pub funcao_responsavel: String,
     pub nome_empresa: String,
-} 
\ No newline at end of file
+}
+
+// Contexto do template do relatório (report_template.md e os templates do usuário): todas as
+// variáveis que o template usa, montadas a partir dos ReportData dos prédios cobertos. Campos sem
+// valor saem como "Não informado" ou null, nunca ausentes, para o modo estrito só acusar nomes que
+// realmente não existem.
+#[derive(Serialize, Debug, Clone)]
+pub struct ContextoRelatorio {
+    pub nome_projeto: String,
+    pub data_analise: String, // "YYYY-MM-DD"
+    pub data_geracao: String, // "YYYY-MM-DD HH:MM:SS"
+    pub nome_responsavel: String,
+    pub funcao_responsavel: String,
+    pub nome_empresa: String,
+    pub nome_predio: String,
+    pub endereco_predio: String,
+    pub numero_andares: String,
+    pub ano_construcao: String,
+    pub tipo_estrutura: String,
+    pub observacoes_gerais: String,
+    pub metodologia: MetodologiaRelatorio,
+    pub facetas: Vec<Faceta>,
+    pub resumo_classes: Vec<ResumoClasse>,
+    pub indice_severidade: String, // Soma dos pesos das fissuras, com uma casa decimal
+    pub predios: Vec<ResumoPredio>, // Só no resumo do projeto; vazio no relatório de um prédio
+    pub classificacao: Option<ClassificacaoRelatorio>, // Quando o projeto rodou a classificação por imagem
+    pub graficos: Vec<GraficoRelatorio>,
+    pub fissuras: Vec<FissuraRelatorio>,
+    pub conclusao_geral: String,
+    pub recomendacoes: String,
+}
+
+// Filtros com que as detecções do relatório foram obtidas; confianças em %
+#[derive(Serialize, Debug, Clone)]
+pub struct MetodologiaRelatorio {
+    pub modelo: String,
+    pub ladrilhamento: String,
+    pub confianca_minima_padrao: u32,
+    pub limiares: Vec<LimiarClasse>,
+    pub iou_nms: f64,
+    pub classes_ignoradas: String,
+    pub escala: String,
+    pub fissuras_revisadas: usize,
+    pub fissuras_total: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LimiarClasse {
+    pub classe: String,
+    pub confianca: u32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ResumoClasse {
+    pub rotulo: String,
+    pub descricao: String,
+    pub quantidade: usize,
+    pub peso: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ResumoPredio {
+    pub nome: String,
+    pub endereco: String,
+    pub imagens: u32,
+    pub fissuras: usize,
+    pub indice_severidade: String,
+    pub classes: Vec<QuantidadeClasse>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct QuantidadeClasse {
+    pub rotulo: String,
+    pub quantidade: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ClassificacaoRelatorio {
+    pub imagens: usize,
+    pub modo: String,
+    pub confianca_minima: u32,
+    pub resumo: Vec<ClassificacaoClasse>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ClassificacaoClasse {
+    pub classe: String,
+    pub imagens: usize,
+    pub caixas: usize,
+}
+
+// Fissura como aparece no detalhamento do relatório. O id é sequencial na ordem do relatório
+// (FIS-001, FIS-002...), então não muda entre gerações com os mesmos dados.
+#[derive(Serialize, Debug, Clone)]
+pub struct FissuraRelatorio {
+    pub id_fissura: String,
+    pub faceta_id: String, // Nome da fachada; "<prédio> / <fachada>" no resumo do projeto
+    pub orientacao: String,
+    pub comprimento: Option<String>,
+    pub largura: Option<String>,
+    pub classe_abertura: Option<String>,
+    pub classificacao: String,
+    pub classificacao_original: Option<String>,
+    pub classe: String,
+    pub confianca: f64, // 0.0 - 1.0
+    pub classificacao_imagem: Option<String>, // Ex: "Térmica (87%)"
+    pub status_revisao: String,
+    pub revisada: bool,
+    pub nota_revisor: Option<String>,
+    pub observacoes: String, // Observações da faceta da fissura
+    pub caminho_imagem: String,
+    pub bbox: Option<[f64; 4]>, // Caixa da fissura na imagem, para as figuras anotadas dos exportadores
+}
@@ -0,0 +1,241 @@
+// revisao.rs
+// Revisão humana das detecções, salva em Projects/<projeto>/review.json, separada da saída do
+// modelo (detection_results.json). Cada decisão identifica a detecção original pela classe,
+// confiança e caixa, então continua válida mesmo que a política de detecção mude.
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::collections::BTreeMap;
+use anyhow::{Result, Context};
+use chrono::Local;
+use serde::{Serialize, Deserialize};
+use crate::manual_processor::FissuraData;
+use crate::project_index::caminho_relativo_imagens;
+
+pub const REVIEW_FILE_NAME: &str = "review.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusRevisao {
+    #[default]
+    Pendente,
+    Aceita,
+    Rejeitada,
+}
+
+// Decisão do revisor sobre uma detecção do modelo
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DecisaoDeteccao {
+    pub classe_original: String,
+    pub confianca: f64,
+    #[serde(default)]
+    pub bbox: Option<[f64; 4]>,
+    pub status: StatusRevisao,
+    #[serde(default)]
+    pub classe: Option<String>, // Classe corrigida pelo revisor
+    #[serde(default)]
+    pub nota: Option<String>,
+    pub revisor: String,
+    pub data: String,
+}
+
+impl DecisaoDeteccao {
+    pub fn corresponde(&self, fissura: &FissuraData) -> bool {
+        self.classe_original == fissura.name
+            && (self.confianca - fissura.confidence).abs() < 1e-9
+            && self.bbox == fissura.bbox
+    }
+}
+
+// Fissura que o modelo não encontrou e o revisor marcou na imagem
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CaixaManual {
+    pub classe: String,
+    pub bbox: [f64; 4], // Pixels da imagem original
+    #[serde(default)]
+    pub nota: Option<String>,
+    pub revisor: String,
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RevisaoImagem {
+    #[serde(default)]
+    pub decisoes: Vec<DecisaoDeteccao>,
+    #[serde(default)]
+    pub caixas_manuais: Vec<CaixaManual>,
+}
+
+impl RevisaoImagem {
+    pub fn decisao(&self, fissura: &FissuraData) -> Option<&DecisaoDeteccao> {
+        self.decisoes.iter().find(|d| d.corresponde(fissura))
+    }
+}
+
+// Informação de revisão anexada às detecções carregadas por `deteccoes::carregar`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InfoRevisao {
+    pub status: StatusRevisao,
+    #[serde(default)]
+    pub classe_original: Option<String>, // Presente quando o revisor mudou a classe
+    #[serde(default)]
+    pub nota: Option<String>,
+    #[serde(default)]
+    pub revisor: Option<String>,
+    #[serde(default)]
+    pub manual: bool,
+}
+
+impl InfoRevisao {
+    // Texto para o relatório (ex: "Confirmada por Ana")
+    pub fn descricao(&self) -> String {
+        let revisor = self.revisor.clone().unwrap_or_default();
+        match (self.status, self.manual) {
+            (_, true) => format!("Adicionada manualmente por {}", revisor),
+            (StatusRevisao::Aceita, _) => format!("Confirmada por {}", revisor),
+            (StatusRevisao::Rejeitada, _) => format!("Rejeitada por {}", revisor),
+            (StatusRevisao::Pendente, _) => "Não revisada".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RevisaoProjeto {
+    #[serde(default)]
+    pub revisor: String, // Último nome usado na tela de revisão
+    // Chave: caminho da imagem relativo à pasta images (ex: "Predio-1/fachada-Norte/foto.jpg")
+    #[serde(default)]
+    pub imagens: BTreeMap<String, RevisaoImagem>,
+}
+
+fn chave(image_path: &Path) -> String {
+    caminho_relativo_imagens(image_path)
+        .unwrap_or_else(|| image_path.to_path_buf())
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn agora() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+impl RevisaoProjeto {
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(REVIEW_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Falha ao ler {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Falha ao parsear {}", path.display()))
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(REVIEW_FILE_NAME);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Falha ao salvar {}", path.display()))
+    }
+
+    pub fn imagem(&self, image_path: &Path) -> Option<&RevisaoImagem> {
+        self.imagens.get(&chave(image_path))
+    }
+
+    fn imagem_mut(&mut self, image_path: &Path) -> &mut RevisaoImagem {
+        self.imagens.entry(chave(image_path)).or_default()
+    }
+
+    // Registra (ou substitui) a decisão sobre uma detecção do modelo
+    pub fn decidir(&mut self, image_path: &Path, fissura: &FissuraData, status: StatusRevisao, classe: Option<String>, nota: Option<String>) {
+        let revisor = self.revisor.clone();
+        let imagem = self.imagem_mut(image_path);
+        imagem.decisoes.retain(|d| !d.corresponde(fissura));
+        imagem.decisoes.push(DecisaoDeteccao {
+            classe_original: fissura.name.clone(),
+            confianca: fissura.confidence,
+            bbox: fissura.bbox,
+            status,
+            classe: classe.filter(|c| *c != fissura.name),
+            nota: nota.filter(|n| !n.trim().is_empty()),
+            revisor,
+            data: agora(),
+        });
+    }
+
+    pub fn adicionar_caixa(&mut self, image_path: &Path, classe: &str, bbox: [f64; 4]) {
+        let revisor = self.revisor.clone();
+        self.imagem_mut(image_path).caixas_manuais.push(CaixaManual {
+            classe: classe.to_string(),
+            bbox,
+            nota: None,
+            revisor,
+            data: agora(),
+        });
+    }
+
+    pub fn remover_caixa(&mut self, image_path: &Path, posicao: usize) {
+        let imagem = self.imagem_mut(image_path);
+        if posicao < imagem.caixas_manuais.len() {
+            imagem.caixas_manuais.remove(posicao);
+        }
+    }
+
+    // Acompanha as imagens movidas pela reorganização do projeto
+    pub fn atualizar_caminhos(&mut self, movimentos: &[(PathBuf, PathBuf)]) -> bool {
+        let mut mudou = false;
+        for (origem, destino) in movimentos {
+            if let Some(revisao) = self.imagens.remove(&chave(origem)) {
+                self.imagens.insert(chave(destino), revisao);
+                mudou = true;
+            }
+        }
+        mudou
+    }
+}
+
+// Combina as detecções do modelo com a revisão da imagem. As decisões do revisor prevalecem sobre
+// a política: aceitas entram mesmo abaixo do limiar, rejeitadas saem. As demais só entram se
+// passaram pela política (`filtradas`). Caixas manuais são acrescentadas com confiança 1.0.
+pub fn aplicar(brutas: &[FissuraData], filtradas: &[FissuraData], revisao: Option<&RevisaoImagem>) -> Vec<FissuraData> {
+    let mut resultado = Vec::new();
+    for fissura in brutas {
+        let decisao = revisao.and_then(|r| r.decisao(fissura));
+        let status = decisao.map_or(StatusRevisao::Pendente, |d| d.status);
+        let incluir = match status {
+            StatusRevisao::Aceita => true,
+            StatusRevisao::Rejeitada => false,
+            StatusRevisao::Pendente => filtradas.contains(fissura),
+        };
+        if !incluir {
+            continue;
+        }
+        let classe = decisao.and_then(|d| d.classe.clone());
+        resultado.push(FissuraData {
+            name: classe.clone().unwrap_or_else(|| fissura.name.clone()),
+            revisao: Some(InfoRevisao {
+                status,
+                classe_original: classe.map(|_| fissura.name.clone()),
+                nota: decisao.and_then(|d| d.nota.clone()),
+                revisor: decisao.map(|d| d.revisor.clone()),
+                manual: false,
+            }),
+            ..fissura.clone()
+        });
+    }
+
+    for caixa in revisao.map(|r| r.caixas_manuais.as_slice()).unwrap_or_default() {
+        resultado.push(FissuraData {
+            name: caixa.classe.clone(),
+            confidence: 1.0,
+            bbox: Some(caixa.bbox),
+            revisao: Some(InfoRevisao {
+                status: StatusRevisao::Aceita,
+                classe_original: None,
+                nota: caixa.nota.clone(),
+                revisor: Some(caixa.revisor.clone()),
+                manual: true,
+            }),
+            geometria: None,
+        });
+    }
+    resultado
+}
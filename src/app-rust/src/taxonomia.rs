@@ -0,0 +1,144 @@
+// taxonomia.rs
+// Taxonomia de classes de fissura de cada modelo registrado (ver modelos.rs): índice no modelo,
+// chave interna, rótulo em português, cor, peso de severidade e descrição para os relatórios.
+// Gráficos, legendas, política de detecção, revisão e relatórios leem as classes daqui, então um
+// modelo novo com uma classe "estrutural" funciona sem mudar código.
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use crate::anotacoes::normalizar_classe;
+use crate::modelos::RegistroModelos;
+use crate::manual_processor::ImageAnalysisResult;
+use crate::project_settings::ProjectSettings;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClasseFissura {
+    pub indice: u32, // Índice da classe na saída do modelo
+    pub chave: String, // Ex: "termica"; é o nome gravado nos resultados
+    pub rotulo: String, // Ex: "Térmica"
+    pub cor: String, // Cor CSS usada nos gráficos e na revisão (ex: "#ff5a5f")
+    pub peso_severidade: f64, // 1.0 = referência; usado no índice de severidade dos relatórios
+    #[serde(default)]
+    pub descricao: String, // Texto explicativo para os relatórios
+}
+
+// Valores sugeridos para classes conhecidas ao importar um modelo
+const CATALOGO: [(&str, &str, &str, f64, &str); 5] = [
+    ("retracao", "Retração", "#0077ff", 1.0,
+        "Fissuras causadas pela retração do concreto ou da argamassa durante a secagem; geralmente superficiais e sem risco estrutural."),
+    ("termica", "Térmica", "#ff5a5f", 1.5,
+        "Fissuras provocadas pela dilatação e contração térmica dos materiais; tendem a aparecer em juntas e encontros de elementos."),
+    ("estrutural", "Estrutural", "#d62828", 3.0,
+        "Fissuras associadas a esforços na estrutura (flexão, cisalhamento, recalque); exigem avaliação de um engenheiro estrutural."),
+    ("deslocamento", "Deslocamento", "#f4a261", 2.0,
+        "Descolamento ou deslocamento de revestimento, com risco de queda de material."),
+    ("umidade", "Umidade", "#2a9d8f", 1.0,
+        "Manchas e fissuras associadas à infiltração de umidade."),
+];
+
+// Paleta para classes fora do catálogo
+const PALETA: [&str; 6] = ["#8e44ad", "#16a085", "#e67e22", "#2c3e50", "#c0392b", "#7f8c8d"];
+
+impl ClasseFissura {
+    // Classe com os valores do catálogo, ou genéricos se a chave for desconhecida
+    pub fn sugerida(indice: u32, chave: &str) -> Self {
+        let chave = normalizar_classe(chave);
+        match CATALOGO.iter().find(|(c, ..)| *c == chave) {
+            Some((_, rotulo, cor, peso, descricao)) => Self {
+                indice,
+                chave,
+                rotulo: rotulo.to_string(),
+                cor: cor.to_string(),
+                peso_severidade: *peso,
+                descricao: descricao.to_string(),
+            },
+            None => {
+                let mut rotulo: Vec<char> = chave.replace('_', " ").chars().collect();
+                if let Some(primeira) = rotulo.first_mut() {
+                    *primeira = primeira.to_ascii_uppercase();
+                }
+                Self {
+                    indice,
+                    rotulo: rotulo.into_iter().collect(),
+                    cor: PALETA[indice as usize % PALETA.len()].to_string(),
+                    peso_severidade: 1.0,
+                    descricao: String::new(),
+                    chave,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Taxonomia {
+    pub classes: Vec<ClasseFissura>, // Na ordem dos índices do modelo
+}
+
+impl Taxonomia {
+    // Taxonomia a partir das chaves na ordem dos índices do modelo
+    pub fn sugerida<S: AsRef<str>>(chaves: &[S]) -> Self {
+        Self {
+            classes: chaves.iter().enumerate().map(|(i, c)| ClasseFissura::sugerida(i as u32, c.as_ref())).collect(),
+        }
+    }
+
+    // Taxonomia do modelo ativo do projeto; sem registro, a do modelo que acompanha o repositório
+    pub fn do_projeto(project_dir: &Path) -> Self {
+        let settings = ProjectSettings::load(project_dir).unwrap_or_default();
+        RegistroModelos::load()
+            .ok()
+            .and_then(|registro| registro.resolver(settings.modelo_ativo.as_deref()).ok().map(|m| m.taxonomia.clone()))
+            .unwrap_or_else(|| Self::sugerida(&["retracao", "termica"]))
+    }
+
+    // Taxonomia do modelo que gerou os resultados; resultados sem modelo registrado usam a do projeto
+    pub fn dos_resultados(project_dir: &Path, resultados: &[ImageAnalysisResult]) -> Self {
+        resultados
+            .first()
+            .and_then(|r| r.modelo.as_deref())
+            .and_then(|id| RegistroModelos::load().ok()?.modelo(id).map(|m| m.taxonomia.clone()))
+            .unwrap_or_else(|| Self::do_projeto(project_dir))
+    }
+
+    pub fn classe(&self, chave: &str) -> Option<&ClasseFissura> {
+        let chave = normalizar_classe(chave);
+        self.classes.iter().find(|c| c.chave == chave)
+    }
+
+    pub fn chaves(&self) -> Vec<String> {
+        self.classes.iter().map(|c| c.chave.clone()).collect()
+    }
+
+    pub fn rotulo(&self, chave: &str) -> String {
+        self.classe(chave).map(|c| c.rotulo.clone()).unwrap_or_else(|| chave.to_string())
+    }
+
+    pub fn cor(&self, chave: &str) -> String {
+        self.classe(chave).map(|c| c.cor.clone()).unwrap_or_else(|| "#7f8c8d".to_string())
+    }
+
+    pub fn peso(&self, chave: &str) -> f64 {
+        self.classe(chave).map_or(1.0, |c| c.peso_severidade)
+    }
+
+    // Mapa índice -> chave no formato do argumento --classes do script (ex: "0=retracao,1=termica")
+    pub fn argumento_script(&self) -> String {
+        self.classes
+            .iter()
+            .map(|c| format!("{}={}", c.indice, c.chave))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    // Classes da taxonomia seguidas das que aparecem nos resultados sem estar nela
+    pub fn com_extras<I: IntoIterator<Item = String>>(&self, presentes: I) -> Vec<String> {
+        let mut chaves = self.chaves();
+        for chave in presentes {
+            let chave = normalizar_classe(&chave);
+            if !chaves.contains(&chave) {
+                chaves.push(chave);
+            }
+        }
+        chaves
+    }
+}
@@ -0,0 +1,411 @@
+// templates_relatorio.rs
+// Templates Handlebars dos relatórios. Além do template embutido no app ("padrao"), qualquer .md em
+// Templates/ (compartilhado entre projetos) ou em Projects/<projeto>/templates/ vira uma opção; um
+// arquivo do projeto com o mesmo nome substitui o global, e um "padrao.md" substitui o embutido. O
+// projeto guarda o nome escolhido em project_settings.json. Antes de renderizar, o template é
+// conferido contra os dados do relatório para apontar variáveis que sairiam em branco.
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context};
+use chrono::{NaiveDate, NaiveDateTime};
+use handlebars::template::{HelperTemplate, Parameter, Template, TemplateElement};
+use handlebars::{
+    handlebars_helper, Handlebars, Helper, HelperResult, JsonTruthy, Output, RenderContext, RenderErrorReason, TemplateError,
+};
+use serde_json::Value;
+use crate::project_settings::ProjectSettings;
+use crate::taxonomia::Taxonomia;
+
+pub const TEMPLATE_PADRAO: &str = "padrao";
+pub const TEMPLATES_DIR_NAME: &str = "templates";
+const TEMPLATE_EMBUTIDO: &str = include_str!("Template/report_template.md");
+
+// Helpers do Handlebars e os registrados por registrar_helpers, para a validação não confundir um
+// helper com uma variável
+const HELPERS: &[&str] = &[
+    "if", "unless", "each", "with", "lookup", "raw", "log", "eq", "ne", "gt", "gte", "lt", "lte", "and", "or", "not", "len",
+    "formatar_data", "porcentagem", "plural", "rotulo_classe",
+];
+
+// Palavras que encerram a parte pluralizada de uma expressão ("classe de abertura" -> "classes de abertura")
+const CONECTIVOS: &[&str] = &["de", "da", "do", "das", "dos", "em", "com", "por", "para", "sem", "e"];
+
+// Templates compartilhados entre projetos
+pub fn templates_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Templates")
+}
+
+pub fn templates_projeto(project_dir: &Path) -> PathBuf {
+    project_dir.join(TEMPLATES_DIR_NAME)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrigemTemplate {
+    Embutido,
+    Global,
+    Projeto,
+}
+
+impl OrigemTemplate {
+    pub fn descricao(&self) -> &'static str {
+        match self {
+            OrigemTemplate::Embutido => "embutido",
+            OrigemTemplate::Global => "global",
+            OrigemTemplate::Projeto => "do projeto",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateRelatorio {
+    pub nome: String, // Nome do arquivo sem ".md"
+    pub origem: OrigemTemplate,
+    pub caminho: Option<PathBuf>, // Ausente no embutido
+    pub conteudo: String,
+}
+
+fn templates_da_pasta(pasta: &Path, origem: OrigemTemplate) -> Vec<TemplateRelatorio> {
+    let Ok(entradas) = fs::read_dir(pasta) else { return Vec::new() };
+    let mut templates: Vec<TemplateRelatorio> = entradas
+        .filter_map(|entrada| entrada.ok().map(|e| e.path()))
+        .filter(|caminho| caminho.extension().is_some_and(|e| e.eq_ignore_ascii_case("md")))
+        .filter_map(|caminho| match fs::read_to_string(&caminho) {
+            Ok(conteudo) => Some(TemplateRelatorio {
+                nome: caminho.file_stem()?.to_string_lossy().to_string(),
+                origem,
+                caminho: Some(caminho),
+                conteudo,
+            }),
+            Err(e) => {
+                eprintln!("Template ignorado ({}): {}", caminho.display(), e);
+                None
+            }
+        })
+        .collect();
+    templates.sort_by(|a, b| a.nome.cmp(&b.nome));
+    templates
+}
+
+// Templates disponíveis para o projeto: o padrão primeiro, depois os demais por nome
+pub fn listar(project_dir: &Path) -> Vec<TemplateRelatorio> {
+    let mut templates = vec![TemplateRelatorio {
+        nome: TEMPLATE_PADRAO.to_string(),
+        origem: OrigemTemplate::Embutido,
+        caminho: None,
+        conteudo: TEMPLATE_EMBUTIDO.to_string(),
+    }];
+    let pastas = [(templates_dir(), OrigemTemplate::Global), (templates_projeto(project_dir), OrigemTemplate::Projeto)];
+    for (pasta, origem) in pastas {
+        for template in templates_da_pasta(&pasta, origem) {
+            match templates.iter_mut().find(|t| t.nome == template.nome) {
+                Some(existente) => *existente = template,
+                None => templates.push(template),
+            }
+        }
+    }
+    templates[1..].sort_by(|a, b| a.nome.cmp(&b.nome));
+    templates
+}
+
+// Nome do template escolhido no projeto (o padrão quando não há escolha)
+pub fn nome_selecionado(project_dir: &Path) -> String {
+    ProjectSettings::load(project_dir)
+        .ok()
+        .and_then(|settings| settings.template_relatorio)
+        .unwrap_or_else(|| TEMPLATE_PADRAO.to_string())
+}
+
+// Template escolhido no projeto; se o arquivo sumiu, volta ao padrão
+pub fn selecionado(project_dir: &Path) -> TemplateRelatorio {
+    let nome = nome_selecionado(project_dir);
+    let mut templates = listar(project_dir);
+    match templates.iter().position(|t| t.nome == nome) {
+        Some(posicao) => templates.swap_remove(posicao),
+        None => {
+            eprintln!("Template '{}' não encontrado; usando o padrão", nome);
+            templates.swap_remove(0)
+        }
+    }
+}
+
+pub fn escolher(project_dir: &Path, nome: &str) -> Result<()> {
+    let mut settings = ProjectSettings::load(project_dir)?;
+    settings.template_relatorio = (nome != TEMPLATE_PADRAO).then(|| nome.to_string());
+    settings.save(project_dir)
+}
+
+// Cópia editável do template na pasta do projeto; um template do projeto é devolvido como está
+pub fn copiar_para_projeto(project_dir: &Path, template: &TemplateRelatorio) -> Result<PathBuf> {
+    if let (OrigemTemplate::Projeto, Some(caminho)) = (template.origem, &template.caminho) {
+        return Ok(caminho.clone());
+    }
+    let pasta = templates_projeto(project_dir);
+    fs::create_dir_all(&pasta).with_context(|| format!("Falha ao criar {}", pasta.display()))?;
+    let caminho = pasta.join(format!("{}.md", template.nome));
+    fs::write(&caminho, &template.conteudo).with_context(|| format!("Falha ao salvar {}", caminho.display()))?;
+    Ok(caminho)
+}
+
+//  Validação
+
+// Nível do contexto durante a conferência: o valor e como ele é chamado nas mensagens
+struct Escopo<'a> {
+    valor: &'a Value,
+    prefixo: String, // Ex: "fissuras[]."
+}
+
+fn resolver<'a>(escopos: &[Escopo<'a>], caminho: &str) -> Option<&'a Value> {
+    let mut nivel = escopos.len() - 1;
+    let mut resto = caminho;
+    if let Some(depois) = resto.strip_prefix("@root") {
+        nivel = 0;
+        resto = depois.trim_start_matches(['.', '/']);
+    }
+    while let Some(depois) = resto.strip_prefix("../") {
+        nivel = nivel.saturating_sub(1);
+        resto = depois;
+    }
+    resto = resto.strip_prefix("this").map_or(resto, |depois| depois.trim_start_matches(['.', '/']));
+    resto = resto.strip_prefix("./").unwrap_or(resto);
+    let mut valor = escopos[nivel].valor;
+    for segmento in resto.split(['.', '/']).filter(|s| !s.is_empty()) {
+        valor = match valor {
+            Value::Object(mapa) => mapa.get(segmento)?,
+            Value::Array(lista) => lista.get(segmento.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(valor)
+}
+
+struct Conferencia<'a> {
+    escopos: Vec<Escopo<'a>>,
+    ausentes: BTreeSet<String>,
+}
+
+impl<'a> Conferencia<'a> {
+    fn registrar_ausente(&mut self, caminho: &str) {
+        let prefixo = &self.escopos[self.escopos.len() - 1].prefixo;
+        self.ausentes.insert(format!("{}{}", prefixo, caminho));
+    }
+
+    // Valor de um parâmetro; None para literais, subexpressões e variáveis locais (@index...)
+    fn valor(&self, parametro: &Parameter) -> Option<Option<&'a Value>> {
+        match parametro {
+            Parameter::Path(handlebars::Path::Relative((_, caminho))) => Some(resolver(&self.escopos, caminho)),
+            _ => None,
+        }
+    }
+
+    fn conferir_parametro(&mut self, parametro: &Parameter) {
+        match parametro {
+            Parameter::Path(handlebars::Path::Relative((_, caminho))) if resolver(&self.escopos, caminho).is_none() => {
+                self.registrar_ausente(caminho);
+            }
+            Parameter::Subexpression(sub) => {
+                if let TemplateElement::Expression(expressao) = sub.as_element() {
+                    self.conferir_expressao(expressao);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn conferir_helper(&mut self, nome: &str) {
+        if !HELPERS.contains(&nome) {
+            self.ausentes.insert(format!("helper desconhecido: {}", nome));
+        }
+    }
+
+    fn conferir_expressao(&mut self, expressao: &HelperTemplate) {
+        match &expressao.name {
+            // Sem parâmetros é uma variável; com eles, o nome é de um helper
+            Parameter::Path(_) if expressao.params.is_empty() && expressao.hash.is_empty() => {
+                self.conferir_parametro(&expressao.name)
+            }
+            Parameter::Name(nome) => self.conferir_helper(nome),
+            _ => {}
+        }
+        for parametro in expressao.params.iter().chain(expressao.hash.values()) {
+            self.conferir_parametro(parametro);
+        }
+    }
+
+    // Segue os blocos como a renderização seguiria com estes dados: só o ramo que seria mostrado é
+    // conferido, e o corpo de um #each uma vez por item
+    fn conferir_bloco(&mut self, bloco: &HelperTemplate) {
+        let nome = match &bloco.name {
+            Parameter::Name(nome) => nome.as_str(),
+            _ => "",
+        };
+        let primeiro = bloco.params.first();
+        match nome {
+            "if" | "unless" => {
+                let Some(condicao) = primeiro.and_then(|p| self.valor(p)) else {
+                    // Condição calculada por subexpressão: os dois ramos podem aparecer
+                    bloco.params.iter().for_each(|p| self.conferir_parametro(p));
+                    self.conferir_opcional(bloco.template.as_ref());
+                    self.conferir_opcional(bloco.inverse.as_ref());
+                    return;
+                };
+                // Variável ausente numa condição é o jeito de marcar um campo opcional; não é erro
+                let verdadeiro = condicao.is_some_and(|v| v.is_truthy(false)) == (nome == "if");
+                self.conferir_opcional(if verdadeiro { bloco.template.as_ref() } else { bloco.inverse.as_ref() });
+            }
+            "each" | "with" => {
+                let Some(caminho) = primeiro.and_then(|p| match p {
+                    Parameter::Path(handlebars::Path::Relative((_, caminho))) => Some(caminho.clone()),
+                    _ => None,
+                }) else {
+                    self.conferir_opcional(bloco.template.as_ref());
+                    return;
+                };
+                let itens: Vec<&'a Value> = match resolver(&self.escopos, &caminho) {
+                    None => {
+                        self.registrar_ausente(&caminho);
+                        Vec::new()
+                    }
+                    Some(Value::Array(lista)) if nome == "each" => lista.iter().collect(),
+                    Some(Value::Object(mapa)) if nome == "each" => mapa.values().collect(),
+                    Some(valor) if nome == "with" && valor.is_truthy(false) => vec![valor],
+                    Some(_) => Vec::new(),
+                };
+                if itens.is_empty() {
+                    self.conferir_opcional(bloco.inverse.as_ref());
+                    return;
+                }
+                let prefixo = format!(
+                    "{}{}{}.",
+                    self.escopos[self.escopos.len() - 1].prefixo,
+                    caminho,
+                    if nome == "each" { "[]" } else { "" }
+                );
+                for item in itens {
+                    self.escopos.push(Escopo { valor: item, prefixo: prefixo.clone() });
+                    self.conferir_opcional(bloco.template.as_ref());
+                    self.escopos.pop();
+                }
+            }
+            _ => {
+                self.conferir_helper(nome);
+                for parametro in bloco.params.iter().chain(bloco.hash.values()) {
+                    self.conferir_parametro(parametro);
+                }
+                self.conferir_opcional(bloco.template.as_ref());
+                self.conferir_opcional(bloco.inverse.as_ref());
+            }
+        }
+    }
+
+    fn conferir_opcional(&mut self, template: Option<&Template>) {
+        if let Some(template) = template {
+            self.conferir(template);
+        }
+    }
+
+    fn conferir(&mut self, template: &Template) {
+        for elemento in &template.elements {
+            match elemento {
+                TemplateElement::Expression(expressao) | TemplateElement::HtmlExpression(expressao) => {
+                    self.conferir_expressao(expressao)
+                }
+                TemplateElement::HelperBlock(bloco) => self.conferir_bloco(bloco),
+                _ => {}
+            }
+        }
+    }
+}
+
+// Variáveis (e helpers) que o template usa e os dados não têm, como "endereco_predio" ou
+// "fissuras[].comprimento". Erro só quando o template nem compila.
+pub fn variaveis_ausentes(template: &str, dados: &Value) -> Result<Vec<String>, TemplateError> {
+    let compilado = Template::compile(template)?;
+    let mut conferencia = Conferencia { escopos: vec![Escopo { valor: dados, prefixo: String::new() }], ausentes: BTreeSet::new() };
+    conferencia.conferir(&compilado);
+    Ok(conferencia.ausentes.into_iter().collect())
+}
+
+//  Helpers
+
+// Plural de uma palavra pelas regras regulares do português
+fn pluralizar_palavra(palavra: &str) -> String {
+    if let Some(raiz) = palavra.strip_suffix("ão") {
+        format!("{}ões", raiz)
+    } else if let Some(raiz) = palavra.strip_suffix('m') {
+        format!("{}ns", raiz)
+    } else if palavra.ends_with(['r', 'z']) {
+        format!("{}es", palavra)
+    } else if let Some(raiz) = palavra.strip_suffix("il") {
+        format!("{}is", raiz)
+    } else if let Some(raiz) = palavra.strip_suffix('l') {
+        format!("{}is", raiz)
+    } else if palavra.ends_with('s') || palavra.ends_with('x') {
+        palavra.to_string()
+    } else {
+        format!("{}s", palavra)
+    }
+}
+
+// Plural de uma expressão: as palavras até o primeiro conectivo ("fissura ativa" -> "fissuras ativas")
+pub fn pluralizar(expressao: &str) -> String {
+    let mut no_nucleo = true;
+    expressao
+        .split(' ')
+        .map(|palavra| {
+            no_nucleo &= !CONECTIVOS.contains(&palavra.to_lowercase().as_str());
+            if no_nucleo && !palavra.is_empty() { pluralizar_palavra(palavra) } else { palavra.to_string() }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// {{formatar_data data_analise}} -> "05/03/2025"; aceita "YYYY-MM-DD" e "YYYY-MM-DD HH:MM:SS" e
+// devolve o texto original quando não reconhece a data. Formato opcional: formato="%d de %B de %Y".
+handlebars_helper!(FormatarData: |data: str, { formato: str = "%d/%m/%Y" }| formatar_data(data, formato));
+
+// {{porcentagem confianca}} -> "87%" para frações de 0 a 1; casas=1 -> "87,4%"
+handlebars_helper!(Porcentagem: |valor: f64, { casas: u64 = 0 }| porcentagem(valor, casas as usize));
+
+// Mesmas conversões dos helpers, para os exportadores que montam o documento sem o template
+pub fn formatar_data(data: &str, formato: &str) -> String {
+    NaiveDateTime::parse_from_str(data, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDate::parse_from_str(data, "%Y-%m-%d").map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default()))
+        .map(|d| d.format(formato).to_string())
+        .unwrap_or_else(|_| data.to_string())
+}
+
+pub fn porcentagem(valor: f64, casas: usize) -> String {
+    format!("{:.*}%", casas, valor * 100.0).replace('.', ",")
+}
+
+// {{plural quantidade "fissura"}} -> "1 fissura", "3 fissuras"; plural="..." para formas irregulares
+handlebars_helper!(Plural: |quantidade: u64, singular: str, { plural: str = "" }| {
+    let forma = match (quantidade, plural) {
+        (1, _) => singular.to_string(),
+        (_, "") => pluralizar(singular),
+        (_, plural) => plural.to_string(),
+    };
+    format!("{} {}", quantidade, forma)
+});
+
+// Helpers disponíveis nos templates; `rotulo_classe` usa a taxonomia do modelo dos resultados
+pub fn registrar_helpers(handlebars: &mut Handlebars, taxonomia: Taxonomia) {
+    handlebars.register_helper("formatar_data", Box::new(FormatarData));
+    handlebars.register_helper("porcentagem", Box::new(Porcentagem));
+    handlebars.register_helper("plural", Box::new(Plural));
+    // {{rotulo_classe classe}} -> "Térmica"
+    handlebars.register_helper(
+        "rotulo_classe",
+        Box::new(
+            move |h: &Helper, _: &Handlebars, _: &handlebars::Context, _: &mut RenderContext, saida: &mut dyn Output| -> HelperResult {
+                let classe = h
+                    .param(0)
+                    .and_then(|p| p.value().as_str())
+                    .ok_or(RenderErrorReason::ParamNotFoundForIndex("rotulo_classe", 0))?;
+                saida.write(&taxonomia.rotulo(classe))?;
+                Ok(())
+            },
+        ),
+    );
+}
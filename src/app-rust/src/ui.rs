@@ -12,12 +12,76 @@ use crate::manual_processor::{ManualProcessor, ManualProcessorProps, run_yolo_sc
 use crate::create_project::PROJECT_NAME;
 use dioxus::prelude::Readable;
 use dioxus_router::prelude::use_navigator;
+use crate::project_index;
+use crate::project_settings::{ProjectSettings, ConfiguracaoLadrilhamento, ConfiguracaoClassificacao, ModoClassificacao, ConfiguracaoEscala, MetodoEscala};
+use crate::modelos::RegistroModelos;
+
+fn nome_projeto_atual() -> Option<String> {
+    PROJECT_NAME.try_read().ok().and_then(|guard| guard.clone())
+}
+
+// Salva a configuração de tiles do projeto atual; o script de detecção a lê de lá
+fn salvar_ladrilhamento(config: ConfiguracaoLadrilhamento) -> anyhow::Result<()> {
+    let Some(nome) = nome_projeto_atual() else { return Ok(()) };
+    let project_dir = project_index::project_dir(&nome);
+    let mut settings = ProjectSettings::load(&project_dir)?;
+    settings.ladrilhamento = config;
+    settings.save(&project_dir)
+}
+
+// Salva a configuração do classificador; ele roda logo depois da detecção
+fn salvar_classificacao(config: ConfiguracaoClassificacao) -> anyhow::Result<()> {
+    let Some(nome) = nome_projeto_atual() else { return Ok(()) };
+    let project_dir = project_index::project_dir(&nome);
+    let mut settings = ProjectSettings::load(&project_dir)?;
+    settings.classificacao = config;
+    settings.save(&project_dir)
+}
+
+// Salva a escala usada para converter as medidas das fissuras em milímetros
+fn salvar_escala(config: ConfiguracaoEscala) -> anyhow::Result<()> {
+    let Some(nome) = nome_projeto_atual() else { return Ok(()) };
+    let project_dir = project_index::project_dir(&nome);
+    let mut settings = ProjectSettings::load(&project_dir)?;
+    settings.escala = config;
+    settings.save(&project_dir)
+}
+
+// Descrição do modelo que a próxima detecção do projeto vai usar
+fn modelo_do_projeto(nome: &str) -> String {
+    let settings = ProjectSettings::load(&project_index::project_dir(nome)).unwrap_or_default();
+    match RegistroModelos::load() {
+        Ok(registro) => registro
+            .resolver(settings.modelo_ativo.as_deref())
+            .map(|m| m.descricao())
+            .unwrap_or_else(|e| e.to_string()),
+        Err(e) => format!("erro ao ler registro de modelos: {}", e),
+    }
+}
 
 #[component]
 pub fn Home() -> Element {
     let mut folder_path = use_signal(|| None::<String>);
     let mut status = use_signal(String::new);
     let mut threshold = use_signal(|| 200.0_f64);
+    let mut ladrilhamento = use_signal(|| {
+        nome_projeto_atual()
+            .and_then(|nome| ProjectSettings::load(&project_index::project_dir(&nome)).ok())
+            .unwrap_or_default()
+            .ladrilhamento
+    });
+    let mut classificacao = use_signal(|| {
+        nome_projeto_atual()
+            .and_then(|nome| ProjectSettings::load(&project_index::project_dir(&nome)).ok())
+            .unwrap_or_default()
+            .classificacao
+    });
+    let mut escala = use_signal(|| {
+        nome_projeto_atual()
+            .and_then(|nome| ProjectSettings::load(&project_index::project_dir(&nome)).ok())
+            .unwrap_or_default()
+            .escala
+    });
     let mut stats = use_signal(|| None::<ProcessingStats>);
     let mut is_processing = use_signal(|| false);
     let mut is_selecting_folder = use_signal(|| false);
@@ -100,6 +164,253 @@ pub fn Home() -> Element {
                     }
                 }
             }
+                    div { class: "mb-6",
+                        label { class: "flex items-center gap-2 text-gray-700 mb-2",
+                            input {
+                                r#type: "checkbox",
+                                checked: ladrilhamento().ativo,
+                                onchange: move |e| {
+                                    ladrilhamento.write().ativo = e.checked();
+                                    if let Err(e) = salvar_ladrilhamento(ladrilhamento()) {
+                                        status.set(format!("Erro ao salvar configuração de tiles: {}", e));
+                                    }
+                                }
+                            }
+                            "Detecção por tiles (imagens de alta resolução / drone)"
+                        }
+                        if ladrilhamento().ativo {
+                            div { class: "flex gap-4",
+                                div { class: "flex-1",
+                                    label { class: "block text-gray-700 mb-2", "Tamanho do tile (px):" }
+                                    input {
+                                        class: "w-full px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        r#type: "number",
+                                        value: "{ladrilhamento().tamanho}",
+                                        min: "320",
+                                        step: "32",
+                                        onchange: move |e| {
+                                            if let Ok(val) = e.value().parse::<u32>() {
+                                                ladrilhamento.write().tamanho = val;
+                                                if let Err(e) = salvar_ladrilhamento(ladrilhamento()) {
+                                                    status.set(format!("Erro ao salvar configuração de tiles: {}", e));
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                                div { class: "flex-1",
+                                    label { class: "block text-gray-700 mb-2", "Sobreposição entre tiles (%):" }
+                                    input {
+                                        class: "w-full px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        r#type: "number",
+                                        value: "{(ladrilhamento().sobreposicao * 100.0).round()}",
+                                        min: "0",
+                                        max: "50",
+                                        step: "5",
+                                        onchange: move |e| {
+                                            if let Ok(val) = e.value().parse::<f64>() {
+                                                ladrilhamento.write().sobreposicao = (val / 100.0).clamp(0.0, 0.5);
+                                                if let Err(e) = salvar_ladrilhamento(ladrilhamento()) {
+                                                    status.set(format!("Erro ao salvar configuração de tiles: {}", e));
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    div { class: "mb-6",
+                        label { class: "flex items-center gap-2 text-gray-700 mb-2",
+                            input {
+                                r#type: "checkbox",
+                                checked: classificacao().ativa,
+                                onchange: move |e| {
+                                    classificacao.write().ativa = e.checked();
+                                    if let Err(e) = salvar_classificacao(classificacao()) {
+                                        status.set(format!("Erro ao salvar configuração da classificação: {}", e));
+                                    }
+                                }
+                            }
+                            "Rodar também o modelo de classificação (probabilidade de cada tipo de fissura por imagem)"
+                        }
+                        if classificacao().ativa {
+                            div { class: "flex gap-4",
+                                div { class: "flex-1",
+                                    label { class: "block text-gray-700 mb-2", "Classificar:" }
+                                    select {
+                                        class: "w-full px-4 py-2 border border-gray-300 rounded-md bg-white focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        value: classificacao().modo.argumento_script(),
+                                        onchange: move |e| {
+                                            classificacao.write().modo = if e.value() == "recortes" { ModoClassificacao::Recortes } else { ModoClassificacao::Imagem };
+                                            if let Err(e) = salvar_classificacao(classificacao()) {
+                                                status.set(format!("Erro ao salvar configuração da classificação: {}", e));
+                                            }
+                                        },
+                                        option { value: "imagem", "Imagem inteira" }
+                                        option { value: "recortes", "Cada caixa detectada" }
+                                    }
+                                }
+                                div { class: "flex-1",
+                                    label { class: "block text-gray-700 mb-2", "Probabilidade mínima (%):" }
+                                    input {
+                                        class: "w-full px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        r#type: "number",
+                                        value: "{(classificacao().confianca_minima * 100.0).round()}",
+                                        min: "0",
+                                        max: "100",
+                                        step: "5",
+                                        onchange: move |e| {
+                                            if let Ok(val) = e.value().parse::<f64>() {
+                                                classificacao.write().confianca_minima = (val / 100.0).clamp(0.0, 1.0);
+                                                if let Err(e) = salvar_classificacao(classificacao()) {
+                                                    status.set(format!("Erro ao salvar configuração da classificação: {}", e));
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    div { class: "mb-6",
+                        label { class: "block text-gray-700 mb-2", "Escala para medir as fissuras em milímetros:" }
+                        select {
+                            class: "w-full px-4 py-2 border border-gray-300 rounded-md bg-white focus:outline-none focus:ring-2 focus:ring-blue-500 mb-2",
+                            value: match escala().metodo { MetodoEscala::Nenhuma => "nenhuma", MetodoEscala::Marcador => "marcador", MetodoEscala::Camera => "camera" },
+                            onchange: move |e| {
+                                escala.write().metodo = match e.value().as_str() {
+                                    "marcador" => MetodoEscala::Marcador,
+                                    "camera" => MetodoEscala::Camera,
+                                    _ => MetodoEscala::Nenhuma,
+                                };
+                                if let Err(e) = salvar_escala(escala()) {
+                                    status.set(format!("Erro ao salvar escala: {}", e));
+                                }
+                            },
+                            option { value: "nenhuma", "Sem escala (medidas em pixels)" }
+                            option { value: "marcador", "Marcador de referência nas fotos" }
+                            option { value: "camera", "Distância e dados da câmera" }
+                        }
+                        if escala().metodo == MetodoEscala::Marcador {
+                            div { class: "flex gap-4",
+                                div { class: "flex-1",
+                                    label { class: "block text-gray-700 mb-2", "Tamanho do marcador (mm):" }
+                                    input {
+                                        class: "w-full px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        r#type: "number",
+                                        value: "{escala().marcador_mm}",
+                                        min: "0",
+                                        step: "any",
+                                        onchange: move |e| {
+                                            if let Ok(val) = e.value().parse::<f64>() {
+                                                escala.write().marcador_mm = val;
+                                                if let Err(e) = salvar_escala(escala()) {
+                                                    status.set(format!("Erro ao salvar escala: {}", e));
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                                div { class: "flex-1",
+                                    label { class: "block text-gray-700 mb-2", "Tamanho na imagem (px):" }
+                                    input {
+                                        class: "w-full px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        r#type: "number",
+                                        value: "{escala().marcador_px}",
+                                        min: "0",
+                                        step: "any",
+                                        onchange: move |e| {
+                                            if let Ok(val) = e.value().parse::<f64>() {
+                                                escala.write().marcador_px = val;
+                                                if let Err(e) = salvar_escala(escala()) {
+                                                    status.set(format!("Erro ao salvar escala: {}", e));
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        if escala().metodo == MetodoEscala::Camera {
+                            div { class: "flex gap-4",
+                                div { class: "flex-1",
+                                    label { class: "block text-gray-700 mb-2", "Distância até a fachada (m):" }
+                                    input {
+                                        class: "w-full px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        r#type: "number",
+                                        value: "{escala().distancia_mm / 1000.0}",
+                                        min: "0",
+                                        step: "any",
+                                        onchange: move |e| {
+                                            if let Ok(val) = e.value().parse::<f64>() {
+                                                escala.write().distancia_mm = val * 1000.0;
+                                                if let Err(e) = salvar_escala(escala()) {
+                                                    status.set(format!("Erro ao salvar escala: {}", e));
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                                div { class: "flex-1",
+                                    label { class: "block text-gray-700 mb-2", "Distância focal (mm):" }
+                                    input {
+                                        class: "w-full px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        r#type: "number",
+                                        value: "{escala().focal_mm}",
+                                        min: "0",
+                                        step: "any",
+                                        onchange: move |e| {
+                                            if let Ok(val) = e.value().parse::<f64>() {
+                                                escala.write().focal_mm = val;
+                                                if let Err(e) = salvar_escala(escala()) {
+                                                    status.set(format!("Erro ao salvar escala: {}", e));
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                                div { class: "flex-1",
+                                    label { class: "block text-gray-700 mb-2", "Largura do sensor (mm):" }
+                                    input {
+                                        class: "w-full px-4 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500",
+                                        r#type: "number",
+                                        value: "{escala().largura_sensor_mm}",
+                                        min: "0",
+                                        step: "any",
+                                        onchange: move |e| {
+                                            if let Ok(val) = e.value().parse::<f64>() {
+                                                escala.write().largura_sensor_mm = val;
+                                                if let Err(e) = salvar_escala(escala()) {
+                                                    status.set(format!("Erro ao salvar escala: {}", e));
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    if let Some(nome) = nome_projeto_atual() {
+                        div { class: "mb-6 flex items-center justify-between gap-4",
+                            span { class: "text-gray-700",
+                                "Modelo de detecção: "
+                                span { class: "font-semibold", {modelo_do_projeto(&nome)} }
+                            }
+                            button {
+                                class: "px-4 py-2 bg-gray-200 text-gray-800 rounded-md hover:bg-gray-300 flex items-center gap-2",
+                                onclick: {
+                                    let nome = nome.clone();
+                                    move |_| { navigator.push(AppRoute::ModelRegistry { project_name: nome.clone() }); }
+                                },
+                                i { class: "material-icons", "memory" }
+                                "Gerenciar modelos"
+                            }
+                        }
+                    }
 
                     if !project_name_available() {
                         p { class: "text-center text-red-500 mb-4 py-2 px-4 border border-red-300 bg-red-50 rounded-md",
@@ -117,6 +428,18 @@ pub fn Home() -> Element {
                             status.set("Erro: Crie um projeto antes de processar.".to_string());
                             return;
                         }
+                        if let Err(e) = salvar_ladrilhamento(ladrilhamento()) {
+                            status.set(format!("Erro ao salvar configuração de tiles: {}", e));
+                            return;
+                        }
+                        if let Err(e) = salvar_classificacao(classificacao()) {
+                            status.set(format!("Erro ao salvar configuração da classificação: {}", e));
+                            return;
+                        }
+                        if let Err(e) = salvar_escala(escala()) {
+                            status.set(format!("Erro ao salvar escala: {}", e));
+                            return;
+                        }
                         is_processing.set(true);
                         status.set("Processando imagens...".to_string());
                         
@@ -210,6 +533,18 @@ pub fn Home() -> Element {
                                 p { class: "text-gray-700", "Imagens sem GPS: {stats_data.images_without_gps}" }
                                 p { class: "text-gray-700", "Imagens com direção: {stats_data.images_with_direction}" }
                                 p { class: "text-gray-700", "Prédios identificados: {stats_data.predio_groups}" }
+                                p { class: "text-gray-700", "Possíveis duplicatas: {stats_data.duplicate_images}" }
+                                p { class: "text-gray-700", "Imagens reprovadas na qualidade: {stats_data.low_quality_images}" }
+                            }
+                            if !stats_data.quality_warnings.is_empty() {
+                                div { class: "mt-4",
+                                    h3 { class: "text-xl font-semibold mb-2 text-yellow-600", "Avisos de qualidade:" }
+                                    ul { class: "list-disc list-inside text-yellow-700",
+                                        {stats_data.quality_warnings.iter().map(|aviso| rsx! {
+                                    li { "{aviso}" }
+                                })}
+                                    }
+                                }
                             }
                             if !stats_data.errors.is_empty() {
                                 div { class: "mt-4",
@@ -231,6 +566,46 @@ pub fn Home() -> Element {
                                     i { class: "material-icons", "folder" }
                                     "Visualizar Pastas Organizadas"
                                 }
+                                button {
+                                    class: "mt-4 px-6 py-3 bg-blue-600 text-white rounded-md hover:bg-blue-700 flex items-center gap-2",
+                                    onclick: move |_| {
+                                        if let Ok(guard) = PROJECT_NAME.try_read() {
+                                            if let Some(name) = &*guard {
+                                                navigator.push(AppRoute::GroupingReview { project_name: name.clone() });
+                                            }
+                                        }
+                                    },
+                                    i { class: "material-icons", "photo_library" }
+                                    "Revisar Agrupamento"
+                                }
+                                if stats_data.duplicate_images > 0 {
+                                    button {
+                                        class: "mt-4 px-6 py-3 bg-blue-600 text-white rounded-md hover:bg-blue-700 flex items-center gap-2",
+                                        onclick: move |_| {
+                                            if let Ok(guard) = PROJECT_NAME.try_read() {
+                                                if let Some(name) = &*guard {
+                                                    navigator.push(AppRoute::DuplicateReview { project_name: name.clone() });
+                                                }
+                                            }
+                                        },
+                                        i { class: "material-icons", "filter_none" }
+                                        "Revisar Duplicatas"
+                                    }
+                                }
+                                if stats_data.low_quality_images > 0 {
+                                    button {
+                                        class: "mt-4 px-6 py-3 bg-blue-600 text-white rounded-md hover:bg-blue-700 flex items-center gap-2",
+                                        onclick: move |_| {
+                                            if let Ok(guard) = PROJECT_NAME.try_read() {
+                                                if let Some(name) = &*guard {
+                                                    navigator.push(AppRoute::QualityReview { project_name: name.clone() });
+                                                }
+                                            }
+                                        },
+                                        i { class: "material-icons", "high_quality" }
+                                        "Revisar Qualidade"
+                                    }
+                                }
                             }
                         }
                     }
@@ -0,0 +1,248 @@
+// versoes_relatorio.rs
+// Histórico de versões de cada relatório, em Report/<projeto>/versoes/. Cada versão guarda o
+// Markdown gerado e o SHA-256 de cada entrada usada para gerá-lo (detecções, revisão, configurações,
+// índice, classificação, medidas, dados do projeto, registro de modelos e template), recortadas no
+// prédio quando o relatório cobre um só. O relatório é gerado de novo quando alguma entrada muda ou
+// quando o usuário pede; as versões anteriores ficam disponíveis para consulta e comparação linha a
+// linha. Edições feitas no editor do app entram no
+// histórico como versões próprias antes de serem substituídas por uma nova geração.
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::collections::BTreeMap;
+use anyhow::{Result, Context, anyhow};
+use chrono::Local;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use crate::classificacao::CLASSIFICATION_FILE_NAME;
+use crate::deteccoes::DETECTION_FILE_NAME;
+use crate::geometria::MEASUREMENTS_FILE_NAME;
+use crate::manifesto::MANIFEST_FILE_NAME;
+use crate::modelos::{self, REGISTRY_FILE_NAME};
+use crate::image_processor::Predio;
+use crate::project_index::{ProjectIndex, INDEX_FILE_NAME};
+use crate::project_settings::SETTINGS_FILE_NAME;
+use crate::revisao::REVIEW_FILE_NAME;
+
+pub const HISTORY_DIR_NAME: &str = "versoes";
+
+// Chave usada para o template nas entradas (os demais são nomes de arquivo)
+const ENTRADA_TEMPLATE: &str = "template";
+// Hash de uma entrada cujo arquivo não existe (ex: projeto sem classificação)
+const ENTRADA_AUSENTE: &str = "ausente";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VersaoRelatorio {
+    pub numero: u32,
+    pub gerada_em: String, // "YYYY-MM-DD HH:MM:SS"
+    pub arquivo: String, // Nome do .md dentro de versoes/
+    // Entrada -> SHA-256; vazio na versão importada de um relatório anterior ao histórico
+    #[serde(default)]
+    pub entradas: BTreeMap<String, String>,
+    // Versão salva a partir do editor do app, não gerada pelo template
+    #[serde(default)]
+    pub editada: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HistoricoRelatorio {
+    #[serde(default)]
+    pub versoes: Vec<VersaoRelatorio>,
+}
+
+fn caminho_historico(report_dir: &Path, nome_base: &str) -> PathBuf {
+    report_dir.join(HISTORY_DIR_NAME).join(format!("{}.json", nome_base))
+}
+
+impl HistoricoRelatorio {
+    // Relatório sem histórico devolve um histórico vazio
+    pub fn load(report_dir: &Path, nome_base: &str) -> Result<Self> {
+        let path = caminho_historico(report_dir, nome_base);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Falha ao ler {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Falha ao parsear {}", path.display()))
+    }
+
+    fn save(&self, report_dir: &Path, nome_base: &str) -> Result<()> {
+        let path = caminho_historico(report_dir, nome_base);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Falha ao salvar {}", path.display()))
+    }
+
+    pub fn atual(&self) -> Option<&VersaoRelatorio> {
+        self.versoes.last()
+    }
+
+    pub fn ler(&self, report_dir: &Path, numero: u32) -> Result<String> {
+        let versao = self.versoes
+            .iter()
+            .find(|v| v.numero == numero)
+            .ok_or_else(|| anyhow!("Versão {} não encontrada no histórico", numero))?;
+        let path = report_dir.join(HISTORY_DIR_NAME).join(&versao.arquivo);
+        fs::read_to_string(&path).with_context(|| format!("Falha ao ler {}", path.display()))
+    }
+
+    // Grava o conteúdo como uma nova versão e atualiza o histórico
+    pub fn registrar(&mut self, report_dir: &Path, nome_base: &str, conteudo: &str, entradas: BTreeMap<String, String>, editada: bool) -> Result<&VersaoRelatorio> {
+        let dir = report_dir.join(HISTORY_DIR_NAME);
+        fs::create_dir_all(&dir).with_context(|| format!("Falha ao criar {}", dir.display()))?;
+        let numero = self.atual().map_or(1, |v| v.numero + 1);
+        let arquivo = format!("{}-v{}.md", nome_base, numero);
+        fs::write(dir.join(&arquivo), conteudo)
+            .with_context(|| format!("Falha ao salvar {}", dir.join(&arquivo).display()))?;
+        self.versoes.push(VersaoRelatorio {
+            numero,
+            gerada_em: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            arquivo,
+            entradas,
+            editada,
+        });
+        self.save(report_dir, nome_base)?;
+        Ok(self.versoes.last().expect("versão recém-registrada"))
+    }
+}
+
+fn hash_texto(texto: &str) -> String {
+    Sha256::digest(texto.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Parte de um arquivo do projeto que pertence a um prédio, serializada para o hash
+fn recorte_do_predio(project_dir: &Path, nome: &str, index: &ProjectIndex, predio: &Predio) -> Result<Option<String>> {
+    let path = project_dir.join(nome);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let do_predio = |caminho: &str| index.localizar(Path::new(caminho)).is_some_and(|(p, _, _)| p.id == predio.id);
+    let recorte = match nome {
+        INDEX_FILE_NAME => serde_json::json!({
+            "predio": predio,
+            "excluir_duplicatas": index.excluir_duplicatas,
+            "excluir_baixa_qualidade": index.excluir_baixa_qualidade,
+        }),
+        DETECTION_FILE_NAME | CLASSIFICATION_FILE_NAME => {
+            let content = fs::read_to_string(&path).with_context(|| format!("Falha ao ler {}", path.display()))?;
+            let itens: Vec<serde_json::Value> = serde_json::from_str(&content)
+                .with_context(|| format!("Falha ao parsear {}", path.display()))?;
+            serde_json::Value::Array(
+                itens.into_iter().filter(|item| item["path"].as_str().is_some_and(do_predio)).collect(),
+            )
+        }
+        // Imagens por caminho relativo à pasta images ("<prédio>/<fachada>/<arquivo>")
+        REVIEW_FILE_NAME | MEASUREMENTS_FILE_NAME => {
+            let content = fs::read_to_string(&path).with_context(|| format!("Falha ao ler {}", path.display()))?;
+            let valor: serde_json::Value = serde_json::from_str(&content)
+                .with_context(|| format!("Falha ao parsear {}", path.display()))?;
+            let prefixo = format!("{}/", predio.id);
+            let imagens: serde_json::Map<String, serde_json::Value> = valor["imagens"]
+                .as_object()
+                .map(|imagens| imagens.iter().filter(|(chave, _)| chave.starts_with(&prefixo)).map(|(c, v)| (c.clone(), v.clone())).collect())
+                .unwrap_or_default();
+            serde_json::Value::Object(imagens)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(serde_json::to_string(&recorte)?))
+}
+
+// SHA-256 de cada entrada do relatório. No relatório de um prédio (`predio` = id ou nome) detecções,
+// revisão, índice, classificação e medidas entram só com a parte daquele prédio, para mudanças em
+// outro prédio não gerarem versões novas; no resumo do projeto (None) entram os arquivos inteiros.
+pub fn hashes_entradas(project_dir: &Path, predio: Option<&str>, template: &str) -> BTreeMap<String, String> {
+    let arquivos = [
+        DETECTION_FILE_NAME,
+        REVIEW_FILE_NAME,
+        SETTINGS_FILE_NAME,
+        INDEX_FILE_NAME,
+        CLASSIFICATION_FILE_NAME,
+        MEASUREMENTS_FILE_NAME,
+        MANIFEST_FILE_NAME,
+    ];
+    let index = ProjectIndex::abrir(project_dir).unwrap_or_default();
+    let predio = predio.and_then(|p| index.buscar(p));
+    let mut entradas: BTreeMap<String, String> = arquivos
+        .iter()
+        .map(|nome| {
+            let path = project_dir.join(nome);
+            let recorte = predio.and_then(|predio| recorte_do_predio(project_dir, nome, &index, predio).unwrap_or_default());
+            let hash = match recorte {
+                Some(texto) => hash_texto(&texto),
+                None if path.exists() => modelos::checksum(&path).unwrap_or_default(),
+                None => ENTRADA_AUSENTE.to_string(),
+            };
+            (nome.to_string(), hash)
+        })
+        .collect();
+    let registro = modelos::models_dir().join(REGISTRY_FILE_NAME);
+    let hash_registro = if registro.exists() { modelos::checksum(&registro).unwrap_or_default() } else { ENTRADA_AUSENTE.to_string() };
+    entradas.insert(REGISTRY_FILE_NAME.to_string(), hash_registro);
+    entradas.insert(ENTRADA_TEMPLATE.to_string(), hash_texto(template));
+    entradas
+}
+
+// Nome legível de uma entrada para a tela de histórico
+pub fn rotulo_entrada(entrada: &str) -> &str {
+    match entrada {
+        DETECTION_FILE_NAME => "detecções",
+        REVIEW_FILE_NAME => "revisão",
+        SETTINGS_FILE_NAME => "configurações",
+        INDEX_FILE_NAME => "índice de prédios",
+        CLASSIFICATION_FILE_NAME => "classificação",
+        MEASUREMENTS_FILE_NAME => "medidas",
+        MANIFEST_FILE_NAME => "dados do projeto",
+        REGISTRY_FILE_NAME => "registro de modelos",
+        ENTRADA_TEMPLATE => "template",
+        outra => outra,
+    }
+}
+
+// Entradas com hash diferente (ou presentes só em um dos lados)
+pub fn entradas_alteradas<'a>(antes: &'a BTreeMap<String, String>, depois: &'a BTreeMap<String, String>) -> Vec<&'a str> {
+    let mut alteradas: Vec<&str> = depois
+        .iter()
+        .filter(|(entrada, hash)| antes.get(*entrada) != Some(*hash))
+        .map(|(entrada, _)| entrada.as_str())
+        .collect();
+    alteradas.extend(antes.keys().filter(|e| !depois.contains_key(*e)).map(|e| e.as_str()));
+    alteradas
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinhaDiff {
+    Igual(String),
+    Removida(String),
+    Adicionada(String),
+}
+
+// Diferença linha a linha pela maior subsequência comum
+pub fn diff_linhas(antigo: &str, novo: &str) -> Vec<LinhaDiff> {
+    let a: Vec<&str> = antigo.lines().collect();
+    let b: Vec<&str> = novo.lines().collect();
+    // comum[i][j] = tamanho da maior subsequência comum de a[i..] e b[j..]
+    let mut comum = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            comum[i][j] = if a[i] == b[j] { comum[i + 1][j + 1] + 1 } else { comum[i + 1][j].max(comum[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut linhas = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            linhas.push(LinhaDiff::Igual(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if comum[i + 1][j] >= comum[i][j + 1] {
+            linhas.push(LinhaDiff::Removida(a[i].to_string()));
+            i += 1;
+        } else {
+            linhas.push(LinhaDiff::Adicionada(b[j].to_string()));
+            j += 1;
+        }
+    }
+    linhas.extend(a[i..].iter().map(|l| LinhaDiff::Removida(l.to_string())));
+    linhas.extend(b[j..].iter().map(|l| LinhaDiff::Adicionada(l.to_string())));
+    linhas
+}